@@ -1,5 +1,8 @@
-use ark_bn254::Fr;
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::UniformRand;
 use ark_fflonk_verifier::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+use ark_fflonk_verifier::pairing::{ArkworksBackend, DesignatedVerifierBackend, PairingBackend};
 use ark_fflonk_verifier::proof::Proof;
 use ark_fflonk_verifier::transcript::Keccak256TranscriptHash;
 use ark_fflonk_verifier::verifier::fflonk_verifier;
@@ -20,6 +23,23 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("fflonk_verifier_with_recursive_verifier", |b| {
         b.iter(|| fflonk_verifier::<Keccak256TranscriptHash>(&vk, &proof, &pub_input, true))
     });
+
+    // the pairing check vs the designated verifier one, A1 = x·W2
+    let rng = &mut ark_std::test_rng();
+    let x = Fr::rand(rng);
+    let vk = VerificationKey {
+        x2: (G2Affine::generator() * x).into_affine(),
+        ..Default::default()
+    };
+    let w2 = G1Affine::rand(rng);
+    let a1 = (w2 * x).into_affine();
+    let designated = DesignatedVerifierBackend::new(&vk, x).unwrap();
+    c.bench_function("check_pairing", |b| {
+        b.iter(|| ArkworksBackend.check_pairing(&vk, &a1, &w2))
+    });
+    c.bench_function("check_pairing_designated_verifier", |b| {
+        b.iter(|| designated.check_pairing(&vk, &a1, &w2))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
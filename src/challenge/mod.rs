@@ -1,44 +1,132 @@
 pub mod root;
 
+use crate::curve::{field_to_bytes_be, point_to_bytes_be};
 use crate::vk::VerificationKey;
-use ark_bn254::{Fr, G1Affine};
-use ark_ff::{BigInteger, Field, PrimeField};
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, Zero};
 use num_bigint::BigInt;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 use crate::challenge::root::Roots;
 use crate::proof::{Evaluations, Proof};
-use crate::transcript::TranscriptHash;
+use crate::transcript::{
+    bind_context, Blake3TranscriptHash, FiatShamirProfile, Keccak256TranscriptHash, TranscriptHash,
+    TranscriptKind, STRONG_FS_DOMAIN,
+};
 use crate::utils::compute_zero_poly_evals;
 use ark_ec::CurveGroup;
 use std::str::FromStr;
 
-#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
-pub struct Challenges {
-    pub alpha: Fr,
-    pub beta: Fr,
-    pub gamma: Fr,
-    pub y: Fr,
-    pub xi: Fr,
-    pub zh: Fr,
-    pub roots: Roots,
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Challenges<E: Pairing = Bn254> {
+    #[serde(with = "crate::serde::fr")]
+    pub alpha: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub beta: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub gamma: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub y: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub xi: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub zh: E::ScalarField,
+    pub roots: Roots<E>,
 }
-impl Challenges {
+
+impl<E: Pairing> Default for Challenges<E> {
+    fn default() -> Self {
+        Challenges {
+            alpha: E::ScalarField::zero(),
+            beta: E::ScalarField::zero(),
+            gamma: E::ScalarField::zero(),
+            y: E::ScalarField::zero(),
+            xi: E::ScalarField::zero(),
+            zh: E::ScalarField::zero(),
+            roots: Roots::default(),
+        }
+    }
+}
+
+impl<E: Pairing> Challenges<E> {
     // compute challenge, roots and zero_poly_eval zh:
     //  beta, gamma, xi, alpha and y ∈ F, h1w4/h2w3/h3w3 roots, xiN and zh(xi)
-    pub fn compute<T: TranscriptHash>(vk: &VerificationKey, proof: &Proof, pub_input: &Fr) -> Self {
+    pub fn compute<T: TranscriptHash>(
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> Self {
+        Self::compute_with_profile::<T>(FiatShamirProfile::SnarkJS, vk, proof, pub_input)
+    }
+
+    // compute challenge under the given Fiat-Shamir profile, which only differs in beta.
+    pub fn compute_with_profile<T: TranscriptHash>(
+        profile: FiatShamirProfile,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> Self {
+        Self::compute_with_context::<T>(profile, &[], vk, proof, pub_input)
+    }
+
+    // compute challenge under the given Fiat-Shamir profile, with the caller-supplied `context`
+    // (eg: chain id, contract address or session nonce) absorbed before beta is squeezed.
+    // The empty context absorbs nothing, see `bind_context`.
+    pub fn compute_with_context<T: TranscriptHash>(
+        profile: FiatShamirProfile,
+        context: &[u8],
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> Self {
         // Compute challenges beta,gamma,xi,alpha,y ∈ 𝐹 as in prover description, from the common inputs, public input, and the elements of 𝜋_SNARK
 
-        // 1 compute beta: keccak_hash with c0, pub_input, c1
-        let beta = Self::compute_beta::<T>(&vk.c0, &proof.polynomials.c1.into_affine(), pub_input);
+        // 1 compute beta
+        let c1 = proof.polynomials.c1.into_affine();
+        let preimage = Self::beta_preimage_with_profile::<T>(profile, vk, &c1, pub_input);
+        let beta = T::hash_to_fr(bind_context(context, preimage));
+
+        Self::compute_from_beta::<T>(vk, proof, beta)
+    }
+
+    // build challenges from externally supplied values, eg: the ones of a Solidity trace,
+    // deriving xi = xi_seed^24, the roots and zh(xi) as the transcript does.
+    pub fn from_values(
+        vk: &VerificationKey<E>,
+        beta: E::ScalarField,
+        gamma: E::ScalarField,
+        xi_seed: E::ScalarField,
+        alpha: E::ScalarField,
+        y: E::ScalarField,
+    ) -> Self {
+        let xi = xi_seed.pow([24]);
+
+        Challenges {
+            alpha,
+            beta,
+            gamma,
+            y,
+            xi,
+            zh: compute_zero_poly_evals(&xi, &vk.n),
+            roots: Roots::compute(vk, &xi_seed),
+        }
+    }
+
+    // compute the rest challenges, roots and zh once beta is squeezed.
+    pub fn compute_from_beta<T: TranscriptHash>(
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        beta: E::ScalarField,
+    ) -> Self {
         // 2. compute gamma: keccak_hash with beta
         let gamma = Self::compute_gamma::<T>(&beta);
 
         // 3. compute xi
         //      compute xi_seed: keccak_hash with gamma,c2
         let xi_seed = Self::compute_xiseed::<T>(&gamma, proof.polynomials.c2.into_affine());
-        //      compute xi=xi_seeder^24
-        let xi = xi_seed.pow([24]);
 
         // 4. compute alpha: keccak_hash with xi_seed, eval_lines
         let alpha = Self::compute_alpha::<T>(&xi_seed, &proof.evaluations);
@@ -46,70 +134,134 @@ impl Challenges {
         // 5. compute y: keccak_hash with alpha, w1
         let y = Self::compute_y::<T>(&alpha, &proof.polynomials.w1.into_affine());
 
-        Challenges {
-            alpha,
-            beta,
-            gamma,
-            y,
-            xi,
-            zh: compute_zero_poly_evals(&xi, &vk.n),
-            roots: Roots::compute(vk, &xi_seed),
+        //      compute xi=xi_seeder^24, roots and zh
+        Self::from_values(vk, beta, gamma, xi_seed, alpha, y)
+    }
+
+    // compute challenges with the transcript hash selected at runtime.
+    pub fn compute_with_transcript(
+        kind: TranscriptKind,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> Self {
+        match kind {
+            TranscriptKind::Keccak256 => {
+                Self::compute::<Keccak256TranscriptHash>(vk, proof, pub_input)
+            }
+            TranscriptKind::Blake3 => Self::compute::<Blake3TranscriptHash>(vk, proof, pub_input),
         }
     }
 
     // compute beta: keccak_hash with c0, pub_input, c1
-    pub fn compute_beta<T: TranscriptHash>(c0: &G1Affine, c1: &G1Affine, pub_input: &Fr) -> Fr {
-        let concatenated = vec![
-            c0.x.into_bigint().to_bytes_be(),
-            c0.y.into_bigint().to_bytes_be(),
-            pub_input.into_bigint().to_bytes_be(),
-            c1.x.into_bigint().to_bytes_be(),
-            c1.y.into_bigint().to_bytes_be(),
+    pub fn compute_beta<T: TranscriptHash>(
+        c0: &E::G1Affine,
+        c1: &E::G1Affine,
+        pub_input: &E::ScalarField,
+    ) -> E::ScalarField {
+        T::hash_to_fr(Self::beta_preimage(c0, c1, pub_input))
+    }
+
+    // the bytes hashed into beta: c0 || pub_input || c1
+    pub fn beta_preimage(
+        c0: &E::G1Affine,
+        c1: &E::G1Affine,
+        pub_input: &E::ScalarField,
+    ) -> Vec<u8> {
+        vec![
+            point_to_bytes_be(c0),
+            field_to_bytes_be(pub_input),
+            point_to_bytes_be(c1),
         ]
         .into_iter()
         .flatten()
-        .collect::<Vec<_>>();
-        T::hash_to_fr(concatenated)
+        .collect::<Vec<_>>()
+    }
+
+    // the bytes hashed into beta under the given Fiat-Shamir profile, for the verifier and the prover.
+    pub fn beta_preimage_with_profile<T: TranscriptHash>(
+        profile: FiatShamirProfile,
+        vk: &VerificationKey<E>,
+        c1: &E::G1Affine,
+        pub_input: &E::ScalarField,
+    ) -> Vec<u8> {
+        match profile {
+            // keccak_hash with c0, pub_input, c1
+            FiatShamirProfile::SnarkJS => Self::beta_preimage(&vk.c0, c1, pub_input),
+            // hash with vk digest, pub_inputs, c1
+            FiatShamirProfile::Strong => Self::beta_strong_preimage::<T>(vk, c1, &[*pub_input]),
+        }
+    }
+
+    // compute beta under the strong Fiat-Shamir profile: hash with
+    //      STRONG_FS_DOMAIN || vk.digest() || len(pub_inputs) || pub_inputs || c1
+    // so that the first challenge binds the whole verification key and all the public inputs.
+    pub fn compute_beta_strong<T: TranscriptHash>(
+        vk: &VerificationKey<E>,
+        c1: &E::G1Affine,
+        pub_inputs: &[E::ScalarField],
+    ) -> E::ScalarField {
+        T::hash_to_fr(Self::beta_strong_preimage::<T>(vk, c1, pub_inputs))
+    }
+
+    // the bytes hashed into beta under the strong Fiat-Shamir profile.
+    pub fn beta_strong_preimage<T: TranscriptHash>(
+        vk: &VerificationKey<E>,
+        c1: &E::G1Affine,
+        pub_inputs: &[E::ScalarField],
+    ) -> Vec<u8> {
+        let mut concatenated = STRONG_FS_DOMAIN.to_vec();
+        concatenated.extend(field_to_bytes_be(&vk.digest::<T>()));
+        concatenated.extend(field_to_bytes_be(&E::ScalarField::from(
+            pub_inputs.len() as u64
+        )));
+        for pub_input in pub_inputs {
+            concatenated.extend(field_to_bytes_be(pub_input));
+        }
+        concatenated.extend(point_to_bytes_be(c1));
+        concatenated
     }
 
     // 2. compute gamma: keccak_hash with beta
-    pub fn compute_gamma<T: TranscriptHash>(beta: &Fr) -> Fr {
-        let concatenated = beta.into_bigint().to_bytes_be();
+    pub fn compute_gamma<T: TranscriptHash>(beta: &E::ScalarField) -> E::ScalarField {
+        let concatenated = field_to_bytes_be(beta);
         T::hash_to_fr(concatenated)
     }
 
     //  compute xi_seed: hash with gamma,c2
-    pub fn compute_xiseed<T: TranscriptHash>(gamma: &Fr, c2: G1Affine) -> Fr {
-        let concatenated = vec![
-            gamma.into_bigint().to_bytes_be(),
-            c2.x.into_bigint().to_bytes_be(),
-            c2.y.into_bigint().to_bytes_be(),
-        ]
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
+    pub fn compute_xiseed<T: TranscriptHash>(
+        gamma: &E::ScalarField,
+        c2: E::G1Affine,
+    ) -> E::ScalarField {
+        let concatenated = vec![field_to_bytes_be(gamma), point_to_bytes_be(&c2)]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
         T::hash_to_fr(concatenated)
     }
 
     // compute alpha: keccak_hash with xi_seed, eval_lines
-    pub fn compute_alpha<T: TranscriptHash>(xi_seed: &Fr, evaluations: &Evaluations) -> Fr {
+    pub fn compute_alpha<T: TranscriptHash>(
+        xi_seed: &E::ScalarField,
+        evaluations: &Evaluations<E>,
+    ) -> E::ScalarField {
         let concatenated = vec![
-            xi_seed.into_bigint().to_bytes_be(),
-            evaluations.ql.into_bigint().to_bytes_be(),
-            evaluations.qr.into_bigint().to_bytes_be(),
-            evaluations.qm.into_bigint().to_bytes_be(),
-            evaluations.qo.into_bigint().to_bytes_be(),
-            evaluations.qc.into_bigint().to_bytes_be(),
-            evaluations.s1.into_bigint().to_bytes_be(),
-            evaluations.s2.into_bigint().to_bytes_be(),
-            evaluations.s3.into_bigint().to_bytes_be(),
-            evaluations.a.into_bigint().to_bytes_be(),
-            evaluations.b.into_bigint().to_bytes_be(),
-            evaluations.c.into_bigint().to_bytes_be(),
-            evaluations.z.into_bigint().to_bytes_be(),
-            evaluations.zw.into_bigint().to_bytes_be(),
-            evaluations.t1w.into_bigint().to_bytes_be(),
-            evaluations.t2w.into_bigint().to_bytes_be(),
+            field_to_bytes_be(xi_seed),
+            field_to_bytes_be(&evaluations.ql),
+            field_to_bytes_be(&evaluations.qr),
+            field_to_bytes_be(&evaluations.qm),
+            field_to_bytes_be(&evaluations.qo),
+            field_to_bytes_be(&evaluations.qc),
+            field_to_bytes_be(&evaluations.s1),
+            field_to_bytes_be(&evaluations.s2),
+            field_to_bytes_be(&evaluations.s3),
+            field_to_bytes_be(&evaluations.a),
+            field_to_bytes_be(&evaluations.b),
+            field_to_bytes_be(&evaluations.c),
+            field_to_bytes_be(&evaluations.z),
+            field_to_bytes_be(&evaluations.zw),
+            field_to_bytes_be(&evaluations.t1w),
+            field_to_bytes_be(&evaluations.t2w),
         ]
         .into_iter()
         .flatten()
@@ -118,21 +270,20 @@ impl Challenges {
     }
 
     // compute y: keccak_hash with alpha, w1
-    pub fn compute_y<T: TranscriptHash>(alpha: &Fr, w1: &G1Affine) -> Fr {
-        let concatenated = vec![
-            alpha.into_bigint().to_bytes_be(),
-            w1.x.into_bigint().to_bytes_be(),
-            w1.y.into_bigint().to_bytes_be(),
-        ]
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
+    pub fn compute_y<T: TranscriptHash>(
+        alpha: &E::ScalarField,
+        w1: &E::G1Affine,
+    ) -> E::ScalarField {
+        let concatenated = vec![field_to_bytes_be(alpha), point_to_bytes_be(w1)]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
         T::hash_to_fr(concatenated)
     }
 }
 
 #[allow(clippy::to_string_in_format_args)]
-impl fmt::Display for Challenges {
+impl<E: Pairing> fmt::Display for Challenges<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "alpha: {:?}", self.alpha.to_string())?;
         write!(f, "beta: {}", self.beta.to_string())?;
@@ -153,7 +304,8 @@ pub fn decimal_to_hex(decimal_str: &str) -> String {
 mod test {
     use super::*;
 
-    use crate::transcript::Blake3TranscriptHash;
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, PrimeField};
     use num_bigint::BigUint;
     use tiny_keccak::{Hasher, Keccak};
 
@@ -247,7 +399,7 @@ mod test {
 
         assert_eq!(actual, expect);
 
-        let gamma = Challenges::compute_gamma::<Blake3TranscriptHash>(&beta);
+        let gamma = Challenges::<Bn254>::compute_gamma::<Blake3TranscriptHash>(&beta);
         println!("gamma: {:?}", gamma.to_string());
         assert_eq!(gamma, expect);
     }
@@ -400,7 +552,111 @@ mod test {
         .into_iter()
         .flatten()
         .collect::<Vec<_>>();
-        let alpha = Blake3TranscriptHash::hash_to_fr(concatenated);
+        let alpha: Fr = Blake3TranscriptHash::hash_to_fr(concatenated);
         println!("alpha: {:?}", alpha.to_string());
     }
+
+    #[test]
+    fn test_strong_profile_vectors() {
+        use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+        use crate::transcript::Keccak256TranscriptHash;
+
+        let vk = VerificationKey::default();
+        let proof: Proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        let c1 = proof.polynomials.c1.into_affine();
+
+        // vk digest
+        let expect = Fr::from_str(
+            "1412562565441408542147891682075159225272605174793234937293913596897573620193",
+        )
+        .unwrap();
+        assert_eq!(vk.digest::<Keccak256TranscriptHash>(), expect);
+        let expect = Fr::from_str(
+            "17084021949167664552272650953865067596631644368772011592813153212097263567360",
+        )
+        .unwrap();
+        assert_eq!(vk.digest::<Blake3TranscriptHash>(), expect);
+
+        // beta
+        let expect = Fr::from_str(
+            "4553637015062800946442172071537740793359132282828684863045889972250863497667",
+        )
+        .unwrap();
+        let beta =
+            Challenges::compute_beta_strong::<Keccak256TranscriptHash>(&vk, &c1, &[pub_input]);
+        assert_eq!(beta, expect);
+        let expect = Fr::from_str(
+            "19664693155488095294648427213873572458320877783270198676212457238029993572146",
+        )
+        .unwrap();
+        let beta = Challenges::compute_beta_strong::<Blake3TranscriptHash>(&vk, &c1, &[pub_input]);
+        assert_eq!(beta, expect);
+
+        // the rest challenges
+        let challenges = Challenges::compute_with_profile::<Keccak256TranscriptHash>(
+            FiatShamirProfile::Strong,
+            &vk,
+            &proof,
+            &pub_input,
+        );
+        let expect = [
+            "4553637015062800946442172071537740793359132282828684863045889972250863497667",
+            "12724296786974244511683224771361274014394020255437830893829647017660489596573",
+            "19218408286499352930813391099902632857712834467865005336796344060307709677172",
+            "14099061299814350974509833707926781616769842466639422509260989872272832333885",
+            "12875191716193233793236435447435944599144074574660216473243619803263190036289",
+        ]
+        .map(|v| Fr::from_str(v).unwrap());
+        assert_eq!(
+            [
+                challenges.beta,
+                challenges.gamma,
+                challenges.xi,
+                challenges.alpha,
+                challenges.y
+            ],
+            expect
+        );
+
+        // the snarkjs profile is unchanged.
+        assert_eq!(
+            Challenges::compute::<Keccak256TranscriptHash>(&vk, &proof, &pub_input),
+            Challenges::compute_with_profile::<Keccak256TranscriptHash>(
+                FiatShamirProfile::SnarkJS,
+                &vk,
+                &proof,
+                &pub_input
+            )
+        );
+    }
+
+    #[test]
+    fn test_strong_profile_binds_vk() {
+        use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+        use crate::transcript::Keccak256TranscriptHash;
+
+        let vk = VerificationKey::default();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+
+        let mut other_vk = vk.clone();
+        other_vk.k1 = Fr::from(5);
+
+        // snarkjs's beta doesn't absorb k1, the strong one does.
+        let compute = |profile, vk| {
+            Challenges::compute_with_profile::<Keccak256TranscriptHash>(
+                profile, vk, &proof, &pub_input,
+            )
+            .beta
+        };
+        assert_eq!(
+            compute(FiatShamirProfile::SnarkJS, &vk),
+            compute(FiatShamirProfile::SnarkJS, &other_vk)
+        );
+        assert_ne!(
+            compute(FiatShamirProfile::Strong, &vk),
+            compute(FiatShamirProfile::Strong, &other_vk)
+        );
+    }
 }
@@ -1,23 +1,42 @@
-use ark_bn254::Fr;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 use crate::vk::VerificationKey;
 use std::ops::Mul;
 
 //
-#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
-pub struct Roots {
-    pub h0w8: [Fr; 8],
-    pub h1w4: [Fr; 4],
-    pub h2w3: [Fr; 3],
-    pub h3w3: [Fr; 3],
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Roots<E: Pairing = Bn254> {
+    #[serde(with = "crate::serde::fr_array")]
+    pub h0w8: [E::ScalarField; 8],
+    #[serde(with = "crate::serde::fr_array")]
+    pub h1w4: [E::ScalarField; 4],
+    #[serde(with = "crate::serde::fr_array")]
+    pub h2w3: [E::ScalarField; 3],
+    #[serde(with = "crate::serde::fr_array")]
+    pub h3w3: [E::ScalarField; 3],
 }
 
-impl Roots {
-    pub fn compute(vk: &VerificationKey, xi_seed: &Fr) -> Self {
+impl<E: Pairing> Default for Roots<E> {
+    fn default() -> Self {
+        Roots {
+            h0w8: [E::ScalarField::zero(); 8],
+            h1w4: [E::ScalarField::zero(); 4],
+            h2w3: [E::ScalarField::zero(); 3],
+            h3w3: [E::ScalarField::zero(); 3],
+        }
+    }
+}
+
+impl<E: Pairing> Roots<E> {
+    pub fn compute(vk: &VerificationKey<E>, xi_seed: &E::ScalarField) -> Self {
         // compute xi_seed_2, xi_seed_3
         let xi_seed_2 = xi_seed.mul(xi_seed);
-        let xi_seed_3 = xi_seed * &xi_seed_2;
+        let xi_seed_3 = *xi_seed * xi_seed_2;
 
         // compute roots h0w8
         let omegas = &vk.omega;
@@ -58,7 +77,7 @@ impl Roots {
 }
 
 #[allow(clippy::to_string_in_format_args)]
-impl fmt::Display for Roots {
+impl<E: Pairing> fmt::Display for Roots<E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Roots: [")?;
         write!(f, "h0w8:[")?;
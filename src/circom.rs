@@ -0,0 +1,367 @@
+use crate::prover::PlonkWitness;
+use crate::setup::{PlonkConstraint, SnarkJSCircuit};
+use anyhow::{anyhow, bail, ensure};
+use ark_ff::{BigInteger, Field, PrimeField};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+
+// The sections of the binary files of circom, see https://github.com/iden3/binfileutils
+const R1CS_HEADER: u32 = 1;
+const R1CS_CONSTRAINTS: u32 = 2;
+const WTNS_HEADER: u32 = 1;
+const WTNS_WITNESS: u32 = 2;
+
+/// A linear combination of the wires, the wire 0 being the constant 1.
+pub type LinearCombination<F> = Vec<(usize, F)>;
+
+/// A `.r1cs` file of circom: the constraints A·B - C = 0.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct R1CS<F: PrimeField> {
+    pub n_wires: usize,
+    pub n_pub_out: usize,
+    pub n_pub_in: usize,
+    pub n_prv_in: usize,
+    pub constraints: Vec<[LinearCombination<F>; 3]>,
+}
+
+impl<F: PrimeField> R1CS<F> {
+    pub fn load<P: AsRef<Path>>(r1cs_path: P) -> anyhow::Result<Self> {
+        Self::from_bytes(&std::fs::read(r1cs_path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let sections = read_sections(bytes, b"r1cs")?;
+        let mut header = section(&sections, R1CS_HEADER)?;
+        read_prime::<F>(&mut header)?;
+        let n_wires = header.u32()? as usize;
+        let n_pub_out = header.u32()? as usize;
+        let n_pub_in = header.u32()? as usize;
+        let n_prv_in = header.u32()? as usize;
+        let _n_labels = header.u64()?;
+        let n_constraints = header.u32()? as usize;
+
+        let mut data = section(&sections, R1CS_CONSTRAINTS)?;
+        let mut read_lc = || -> anyhow::Result<LinearCombination<F>> {
+            let n_terms = data.u32()?;
+            (0..n_terms)
+                .map(|_| {
+                    let wire = data.u32()? as usize;
+                    ensure!(
+                        wire < n_wires,
+                        "The wire {wire} is out of the {n_wires} wires"
+                    );
+                    Ok((wire, data.fr()?))
+                })
+                .collect()
+        };
+        let constraints = (0..n_constraints)
+            .map(|_| Ok([read_lc()?, read_lc()?, read_lc()?]))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            n_wires,
+            n_pub_out,
+            n_pub_in,
+            n_prv_in,
+            constraints,
+        })
+    }
+
+    pub fn n_public(&self) -> usize {
+        self.n_pub_out + self.n_pub_in
+    }
+}
+
+/// The values of the wires of a `.wtns` file of circom.
+pub fn load_wtns<F: PrimeField, P: AsRef<Path>>(wtns_path: P) -> anyhow::Result<Vec<F>> {
+    wtns_from_bytes(&std::fs::read(wtns_path)?)
+}
+
+pub fn wtns_from_bytes<F: PrimeField>(bytes: &[u8]) -> anyhow::Result<Vec<F>> {
+    let sections = read_sections(bytes, b"wtns")?;
+    let mut header = section(&sections, WTNS_HEADER)?;
+    read_prime::<F>(&mut header)?;
+    let n_witness = header.u32()?;
+
+    let mut data = section(&sections, WTNS_WITNESS)?;
+    (0..n_witness).map(|_| data.fr()).collect()
+}
+
+/// An extra signal of snarkjs for the linear combinations of more than one wire:
+///      signal = coefs[0]·signals[0] + coefs[1]·signals[1]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlonkAddition<F: PrimeField> {
+    pub signals: [usize; 2],
+    pub coefs: [F; 2],
+}
+
+/// A circom circuit as `snarkjs fflonk setup` converts it to PLONK: the wires of the R1CS come
+/// first, and the `additions` are the signals after them.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CircomCircuit<F: PrimeField> {
+    pub circuit: SnarkJSCircuit<F>,
+    pub additions: Vec<PlonkAddition<F>>,
+}
+
+impl<F: PrimeField> CircomCircuit<F> {
+    /// Every constraint A·B - C = 0 becomes PLONK constraints the way `processR1csConstraint` of
+    /// snarkjs converts it, with kX the constant of X:
+    /// - if A or B is 0, the addition C = 0,
+    /// - if A is the constant kA, the addition kA·B - C = 0, and the same if B is the constant kB,
+    /// - otherwise the multiplication of A, B and C reduced to a single signal sX·cX each:
+    ///      qm = cA·cB, ql = cA·kB, qr = kA·cB, qo = -cC, qc = kA·kB - kC
+    ///
+    /// An addition c1·s1 + c2·s2 + c3·s3 + k = 0 is the row ql = c1, qr = c2, qo = c3, qc = k,
+    /// after its terms are reduced to 3 signals.
+    ///
+    /// The terms are reduced as `reduceCoefs` of snarkjs does, in the order of their wires: while
+    /// there are too many, the first 2 are added into a new signal s = c1·s1 + c2·s2, by the
+    /// constraint -c1·s1 - c2·s2 + s = 0 in the rows before, and s goes after the other terms.
+    pub fn from_r1cs(r1cs: &R1CS<F>) -> Self {
+        let mut circuit = SnarkJSCircuit {
+            n_vars: r1cs.n_wires,
+            n_public: r1cs.n_public(),
+            constraints: vec![],
+        };
+        let mut additions = vec![];
+
+        for [a, b, c] in &r1cs.constraints {
+            let [a, b, c] = [a, b, c].map(normalize_lc);
+            let is_zero = |k: Option<F>| k.is_some_and(|k| k.is_zero());
+            let addition = match (constant_lc(&a), constant_lc(&b)) {
+                (ka, kb) if is_zero(ka) || is_zero(kb) => Some(c.clone()),
+                (Some(ka), _) => Some(join_lc(&b, &c, ka)),
+                (_, Some(kb)) => Some(join_lc(&a, &c, kb)),
+                _ => None,
+            };
+
+            let constraint = match addition {
+                Some(lc) => {
+                    let (k, terms) = reduce_lc(&lc, 3, &mut circuit, &mut additions);
+                    let [(s1, c1), (s2, c2), (s3, c3)] = [terms[0], terms[1], terms[2]];
+                    PlonkConstraint {
+                        signals: [s1, s2, s3],
+                        qm: F::zero(),
+                        ql: c1,
+                        qr: c2,
+                        qo: c3,
+                        qc: k,
+                    }
+                }
+                None => {
+                    let [(ka, (sa, ca)), (kb, (sb, cb)), (kc, (sc, cc))] = [a, b, c].map(|lc| {
+                        let (k, terms) = reduce_lc(&lc, 1, &mut circuit, &mut additions);
+                        (k, terms[0])
+                    });
+                    PlonkConstraint {
+                        signals: [sa, sb, sc],
+                        qm: ca * cb,
+                        ql: ca * kb,
+                        qr: ka * cb,
+                        qo: -cc,
+                        qc: ka * kb - kc,
+                    }
+                }
+            };
+            circuit.constraints.push(constraint);
+        }
+
+        Self { circuit, additions }
+    }
+
+    pub fn load<P: AsRef<Path>>(r1cs_path: P) -> anyhow::Result<Self> {
+        Ok(Self::from_r1cs(&R1CS::load(r1cs_path)?))
+    }
+
+    /// The wires of the rows for the values of the wires of the R1CS, eg: of a `.wtns` file.
+    pub fn witness(&self, wires: &[F]) -> anyhow::Result<PlonkWitness<F>> {
+        let n_wires = self.circuit.n_vars - self.additions.len();
+        ensure!(
+            wires.len() == n_wires,
+            "The witness has {} wires instead of {n_wires}",
+            wires.len()
+        );
+        ensure!(
+            wires.first().is_some_and(|one| one.is_one()),
+            "The wire 0 isn't 1"
+        );
+
+        let mut signals = wires.to_vec();
+        for addition in &self.additions {
+            let [s1, s2] = addition.signals;
+            let [c1, c2] = addition.coefs;
+            signals.push(c1 * signals[s1] + c2 * signals[s2]);
+        }
+        self.circuit.witness(&signals)
+    }
+}
+
+// The linear combination as snarkjs reads it: the terms of the same wire summed, in the order of
+// the wires, and without the terms of coefficient 0.
+fn normalize_lc<F: PrimeField>(lc: &LinearCombination<F>) -> BTreeMap<usize, F> {
+    let mut normalized = BTreeMap::new();
+    for &(wire, coef) in lc {
+        *normalized.entry(wire).or_insert_with(F::zero) += coef;
+    }
+    normalized.retain(|_, coef| !coef.is_zero());
+    normalized
+}
+
+// The constant of the linear combination if it's only a constant, 0 if it's empty.
+fn constant_lc<F: PrimeField>(lc: &BTreeMap<usize, F>) -> Option<F> {
+    match lc.iter().next() {
+        None => Some(F::zero()),
+        Some((0, k)) if lc.len() == 1 => Some(*k),
+        _ => None,
+    }
+}
+
+// k·lc1 - lc2, `joinLinearCombinations` of snarkjs.
+fn join_lc<F: PrimeField>(
+    lc1: &BTreeMap<usize, F>,
+    lc2: &BTreeMap<usize, F>,
+    k: F,
+) -> BTreeMap<usize, F> {
+    let mut joined: BTreeMap<_, _> = lc1.iter().map(|(wire, coef)| (*wire, k * coef)).collect();
+    for (wire, coef) in lc2 {
+        *joined.entry(*wire).or_insert_with(F::zero) -= coef;
+    }
+    joined.retain(|_, coef| !coef.is_zero());
+    joined
+}
+
+// The constant k of the linear combination, and its other terms reduced to `max_terms` terms c·s,
+// the missing ones being 0·s_0, see `CircomCircuit::from_r1cs`.
+fn reduce_lc<F: PrimeField>(
+    lc: &BTreeMap<usize, F>,
+    max_terms: usize,
+    circuit: &mut SnarkJSCircuit<F>,
+    additions: &mut Vec<PlonkAddition<F>>,
+) -> (F, Vec<(usize, F)>) {
+    let k = lc.get(&0).copied().unwrap_or_else(F::zero);
+    let mut terms: VecDeque<_> = lc
+        .iter()
+        .filter(|(wire, _)| **wire != 0)
+        .map(|(wire, coef)| (*wire, *coef))
+        .collect();
+
+    while terms.len() > max_terms {
+        let (s1, c1) = terms.pop_front().unwrap();
+        let (s2, c2) = terms.pop_front().unwrap();
+        let s = circuit.n_vars;
+        circuit.n_vars += 1;
+        circuit.constraints.push(PlonkConstraint {
+            signals: [s1, s2, s],
+            qm: F::zero(),
+            ql: -c1,
+            qr: -c2,
+            qo: F::one(),
+            qc: F::zero(),
+        });
+        additions.push(PlonkAddition {
+            signals: [s1, s2],
+            coefs: [c1, c2],
+        });
+        terms.push_back((s, F::one()));
+    }
+
+    terms.resize(max_terms, (0, F::zero()));
+    (k, terms.into())
+}
+
+// The sections of a binary file of iden3:
+//      magic || version: u32 || n_sections: u32 || (type: u32 || size: u64 || data)*
+pub(crate) fn read_sections<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+) -> anyhow::Result<HashMap<u32, &'a [u8]>> {
+    let mut reader = Reader(bytes);
+    ensure!(
+        reader.take(4)? == magic,
+        "Not a {} file",
+        String::from_utf8_lossy(magic)
+    );
+    let _version = reader.u32()?;
+    let n_sections = reader.u32()?;
+
+    let mut sections = HashMap::new();
+    for _ in 0..n_sections {
+        let section_type = reader.u32()?;
+        let size = reader.u64()?;
+        let data = reader.take(size as usize)?;
+        ensure!(
+            sections.insert(section_type, data).is_none(),
+            "The section {section_type} is duplicated"
+        );
+    }
+    Ok(sections)
+}
+
+pub(crate) fn section<'a>(
+    sections: &HashMap<u32, &'a [u8]>,
+    section_type: u32,
+) -> anyhow::Result<Reader<'a>> {
+    sections
+        .get(&section_type)
+        .map(|data| Reader(data))
+        .ok_or_else(|| anyhow!("The section {section_type} is missing"))
+}
+
+// n8: u32 || prime: n8 bytes, which must be the modulus of F.
+pub(crate) fn read_prime<F: PrimeField>(reader: &mut Reader) -> anyhow::Result<()> {
+    let n8 = reader.u32()? as usize;
+    ensure!(
+        reader.take(n8)? == F::MODULUS.to_bytes_le(),
+        "The prime of the file isn't the modulus of the field"
+    );
+    Ok(())
+}
+
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        ensure!(self.0.len() >= len, "The file is truncated");
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    // A field element in little-endian bytes, which must be canonical.
+    pub(crate) fn fr<F: PrimeField>(&mut self) -> anyhow::Result<F> {
+        let n8 = F::MODULUS.to_bytes_le().len();
+        let bytes = self.take(n8)?;
+        let fr = F::from_le_bytes_mod_order(bytes);
+        if fr.into_bigint().to_bytes_le() != bytes {
+            bail!("The field element isn't canonical");
+        }
+        Ok(fr)
+    }
+
+    // A field element of snarkjs in memory, as ffjavascript keeps it: the Montgomery form
+    // x·R of every base prime field component in little-endian bytes, with R = 2^(8·n8).
+    pub(crate) fn montgomery<F: Field>(&mut self) -> anyhow::Result<F> {
+        let components = (0..F::extension_degree())
+            .map(|_| {
+                let xr = self.fr::<F::BasePrimeField>()?;
+                let n8 = F::BasePrimeField::MODULUS.to_bytes_le().len();
+                let r = F::BasePrimeField::from(2u64).pow([8 * n8 as u64]);
+                Ok(xr * r.inverse().unwrap())
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        F::from_base_prime_field_elems(&components).ok_or_else(|| {
+            anyhow!(
+                "The field element doesn't have {} components",
+                components.len()
+            )
+        })
+    }
+}
@@ -1,34 +1,58 @@
 use crate::challenge::Challenges;
+use crate::curve::SWPoint;
 use crate::inversion::Inversion;
 use crate::proof::Proof;
 use crate::vk::VerificationKey;
-use ark_bn254::{Fr, G1Affine};
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
 use ark_ec::{AffineRepr, CurveGroup};
 use num_traits::One;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
-pub struct FEJ {
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(bound = "E::G1: SWPoint<BaseField = E::BaseField>")]
+pub struct FEJ<E: Pairing = Bn254> {
     // [F]_1: full batched polynomial commitment
-    pub F: G1Affine,
+    #[serde(with = "crate::serde::g1_affine")]
+    pub F: E::G1Affine,
     // [E]_1: group-encoded batch evaluation
-    pub E: G1Affine,
+    #[serde(with = "crate::serde::g1_affine")]
+    pub E: E::G1Affine,
     // [J]_1: the full difference
-    pub J: G1Affine,
+    #[serde(with = "crate::serde::g1_affine")]
+    pub J: E::G1Affine,
 }
 
-impl FEJ {
+impl<E: Pairing> Default for FEJ<E> {
+    fn default() -> Self {
+        FEJ {
+            F: E::G1Affine::zero(),
+            E: E::G1Affine::zero(),
+            J: E::G1Affine::zero(),
+        }
+    }
+}
+
+impl<E: Pairing> FEJ<E> {
     pub fn compute_scalars(
-        challenge: &Challenges,
-        invers_tuple: &Inversion,
-        R0: Fr,
-        R1: Fr,
-        R2: Fr,
-    ) -> (Fr, Fr, Fr, Fr) {
+        challenge: &Challenges<E>,
+        invers_tuple: &Inversion<E>,
+        R0: E::ScalarField,
+        R1: E::ScalarField,
+        R2: E::ScalarField,
+    ) -> (
+        E::ScalarField,
+        E::ScalarField,
+        E::ScalarField,
+        E::ScalarField,
+    ) {
         let numerator = challenge
             .roots
             .h0w8
             .iter()
-            .fold(Fr::one(), |acc, h0_w8_i| acc * (challenge.y - *h0_w8_i));
+            .fold(E::ScalarField::one(), |acc, h0_w8_i| {
+                acc * (challenge.y - *h0_w8_i)
+            });
         let quotient1 = challenge.alpha * numerator * invers_tuple.den_h1;
         let quotient2 = challenge.alpha * challenge.alpha * numerator * invers_tuple.den_h2;
 
@@ -39,13 +63,13 @@ impl FEJ {
 
     // Compute full batched polynomial commitment [F]_1, group-encoded batch evaluation [E]_1 and the full difference [J]_1
     pub fn compute(
-        vk: &VerificationKey,
-        proof: &Proof,
-        challenge: &Challenges,
-        invers_tuple: &Inversion,
-        R0: Fr,
-        R1: Fr,
-        R2: Fr,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        challenge: &Challenges<E>,
+        invers_tuple: &Inversion<E>,
+        R0: E::ScalarField,
+        R1: E::ScalarField,
+        R2: E::ScalarField,
     ) -> Self {
         let (quotient1, quotient2, e_scalar, numerator) =
             Self::compute_scalars(challenge, invers_tuple, R0, R1, R2);
@@ -53,7 +77,7 @@ impl FEJ {
         let polynomials = &proof.polynomials;
 
         let f = polynomials.c1 * quotient1 + polynomials.c2 * quotient2 + vk.c0;
-        let e = G1Affine::generator() * e_scalar;
+        let e = vk.g1 * e_scalar;
         let j = polynomials.w1 * numerator;
 
         Self {
@@ -3,17 +3,17 @@ use crate::inversion::Inversion;
 use crate::proof::Proof;
 use crate::utils::polynomial_eval;
 use crate::vk::VerificationKey;
-use ark_bn254::Fr;
+use ark_ec::pairing::Pairing;
 use ark_ff::{Field, One};
 
-pub fn compute_r(
-    vk: &VerificationKey,
-    proof: &Proof,
-    challenges: &Challenges,
-    inversion: &Inversion,
-    L_1: &Fr,
-    pi: &Fr,
-) -> (Fr, Fr, Fr) {
+pub fn compute_r<E: Pairing>(
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    challenges: &Challenges<E>,
+    inversion: &Inversion<E>,
+    L_1: &E::ScalarField,
+    pi: &E::ScalarField,
+) -> (E::ScalarField, E::ScalarField, E::ScalarField) {
     let R0 = calculateR0(proof, challenges, inversion.lis_values.li_s0_inv);
     let R1 = calculateR1(
         proof,
@@ -40,7 +40,11 @@ pub fn compute_r(
 // where x = {h9, h0w8, h0w8^2, h0w8^3, h0w8^4, h0w8^5, h0w8^6, h0w8^7}
 // and   y = {C0(h0), C0(h0w8), C0(h0w8^2), C0(h0w8^3), C0(h0w8^4), C0(h0w8^5), C0(h0w8^6), C0(h0w8^7)}
 // and computing C0(xi)
-pub fn calculateR0(proof: &Proof, challenges: &Challenges, li_s0_inv: [Fr; 8]) -> Fr {
+pub fn calculateR0<E: Pairing>(
+    proof: &Proof<E>,
+    challenges: &Challenges<E>,
+    li_s0_inv: [E::ScalarField; 8],
+) -> E::ScalarField {
     // compute num
     let num = challenges.y.pow([8]) - challenges.xi;
 
@@ -68,23 +72,17 @@ pub fn calculateR0(proof: &Proof, challenges: &Challenges, li_s0_inv: [Fr; 8]) -
 // where x = {h1, h1w4, h1w4^2, h1w4^3}
 // and   y = {C1(h1), C1(h1w4), C1(h1w4^2), C1(h1w4^3)}
 // and computing T0(xi)
-pub fn calculateR1(
-    proof: &Proof,
-    challenges: &Challenges,
-    pi: &Fr,
-    li_s1_inv: [Fr; 4],
-    zh_inv: &Fr,
-) -> Fr {
+pub fn calculateR1<E: Pairing>(
+    proof: &Proof<E>,
+    challenges: &Challenges<E>,
+    pi: &E::ScalarField,
+    li_s1_inv: [E::ScalarField; 4],
+    zh_inv: &E::ScalarField,
+) -> E::ScalarField {
     let num = challenges.y.pow([4]) - challenges.xi;
     let evaluations = &proof.evaluations;
 
-    let t0 = (evaluations.ql * evaluations.a
-        + evaluations.qr * evaluations.b
-        + evaluations.qm * evaluations.a * evaluations.b
-        + evaluations.qo * evaluations.c
-        + evaluations.qc
-        + pi)
-        * zh_inv;
+    let t0 = compute_t0(proof, pi, zh_inv);
     let coefficients = [evaluations.a, evaluations.b, evaluations.c, t0];
 
     polynomial_eval(num, &coefficients, &challenges.roots.h1w4, &li_s1_inv, None)
@@ -96,32 +94,22 @@ pub fn calculateR1(
 // where x = {[h2, h2w3, h2w3^2], [h3, h3w3, h3w3^2]}
 // and   y = {[C2(h2), C2(h2w3), C2(h2w3^2)], [CChallenges::C0x.into_fr()2(h3), C2(h3w3), C2(h3w3^2)]}
 // and computing T1(xi) and T2(xi)
-pub fn calculateR2(
-    vk: &VerificationKey,
-    proof: &Proof,
-    challenges: &Challenges,
-    L_1: &Fr,
-    zh_inv: &Fr,
-    li_s2_inv: [Fr; 6],
-) -> Fr {
+pub fn calculateR2<E: Pairing>(
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    challenges: &Challenges<E>,
+    L_1: &E::ScalarField,
+    zh_inv: &E::ScalarField,
+    li_s2_inv: [E::ScalarField; 6],
+) -> E::ScalarField {
     // base = y^6 - y^3*xi*(1-w) + xi^2*w
     let base = challenges.y.pow([6])
-        - (challenges.y.pow([3]) * challenges.xi * (Fr::one() + vk.omega.w))
+        - (challenges.y.pow([3]) * challenges.xi * (E::ScalarField::one() + vk.omega.w))
         + (challenges.xi * challenges.xi * vk.omega.w);
     let evaluations = &proof.evaluations;
 
-    let t1 = (evaluations.z - Fr::one()) * L_1 * zh_inv;
-    let beta_xi = challenges.beta * challenges.xi;
-
-    let t2 = (((evaluations.a + beta_xi + challenges.gamma)
-        * (evaluations.b + beta_xi * vk.k1 + challenges.gamma)
-        * (evaluations.c + beta_xi * vk.k2 + challenges.gamma)
-        * evaluations.z)
-        - ((evaluations.a + challenges.beta * evaluations.s1 + challenges.gamma)
-            * (evaluations.b + challenges.beta * evaluations.s2 + challenges.gamma)
-            * (evaluations.c + challenges.beta * evaluations.s3 + challenges.gamma)
-            * evaluations.zw))
-        * zh_inv;
+    let t1 = compute_t1(proof, L_1, zh_inv);
+    let t2 = compute_t2(vk, proof, challenges, zh_inv);
 
     let coefficients = [evaluations.z, t1, t2];
     let gamma = polynomial_eval(
@@ -141,3 +129,52 @@ pub fn calculateR2(
         Some(gamma),
     )
 }
+
+// Compute T0(xi) = (ql·a + qr·b + qm·a·b + qo·c + qc + PI(xi)) / Z_H(xi), the gate constraint.
+pub fn compute_t0<E: Pairing>(
+    proof: &Proof<E>,
+    pi: &E::ScalarField,
+    zh_inv: &E::ScalarField,
+) -> E::ScalarField {
+    let evaluations = &proof.evaluations;
+
+    (evaluations.ql * evaluations.a
+        + evaluations.qr * evaluations.b
+        + evaluations.qm * evaluations.a * evaluations.b
+        + evaluations.qo * evaluations.c
+        + evaluations.qc
+        + pi)
+        * zh_inv
+}
+
+// Compute T1(xi) = (z - 1)·L_1(xi) / Z_H(xi), the copy constraint on the first row.
+pub fn compute_t1<E: Pairing>(
+    proof: &Proof<E>,
+    L_1: &E::ScalarField,
+    zh_inv: &E::ScalarField,
+) -> E::ScalarField {
+    (proof.evaluations.z - E::ScalarField::one()) * L_1 * zh_inv
+}
+
+// Compute T2(xi), the copy constraint between the rows:
+//      T2(xi) = ((a + beta·xi + gamma)(b + beta·k1·xi + gamma)(c + beta·k2·xi + gamma)·z
+//               - (a + beta·s1 + gamma)(b + beta·s2 + gamma)(c + beta·s3 + gamma)·zw) / Z_H(xi)
+pub fn compute_t2<E: Pairing>(
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    challenges: &Challenges<E>,
+    zh_inv: &E::ScalarField,
+) -> E::ScalarField {
+    let evaluations = &proof.evaluations;
+    let beta_xi = challenges.beta * challenges.xi;
+
+    (((evaluations.a + beta_xi + challenges.gamma)
+        * (evaluations.b + beta_xi * vk.k1 + challenges.gamma)
+        * (evaluations.c + beta_xi * vk.k2 + challenges.gamma)
+        * evaluations.z)
+        - ((evaluations.a + challenges.beta * evaluations.s1 + challenges.gamma)
+            * (evaluations.b + challenges.beta * evaluations.s2 + challenges.gamma)
+            * (evaluations.c + challenges.beta * evaluations.s3 + challenges.gamma)
+            * evaluations.zw))
+        * zh_inv
+}
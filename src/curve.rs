@@ -0,0 +1,48 @@
+use ark_ec::short_weierstrass::{Projective, SWCurveConfig};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::Valid;
+use num_traits::Zero;
+
+/// The projective coordinates `[x, y, z]` of a short Weierstrass point, which is how snarkjs encodes
+/// the points, eg: `"C0": [x, y, z]`.
+///
+/// Implemented for G1 and G2 of the arkworks pairing-friendly curves, eg: BN254 and BLS12-381.
+pub trait SWPoint: Sized + Valid {
+    type BaseField: Field;
+
+    fn coordinates(&self) -> [Self::BaseField; 3];
+
+    // Build the point from its coordinates, without checking it's on the curve and in the
+    // prime order subgroup: use `ark_serialize::Valid::check` for that.
+    fn from_coordinates(x: Self::BaseField, y: Self::BaseField, z: Self::BaseField) -> Self;
+}
+
+impl<P: SWCurveConfig> SWPoint for Projective<P> {
+    type BaseField = P::BaseField;
+
+    fn coordinates(&self) -> [Self::BaseField; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    fn from_coordinates(x: Self::BaseField, y: Self::BaseField, z: Self::BaseField) -> Self {
+        Projective::new_unchecked(x, y, z)
+    }
+}
+
+// Big-endian bytes of a field element: its base prime field components (c0, c1, ...) one after another,
+// each one padded to the byte size of the base prime field.
+pub fn field_to_bytes_be<F: Field>(f: &F) -> Vec<u8> {
+    f.to_base_prime_field_elements()
+        .flat_map(|e| e.into_bigint().to_bytes_be())
+        .collect()
+}
+
+// Big-endian bytes of an affine point: x || y, and zeros for the point at infinity.
+// For G1 it's the same as snarkjs `toRprUncompressed`, which is what the transcript absorbs.
+pub fn point_to_bytes_be<A: AffineRepr>(p: &A) -> Vec<u8> {
+    match p.xy() {
+        Some((x, y)) => [field_to_bytes_be(x), field_to_bytes_be(y)].concat(),
+        None => vec![0u8; 2 * field_to_bytes_be(&A::BaseField::zero()).len()],
+    }
+}
@@ -0,0 +1,461 @@
+use crate::challenge::Challenges;
+use crate::compute_fej::FEJ;
+use crate::curve::SWPoint;
+use crate::inversion::Inversion;
+use crate::pairing::PairingBackend;
+use crate::proof::Proof;
+use crate::stepwise::VerifierState;
+use crate::transcript::TranscriptHash;
+use crate::vk::VerificationKey;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::Zero;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The intermediate values of the verifier published for a proof, eg: by the party of an
+/// optimistic verification, which anyone can dispute with `find_dispute`.
+///
+/// The verifier stops at the inversion if the key is inconsistent or the inverse sent by the
+/// prover is wrong: the trace of such a proof has no `checked` values, and doesn't verify.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "E::G1: SWPoint<BaseField = E::BaseField>")]
+pub struct ClaimedTrace<E: Pairing = Bn254> {
+    pub challenges: Challenges<E>,
+    pub checked: Option<CheckedTrace<E>>,
+    pub verified: bool,
+}
+
+/// The intermediate values of the verifier after the inversion, see `ClaimedTrace`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "E::G1: SWPoint<BaseField = E::BaseField>")]
+pub struct CheckedTrace<E: Pairing = Bn254> {
+    pub inversion: Inversion<E>,
+    #[serde(with = "crate::serde::fr")]
+    pub l1: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub pi: E::ScalarField,
+    #[serde(with = "crate::serde::fr_array")]
+    pub r: [E::ScalarField; 3],
+    pub fej: FEJ<E>,
+    #[serde(with = "crate::serde::g1_affine")]
+    pub a1: E::G1Affine,
+}
+
+impl<E: Pairing> Default for CheckedTrace<E> {
+    fn default() -> Self {
+        Self {
+            inversion: Inversion::default(),
+            l1: E::ScalarField::zero(),
+            pi: E::ScalarField::zero(),
+            r: [E::ScalarField::zero(); 3],
+            fej: FEJ::default(),
+            a1: E::G1Affine::zero(),
+        }
+    }
+}
+
+impl<E: Pairing> ClaimedTrace<E>
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+{
+    pub fn load<P: AsRef<Path>>(trace_path: P) -> anyhow::Result<Self> {
+        let mut file = File::open(trace_path)?;
+        let mut trace_json = String::new();
+        file.read_to_string(&mut trace_json)?;
+        Ok(serde_json::from_str(&trace_json)?)
+    }
+}
+
+impl<E: Pairing> ClaimedTrace<E> {
+    /// The honest trace of the proof, with the pairing check of the `backend`.
+    pub fn compute<T: TranscriptHash, B: PairingBackend<E>>(
+        backend: &B,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> Self {
+        let mut trace = Self {
+            challenges: Challenges::default(),
+            checked: None,
+            verified: false,
+        };
+        let mut checked = CheckedTrace::default();
+        let mut state = VerifierState::default();
+        loop {
+            state = state
+                .step::<T, B>(backend, vk, proof, pub_input)
+                .expect("the states are the ones of the inputs");
+            match &state {
+                VerifierState::Challenges => unreachable!(),
+                VerifierState::Inversion { challenges, .. } => trace.challenges = *challenges,
+                VerifierState::PublicInput { inversion, .. } => checked.inversion = *inversion,
+                VerifierState::R { l1, pi, .. } => (checked.l1, checked.pi) = (*l1, *pi),
+                VerifierState::FEJ { r, .. } => checked.r = *r,
+                VerifierState::A1 { fej, .. } => checked.fej = *fej,
+                VerifierState::Pairing { a1, .. } => {
+                    checked.a1 = *a1;
+                    trace.checked = Some(checked);
+                }
+                // straight after the inversion if the key or the inverse is wrong
+                VerifierState::Done { verified, .. } => {
+                    trace.verified = *verified;
+                    return trace;
+                }
+            }
+        }
+    }
+
+    /// The states of the claimed trace for the inputs, see `VerifierState`: the one before every
+    /// step, and the final one. Without `checked` values, the trace stops at the inversion.
+    pub fn states<T: TranscriptHash>(
+        &self,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> Vec<VerifierState<E>> {
+        let inputs = VerifierState::inputs_digest::<T>(vk, proof, pub_input);
+        let challenges = self.challenges;
+        let done = VerifierState::Done {
+            inputs,
+            verified: self.verified,
+        };
+        let Some(checked) = self.checked else {
+            return vec![
+                VerifierState::Challenges,
+                VerifierState::Inversion { inputs, challenges },
+                done,
+            ];
+        };
+        let inversion = checked.inversion;
+        vec![
+            VerifierState::Challenges,
+            VerifierState::Inversion { inputs, challenges },
+            VerifierState::PublicInput {
+                inputs,
+                challenges,
+                inversion,
+            },
+            VerifierState::R {
+                inputs,
+                challenges,
+                inversion,
+                l1: checked.l1,
+                pi: checked.pi,
+            },
+            VerifierState::FEJ {
+                inputs,
+                challenges,
+                inversion,
+                r: checked.r,
+            },
+            VerifierState::A1 {
+                inputs,
+                challenges,
+                fej: checked.fej,
+            },
+            VerifierState::Pairing {
+                inputs,
+                a1: checked.a1,
+            },
+            done,
+        ]
+    }
+}
+
+/// The evidence that a single step of a `ClaimedTrace` is wrong: the claimed state before the
+/// step, which the previous steps agree with, and the claimed state after it.
+///
+/// A third party checks it against the trace with `DisputeEvidence::check`, running that step
+/// only.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "E::G1: SWPoint<BaseField = E::BaseField>")]
+pub struct DisputeEvidence<E: Pairing = Bn254> {
+    // The index of the step in the trace, from 0 for the challenges to 6 for the pairing check.
+    pub step: usize,
+    pub pre_state: VerifierState<E>,
+    pub claimed_state: VerifierState<E>,
+}
+
+impl<E: Pairing> DisputeEvidence<E>
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+{
+    pub fn load<P: AsRef<Path>>(evidence_path: P) -> anyhow::Result<Self> {
+        let mut file = File::open(evidence_path)?;
+        let mut evidence_json = String::new();
+        file.read_to_string(&mut evidence_json)?;
+        Ok(serde_json::from_str(&evidence_json)?)
+    }
+}
+
+impl<E: Pairing> DisputeEvidence<E> {
+    /// Whether the claimed `trace` is wrong at the step: `pre_state` and `claimed_state` are the
+    /// states of the trace before and after it, and the step run from `pre_state` doesn't lead
+    /// to `claimed_state`.
+    pub fn check<T: TranscriptHash, B: PairingBackend<E>>(
+        &self,
+        backend: &B,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+        trace: &ClaimedTrace<E>,
+    ) -> bool {
+        let states = trace.states::<T>(vk, proof, pub_input);
+        let in_trace = states.get(self.step) == Some(&self.pre_state)
+            && states.get(self.step + 1) == Some(&self.claimed_state);
+
+        in_trace
+            && self
+                .pre_state
+                .clone()
+                .step::<T, B>(backend, vk, proof, pub_input)
+                .is_ok_and(|state| state != self.claimed_state)
+    }
+}
+
+/// Re-execute the steps of the claimed `trace` one after another, and return the evidence of the
+/// first wrong one, or `None` if the whole trace holds.
+///
+/// Every step is checked from the claimed state before it, so the evidence only involves the
+/// values of that step, and not the ones of the steps before.
+pub fn find_dispute<E: Pairing, T: TranscriptHash, B: PairingBackend<E>>(
+    backend: &B,
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    pub_input: &E::ScalarField,
+    trace: &ClaimedTrace<E>,
+) -> Option<DisputeEvidence<E>> {
+    let states = trace.states::<T>(vk, proof, pub_input);
+    states
+        .windows(2)
+        .enumerate()
+        .map(|(step, pair)| DisputeEvidence {
+            step,
+            pre_state: pair[0].clone(),
+            claimed_state: pair[1].clone(),
+        })
+        .find(|evidence| evidence.check::<T, B>(backend, vk, proof, pub_input, trace))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+    use crate::pairing::ArkworksBackend;
+    use crate::transcript::Keccak256TranscriptHash;
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ec::CurveGroup;
+    use ark_ff::One;
+    use std::str::FromStr;
+
+    type Tamper = fn(&mut ClaimedTrace);
+
+    #[test]
+    fn test_find_dispute() {
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let vk = VerificationKey::default();
+        let dispute = |trace: &ClaimedTrace| {
+            find_dispute::<_, Keccak256TranscriptHash, _>(
+                &ArkworksBackend,
+                &vk,
+                &proof,
+                &pub_input,
+                trace,
+            )
+        };
+        let check = |evidence: &DisputeEvidence, trace: &ClaimedTrace| {
+            evidence.check::<Keccak256TranscriptHash, _>(
+                &ArkworksBackend,
+                &vk,
+                &proof,
+                &pub_input,
+                trace,
+            )
+        };
+
+        let honest = ClaimedTrace::compute::<Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &proof,
+            &pub_input,
+        );
+        assert!(honest.verified);
+        assert_eq!(dispute(&honest), None);
+
+        let tampers: [(usize, Tamper); 8] = [
+            (0, |t| t.challenges.beta += Fr::one()),
+            (0, |t| t.challenges.roots.h0w8[3] += Fr::one()),
+            (1, |t| {
+                t.checked.as_mut().unwrap().inversion.lis_values.li_s2_inv[5] += Fr::one()
+            }),
+            (2, |t| t.checked.as_mut().unwrap().pi += Fr::one()),
+            (3, |t| t.checked.as_mut().unwrap().r[2] += Fr::one()),
+            (4, |t| {
+                let fej = &mut t.checked.as_mut().unwrap().fej;
+                fej.J = (fej.J + G1Affine::generator()).into_affine()
+            }),
+            (5, |t| {
+                let a1 = &mut t.checked.as_mut().unwrap().a1;
+                *a1 = (*a1 + G1Affine::generator()).into_affine()
+            }),
+            (6, |t| t.verified = false),
+        ];
+        for (step, tamper) in tampers {
+            let mut trace = honest.clone();
+            tamper(&mut trace);
+            let evidence = dispute(&trace).unwrap();
+            assert_eq!(evidence.step, step);
+
+            // the third party only gets the trace and the evidence
+            let json = serde_json::to_string(&(&trace, &evidence)).unwrap();
+            let (trace, evidence): (ClaimedTrace, DisputeEvidence) =
+                serde_json::from_str(&json).unwrap();
+            assert!(check(&evidence, &trace));
+            // which doesn't hold against the honest trace
+            assert!(!check(&evidence, &honest));
+        }
+
+        // a trace claiming a failing proof passes, with the pairing failing
+        let mut wrong = proof.clone();
+        wrong.polynomials.w2 += G1Affine::generator();
+        let trace = ClaimedTrace::compute::<Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &wrong,
+            &pub_input,
+        );
+        assert!(!trace.verified);
+        let mut claimed = trace.clone();
+        claimed.verified = true;
+        let evidence = find_dispute::<_, Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &wrong,
+            &pub_input,
+            &claimed,
+        )
+        .unwrap();
+        assert_eq!(evidence.step, 6);
+
+        // the honest evidence of a step doesn't check
+        let states = honest.states::<Keccak256TranscriptHash>(&vk, &proof, &pub_input);
+        let evidence = DisputeEvidence {
+            step: 2,
+            pre_state: states[2].clone(),
+            claimed_state: states[3].clone(),
+        };
+        assert!(!check(&evidence, &honest));
+
+        // nor does the evidence of a pre-state which isn't the one of the trace, though the step
+        // run from it doesn't lead to the claimed state
+        let Some(mut forged) = honest.checked else {
+            unreachable!()
+        };
+        forged.pi += Fr::one();
+        let VerifierState::R {
+            inputs,
+            challenges,
+            inversion,
+            l1,
+            ..
+        } = states[3].clone()
+        else {
+            unreachable!()
+        };
+        let evidence = DisputeEvidence {
+            step: 3,
+            pre_state: VerifierState::R {
+                inputs,
+                challenges,
+                inversion,
+                l1,
+                pi: forged.pi,
+            },
+            claimed_state: states[4].clone(),
+        };
+        assert!(evidence
+            .pre_state
+            .clone()
+            .step::<Keccak256TranscriptHash, _>(&ArkworksBackend, &vk, &proof, &pub_input)
+            .is_ok_and(|state| state != evidence.claimed_state));
+        assert!(!check(&evidence, &honest));
+        // nor the evidence of a step out of the trace
+        let evidence = DisputeEvidence {
+            step: 7,
+            ..evidence
+        };
+        assert!(!check(&evidence, &honest));
+    }
+
+    #[test]
+    fn test_find_dispute_wrong_inverse() {
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let vk = VerificationKey::default();
+        let mut wrong = proof.clone();
+        wrong.evaluations.inv += Fr::one();
+        let dispute = |trace: &ClaimedTrace| {
+            find_dispute::<_, Keccak256TranscriptHash, _>(
+                &ArkworksBackend,
+                &vk,
+                &wrong,
+                &pub_input,
+                trace,
+            )
+        };
+
+        // the honest trace stops at the inversion
+        let stopped = ClaimedTrace::compute::<Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &wrong,
+            &pub_input,
+        );
+        assert_eq!(stopped.checked, None);
+        assert!(!stopped.verified);
+        assert_eq!(
+            stopped
+                .states::<Keccak256TranscriptHash>(&vk, &wrong, &pub_input)
+                .len(),
+            3
+        );
+        assert_eq!(dispute(&stopped), None);
+
+        // claiming it verifies, or going past the inversion, is disputed at the inversion
+        let mut claimed = stopped.clone();
+        claimed.verified = true;
+        assert_eq!(dispute(&claimed).unwrap().step, 1);
+        let honest = ClaimedTrace::compute::<Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &proof,
+            &pub_input,
+        );
+        let claimed = ClaimedTrace {
+            challenges: stopped.challenges,
+            ..honest.clone()
+        };
+        assert_eq!(dispute(&claimed).unwrap().step, 1);
+
+        // and the other way around, stopping at a right inverse is disputed at the inversion
+        let stopped = ClaimedTrace {
+            checked: None,
+            verified: false,
+            ..honest
+        };
+        let evidence = find_dispute::<_, Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &proof,
+            &pub_input,
+            &stopped,
+        )
+        .unwrap();
+        assert_eq!(evidence.step, 1);
+    }
+}
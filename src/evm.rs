@@ -0,0 +1,986 @@
+//! Emulation of the BN254 precompiles of the EVM over their exact byte interface:
+//! ecAdd and ecMul of EIP-196, at the addresses 0x06 and 0x07, and ecPairing of EIP-197, at 0x08.
+//!
+//! Every field element is a big-endian 32-byte word, a G1 point is x || y and a G2 point is
+//! x_im || x_re || y_im || y_re, the point at infinity being all zeros.
+//!
+//! `fflonk_evm_verifier` runs the verifier the way `fflonkVerifier.sol` does, with every curve
+//! operation through the precompiles, and meters the gas of the precompiles, keccak256, the
+//! calldata, the arithmetic opcodes and the memory expansion, see `GasReport`: a lower bound of
+//! the gas of the contract, which the stack operations and the jumps of the compiler add up to.
+use crate::challenge::Challenges;
+use crate::compute_fej::FEJ;
+use crate::compute_r::compute_r;
+use crate::inversion::Inversion;
+use crate::proof::{Evaluations, Polynomials, Proof};
+use crate::transcript::Keccak256TranscriptHash;
+use crate::utils::{compute_pi, LangrangePolynomialEvaluation};
+use crate::vk::VerificationKey;
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use ark_serialize::Valid;
+use std::fmt::{Display, Formatter};
+use tiny_keccak::{Hasher, Keccak};
+
+pub const EC_ADD_ADDRESS: u8 = 0x06;
+pub const EC_MUL_ADDRESS: u8 = 0x07;
+pub const EC_PAIRING_ADDRESS: u8 = 0x08;
+
+// The gas schedule since Berlin: EIP-1108 for the precompiles and EIP-2929 for the calls,
+// the precompiles being always warm.
+pub const EC_ADD_GAS: u64 = 150;
+pub const EC_MUL_GAS: u64 = 6_000;
+pub const EC_PAIRING_BASE_GAS: u64 = 45_000;
+pub const EC_PAIRING_PER_PAIR_GAS: u64 = 34_000;
+pub const WARM_STATICCALL_GAS: u64 = 100;
+pub const KECCAK256_GAS: u64 = 30;
+pub const KECCAK256_WORD_GAS: u64 = 6;
+pub const TX_BASE_GAS: u64 = 21_000;
+pub const CALLDATA_ZERO_BYTE_GAS: u64 = 4;
+pub const CALLDATA_NONZERO_BYTE_GAS: u64 = 16;
+pub const MEMORY_WORD_GAS: u64 = 3;
+pub const MEMORY_QUADRATIC_DIVISOR: u64 = 512;
+
+// The memory of `fflonkVerifier.sol` from the free memory pointer of Solidity: its `lastMem` bytes
+// of variables, then the scratch space of the transcript, 512 bytes for alpha, and of
+// `inverseArray`, one word per inverse.
+const FREE_MEMORY_POINTER: usize = 0x80;
+const LAST_MEM: usize = 1920;
+const TRANSCRIPT_MEMORY: usize = FREE_MEMORY_POINTER + LAST_MEM + 16 * WORD;
+const INVERSE_ARRAY_MEMORY: usize = FREE_MEMORY_POINTER + LAST_MEM + 22 * WORD;
+
+const WORD: usize = 32;
+const G1_SIZE: usize = 2 * WORD;
+const G2_SIZE: usize = 4 * WORD;
+const PAIR_SIZE: usize = G1_SIZE + G2_SIZE;
+
+/// The ways a precompile call fails, in which case the call consumes all of its gas.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PrecompileError {
+    // The input of ecPairing isn't a multiple of 192 bytes, or a point or the calldata
+    // doesn't have its size.
+    InvalidInputLength,
+    // A coordinate isn't lower than the base field modulus.
+    InvalidFieldElement,
+    NotOnCurve,
+    // A G2 point isn't in the prime order subgroup.
+    NotInSubgroup,
+    // The calldata isn't a call of `verifyProof(bytes32[24],uint256[1])`.
+    InvalidSelector,
+}
+
+impl Display for PrecompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            PrecompileError::InvalidInputLength => "invalid input length",
+            PrecompileError::InvalidFieldElement => "invalid field element",
+            PrecompileError::NotOnCurve => "point not on curve",
+            PrecompileError::NotInSubgroup => "point not in subgroup",
+            PrecompileError::InvalidSelector => "invalid function selector",
+        };
+        write!(f, "precompile failed: {reason}")
+    }
+}
+
+impl std::error::Error for PrecompileError {}
+
+/// ecAdd: P + Q, with the input right-padded with zeros to 128 bytes.
+pub fn ec_add(input: &[u8]) -> Result<[u8; 64], PrecompileError> {
+    let input = right_padded(input, 2 * G1_SIZE);
+    let p = decode_g1(&input[..G1_SIZE])?;
+    let q = decode_g1(&input[G1_SIZE..])?;
+    Ok(encode_g1(&(p + q).into_affine()))
+}
+
+/// ecMul: s·P, with the input right-padded with zeros to 96 bytes.
+/// The scalar is any 256-bit word, not necessarily lower than the group order.
+pub fn ec_mul(input: &[u8]) -> Result<[u8; 64], PrecompileError> {
+    let input = right_padded(input, G1_SIZE + WORD);
+    let p = decode_g1(&input[..G1_SIZE])?;
+    let mut limbs = [0u64; 4];
+    for (limb, bytes) in limbs.iter_mut().rev().zip(input[G1_SIZE..].chunks(8)) {
+        *limb = u64::from_be_bytes(bytes.try_into().unwrap());
+    }
+    Ok(encode_g1(&p.mul_bigint(limbs).into_affine()))
+}
+
+/// ecPairing: the 32-byte word 1 if ∏ e(P_i, Q_i) = 1 over the pairs (P_i, Q_i) of the input, else 0.
+pub fn ec_pairing(input: &[u8]) -> Result<[u8; 32], PrecompileError> {
+    if input.len() % PAIR_SIZE != 0 {
+        return Err(PrecompileError::InvalidInputLength);
+    }
+
+    let (g1s, g2s): (Vec<_>, Vec<_>) = input
+        .chunks(PAIR_SIZE)
+        .map(|pair| Ok((decode_g1(&pair[..G1_SIZE])?, decode_g2(&pair[G1_SIZE..])?)))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+
+    let res = Bn254::multi_pairing(g1s, g2s);
+    Ok(encode_bool(res.0.is_one()))
+}
+
+pub fn encode_g1(p: &G1Affine) -> [u8; 64] {
+    let mut bytes = [0u8; G1_SIZE];
+    if let Some((x, y)) = p.xy() {
+        bytes[..WORD].copy_from_slice(&encode_fq(x));
+        bytes[WORD..].copy_from_slice(&encode_fq(y));
+    }
+    bytes
+}
+
+pub fn encode_g2(p: &G2Affine) -> [u8; 128] {
+    let mut bytes = [0u8; G2_SIZE];
+    if let Some((x, y)) = p.xy() {
+        for (i, c) in [x.c1, x.c0, y.c1, y.c0].iter().enumerate() {
+            bytes[i * WORD..(i + 1) * WORD].copy_from_slice(&encode_fq(c));
+        }
+    }
+    bytes
+}
+
+pub fn decode_g1(bytes: &[u8]) -> Result<G1Affine, PrecompileError> {
+    if bytes.len() != G1_SIZE {
+        return Err(PrecompileError::InvalidInputLength);
+    }
+    let (x, y) = (
+        decode_fq(&bytes[..WORD])?,
+        decode_fq(&bytes[WORD..G1_SIZE])?,
+    );
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    let p = G1Affine::new_unchecked(x, y);
+    if !p.is_on_curve() {
+        return Err(PrecompileError::NotOnCurve);
+    }
+    Ok(p)
+}
+
+pub fn decode_g2(bytes: &[u8]) -> Result<G2Affine, PrecompileError> {
+    if bytes.len() != G2_SIZE {
+        return Err(PrecompileError::InvalidInputLength);
+    }
+    let word = |i: usize| decode_fq(&bytes[i * WORD..(i + 1) * WORD]);
+    let x = Fq2::new(word(1)?, word(0)?);
+    let y = Fq2::new(word(3)?, word(2)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    let p = G2Affine::new_unchecked(x, y);
+    if !p.is_on_curve() {
+        return Err(PrecompileError::NotOnCurve);
+    }
+    p.check().map_err(|_e| PrecompileError::NotInSubgroup)?;
+    Ok(p)
+}
+
+pub fn encode_fr(f: &Fr) -> [u8; 32] {
+    let mut bytes = [0u8; WORD];
+    bytes.copy_from_slice(&f.into_bigint().to_bytes_be());
+    bytes
+}
+
+fn encode_fq(f: &Fq) -> [u8; 32] {
+    let mut bytes = [0u8; WORD];
+    bytes.copy_from_slice(&f.into_bigint().to_bytes_be());
+    bytes
+}
+
+// Unlike `Fq::from_be_bytes_mod_order`, the precompiles reject the non-canonical encodings.
+fn decode_fq(bytes: &[u8]) -> Result<Fq, PrecompileError> {
+    let f = Fq::from_be_bytes_mod_order(bytes);
+    if encode_fq(&f) != bytes {
+        return Err(PrecompileError::InvalidFieldElement);
+    }
+    Ok(f)
+}
+
+fn right_padded(input: &[u8], len: usize) -> Vec<u8> {
+    let mut padded = input[..input.len().min(len)].to_vec();
+    padded.resize(len, 0);
+    padded
+}
+
+fn encode_bool(b: bool) -> [u8; 32] {
+    let mut word = [0u8; WORD];
+    word[WORD - 1] = b as u8;
+    word
+}
+
+/// The stages of `fflonkVerifier.sol::verifyProof`, plus the intrinsic gas of the transaction.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Stage {
+    // The base cost of the transaction and its calldata.
+    Intrinsic,
+    CheckInput,
+    Challenges,
+    Inversions,
+    Lagrange,
+    Pi,
+    R,
+    FEJ,
+    Pairing,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 9] = [
+        Stage::Intrinsic,
+        Stage::CheckInput,
+        Stage::Challenges,
+        Stage::Inversions,
+        Stage::Lagrange,
+        Stage::Pi,
+        Stage::R,
+        Stage::FEJ,
+        Stage::Pairing,
+    ];
+}
+
+/// The metered gas of a stage, along with its precompile calls.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct StageGas {
+    pub gas: u64,
+    pub ec_add: u64,
+    pub ec_mul: u64,
+    pub ec_pairing: u64,
+    // The pairs of all the ecPairing calls.
+    pub pairs: u64,
+    pub keccak256: u64,
+}
+
+/// The metered gas of the verifier, by stage.
+///
+/// The intrinsic gas, the precompile calls, keccak256, the opcodes of the Yul builtins (see
+/// `opcode_gas`) and the memory expansion are metered. The stack operations and the jumps of the
+/// compiled contract aren't, so it's a lower bound of the gas used.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GasReport {
+    pub stages: Vec<(Stage, StageGas)>,
+}
+
+impl Default for GasReport {
+    fn default() -> Self {
+        Self {
+            stages: Stage::ALL
+                .iter()
+                .map(|stage| (*stage, StageGas::default()))
+                .collect(),
+        }
+    }
+}
+
+impl GasReport {
+    pub fn stage(&self, stage: Stage) -> StageGas {
+        self.stages
+            .iter()
+            .find(|(s, _)| *s == stage)
+            .map(|(_, gas)| *gas)
+            .unwrap_or_default()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.stages.iter().map(|(_, gas)| gas.gas).sum()
+    }
+
+    fn stage_mut(&mut self, stage: Stage) -> &mut StageGas {
+        &mut self.stages.iter_mut().find(|(s, _)| *s == stage).unwrap().1
+    }
+}
+
+/// Runs the precompiles and charges their gas to the current stage.
+#[derive(Debug, Default, Clone)]
+pub struct Evm {
+    stage: Option<Stage>,
+    report: GasReport,
+    memory_words: u64,
+}
+
+impl Evm {
+    pub fn enter(&mut self, stage: Stage) {
+        self.stage = Some(stage);
+    }
+
+    pub fn report(&self) -> &GasReport {
+        &self.report
+    }
+
+    fn current(&mut self) -> &mut StageGas {
+        let stage = self.stage.unwrap_or(Stage::Intrinsic);
+        self.report.stage_mut(stage)
+    }
+
+    pub fn charge(&mut self, gas: u64) {
+        self.current().gas += gas;
+    }
+
+    // The intrinsic gas of a transaction with the given calldata.
+    pub fn charge_calldata(&mut self, calldata: &[u8]) {
+        let zeros = calldata.iter().filter(|b| **b == 0).count() as u64;
+        let nonzeros = calldata.len() as u64 - zeros;
+        self.charge(
+            TX_BASE_GAS + zeros * CALLDATA_ZERO_BYTE_GAS + nonzeros * CALLDATA_NONZERO_BYTE_GAS,
+        );
+    }
+
+    // The memory expansion up to `size` bytes, if it's larger than the memory so far.
+    pub fn expand_memory(&mut self, size: usize) {
+        let words = size.div_ceil(WORD) as u64;
+        if words > self.memory_words {
+            let cost =
+                |words: u64| MEMORY_WORD_GAS * words + words * words / MEMORY_QUADRATIC_DIVISOR;
+            self.charge(cost(words) - cost(self.memory_words));
+            self.memory_words = words;
+        }
+    }
+
+    pub fn keccak256(&mut self, input: &[u8]) -> [u8; 32] {
+        let words = input.len().div_ceil(WORD) as u64;
+        self.charge(KECCAK256_GAS + words * KECCAK256_WORD_GAS);
+        self.current().keccak256 += 1;
+
+        let mut hasher = Keccak::v256();
+        hasher.update(input);
+        let mut output = [0u8; 32];
+        hasher.finalize(&mut output);
+        output
+    }
+
+    pub fn ec_add(&mut self, input: &[u8]) -> Result<[u8; 64], PrecompileError> {
+        self.charge(WARM_STATICCALL_GAS + EC_ADD_GAS);
+        self.current().ec_add += 1;
+        ec_add(input)
+    }
+
+    pub fn ec_mul(&mut self, input: &[u8]) -> Result<[u8; 64], PrecompileError> {
+        self.charge(WARM_STATICCALL_GAS + EC_MUL_GAS);
+        self.current().ec_mul += 1;
+        ec_mul(input)
+    }
+
+    pub fn ec_pairing(&mut self, input: &[u8]) -> Result<[u8; 32], PrecompileError> {
+        let pairs = (input.len() / PAIR_SIZE) as u64;
+        self.charge(WARM_STATICCALL_GAS + EC_PAIRING_BASE_GAS + pairs * EC_PAIRING_PER_PAIR_GAS);
+        let current = self.current();
+        current.ec_pairing += 1;
+        current.pairs += pairs;
+        ec_pairing(input)
+    }
+
+    // g1_mulAcc of the contract: R = R + s·P
+    fn g1_mul_acc(
+        &mut self,
+        r: &[u8; 64],
+        p: &[u8; 64],
+        s: &Fr,
+    ) -> Result<[u8; 64], PrecompileError> {
+        let sp = self.ec_mul(&[p.as_slice(), &encode_fr(s)].concat())?;
+        self.ec_add(&[sp.as_slice(), r].concat())
+    }
+}
+
+/// The gas of the opcodes of the Yul builtins of a stage of `fflonkVerifier.sol`, over a 2^power
+/// domain: 3 for add, sub, lt, eq, iszero, and, mload, mstore, calldataload and calldatacopy
+/// (plus 3 a word copied), 2 for gas, 5 for mul and mod, and 8 for addmod and mulmod, summed over
+/// the functions the stage calls, see `test_opcode_gas`.
+///
+/// keccak256 and staticcall are metered along with their input by `Evm`, and the branches which
+/// revert aren't counted.
+pub fn opcode_gas(stage: Stage, power: u8) -> u64 {
+    match stage {
+        Stage::Intrinsic => 0,
+        Stage::CheckInput => 332,
+        // then xi^n, by squaring xi `power` times
+        Stage::Challenges => 690 + 8 * power as u64,
+        Stage::Inversions => 3_285,
+        Stage::Lagrange => 26,
+        Stage::Pi => 34,
+        Stage::R => 3_541,
+        Stage::FEJ => 632,
+        Stage::Pairing => 352,
+    }
+}
+
+/// The function selector of `verifyProof(bytes32[24] proof, uint256[1] pubSignals)`: the
+/// contract has a single public input, as `FflonkZKey`.
+pub fn selector() -> [u8; 4] {
+    let mut hasher = Keccak::v256();
+    hasher.update(b"verifyProof(bytes32[24],uint256[1])");
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// The calldata of `verifyProof(bytes32[24] proof, uint256[1] pubSignals)`.
+pub fn encode_calldata(proof: &Proof, pub_input: &Fr) -> Vec<u8> {
+    let (polynomials, evaluations) = (&proof.polynomials, &proof.evaluations);
+    let points = [
+        polynomials.c1,
+        polynomials.c2,
+        polynomials.w1,
+        polynomials.w2,
+    ]
+    .map(|p| encode_g1(&p.into_affine()));
+    let evals = [
+        evaluations.ql,
+        evaluations.qr,
+        evaluations.qm,
+        evaluations.qo,
+        evaluations.qc,
+        evaluations.s1,
+        evaluations.s2,
+        evaluations.s3,
+        evaluations.a,
+        evaluations.b,
+        evaluations.c,
+        evaluations.z,
+        evaluations.zw,
+        evaluations.t1w,
+        evaluations.t2w,
+        evaluations.inv,
+    ];
+
+    std::iter::once(selector().to_vec())
+        .chain(points.iter().map(|p| p.to_vec()))
+        .chain(
+            evals
+                .iter()
+                .chain([pub_input])
+                .map(|f| encode_fr(f).to_vec()),
+        )
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+/// The proof and the public input of the calldata of `encode_calldata`, checked the way
+/// `fflonkVerifier.sol` does: the points on the curve and the field elements lower than the
+/// scalar field modulus.
+pub fn decode_calldata(calldata: &[u8]) -> Result<(Proof, Fr), PrecompileError> {
+    if calldata.len() != 4 + 4 * G1_SIZE + 17 * WORD {
+        return Err(PrecompileError::InvalidInputLength);
+    }
+    // the selector of another signature is another function of the contract
+    if calldata[..4] != selector() {
+        return Err(PrecompileError::InvalidSelector);
+    }
+    let (points, words) = calldata[4..].split_at(4 * G1_SIZE);
+    let [c1, c2, w1, w2] = [0, 1, 2, 3]
+        .map(|i| decode_g1(&points[i * G1_SIZE..(i + 1) * G1_SIZE]).map(|p| p.into_group()));
+    let frs = words
+        .chunks(WORD)
+        .map(|word| {
+            let f = Fr::from_be_bytes_mod_order(word);
+            if encode_fr(&f) != word {
+                return Err(PrecompileError::InvalidFieldElement);
+            }
+            Ok(f)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let (evals, pub_input) = (&frs[..16], frs[16]);
+    let proof = Proof {
+        polynomials: Polynomials {
+            c1: c1?,
+            c2: c2?,
+            w1: w1?,
+            w2: w2?,
+        },
+        evaluations: Evaluations {
+            ql: evals[0],
+            qr: evals[1],
+            qm: evals[2],
+            qo: evals[3],
+            qc: evals[4],
+            s1: evals[5],
+            s2: evals[6],
+            s3: evals[7],
+            a: evals[8],
+            b: evals[9],
+            c: evals[10],
+            z: evals[11],
+            zw: evals[12],
+            t1w: evals[13],
+            t2w: evals[14],
+            inv: evals[15],
+        },
+    };
+
+    Ok((proof, pub_input))
+}
+
+/// Run the verifier the way `fflonkVerifier.sol` does, with the Keccak256 transcript: every
+/// curve operation of computeFEJ and checkPairing goes through the precompiles, and a failing
+/// precompile fails the proof. Returns the verification result along with the metered gas, a
+/// lower bound of the gas of the contract, see `GasReport`.
+pub fn fflonk_evm_verifier(
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+) -> (bool, GasReport) {
+    let mut evm = Evm::default();
+    let res = run_fflonk_evm_verifier(&mut evm, vk, proof, pub_input).unwrap_or(false);
+    (res, evm.report)
+}
+
+/// The metered gas of `fflonk_evm_verifier` over a 2^power domain, whatever the rest of the
+/// verification key: the contract inlines it as constants. Like `GasReport`, it's a lower bound of
+/// the gas of the contract.
+///
+/// The calldata is taken as nonzero bytes only, the upper bound of its cost.
+pub fn gas_lower_bound(power: u8) -> GasReport {
+    let mut evm = Evm::default();
+    let nonzero = [1u8; WORD];
+
+    evm.enter(Stage::Intrinsic);
+    evm.charge_calldata(&[1u8; 4 + 25 * WORD]);
+
+    evm.enter(Stage::CheckInput);
+    evm.charge(opcode_gas(Stage::CheckInput, power));
+
+    evm.enter(Stage::Challenges);
+    evm.charge(opcode_gas(Stage::Challenges, power));
+    evm.expand_memory(TRANSCRIPT_MEMORY);
+    for words in [5, 1, 3, 16, 3] {
+        evm.keccak256(&nonzero.repeat(words));
+    }
+
+    evm.enter(Stage::Inversions);
+    evm.charge(opcode_gas(Stage::Inversions, power));
+    evm.expand_memory(INVERSE_ARRAY_MEMORY);
+
+    for stage in [Stage::Lagrange, Stage::Pi, Stage::R] {
+        evm.enter(stage);
+        evm.charge(opcode_gas(stage, power));
+    }
+
+    evm.enter(Stage::FEJ);
+    evm.charge(opcode_gas(Stage::FEJ, power));
+    let _ = evm.g1_mul_acc(&[0u8; 64], &[0u8; 64], &Fr::one());
+    let _ = evm.g1_mul_acc(&[0u8; 64], &[0u8; 64], &Fr::one());
+    let _ = evm.g1_mul_acc(&[0u8; 64], &[0u8; 64], &Fr::one());
+    let _ = evm.g1_mul_acc(&[0u8; 64], &[0u8; 64], &Fr::one());
+
+    evm.enter(Stage::Pairing);
+    evm.charge(opcode_gas(Stage::Pairing, power));
+    let _ = evm.ec_add(&[0u8; 128]);
+    let _ = evm.ec_add(&[0u8; 128]);
+    let _ = evm.g1_mul_acc(&[0u8; 64], &[0u8; 64], &Fr::one());
+    let _ = evm.ec_pairing(&[0u8; 2 * PAIR_SIZE]);
+
+    evm.report
+}
+
+fn run_fflonk_evm_verifier(
+    evm: &mut Evm,
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+) -> Result<bool, PrecompileError> {
+    evm.enter(Stage::Intrinsic);
+    evm.charge_calldata(&encode_calldata(proof, pub_input));
+
+    // The points and the evaluations of `Proof` are always on the curve and in the field.
+    evm.enter(Stage::CheckInput);
+    evm.charge(opcode_gas(Stage::CheckInput, vk.power));
+
+    // beta = H(C0 || PI || C1), gamma = H(beta), xi_seed = H(gamma || C2), alpha = H(xi_seed || evaluations), y = H(alpha || W1)
+    evm.enter(Stage::Challenges);
+    evm.charge(opcode_gas(Stage::Challenges, vk.power));
+    evm.expand_memory(TRANSCRIPT_MEMORY);
+    let challenges = Challenges::compute::<Keccak256TranscriptHash>(vk, proof, pub_input);
+    for words in [5, 1, 3, 16, 3] {
+        evm.keccak256(&vec![0u8; words * WORD]);
+    }
+
+    evm.enter(Stage::Inversions);
+    evm.charge(opcode_gas(Stage::Inversions, vk.power));
+    evm.expand_memory(INVERSE_ARRAY_MEMORY);
+    let Some(inversion) = Inversion::try_build(vk, proof, &challenges) else {
+        return Ok(false);
+    };
+
+    evm.enter(Stage::Lagrange);
+    evm.charge(opcode_gas(Stage::Lagrange, vk.power));
+    let L_1 = LangrangePolynomialEvaluation::compute_L1_polynomial_evaluation(
+        &challenges.zh,
+        &inversion.eval_l1,
+    );
+
+    evm.enter(Stage::Pi);
+    evm.charge(opcode_gas(Stage::Pi, vk.power));
+    let pi = compute_pi(&[*pub_input], &[L_1]);
+
+    evm.enter(Stage::R);
+    evm.charge(opcode_gas(Stage::R, vk.power));
+    let (R0, R1, R2) = compute_r(vk, proof, &challenges, &inversion, &L_1, &pi);
+
+    // computeFEJ
+    evm.enter(Stage::FEJ);
+    evm.charge(opcode_gas(Stage::FEJ, vk.power));
+    let (quotient1, quotient2, e_scalar, numerator) =
+        FEJ::compute_scalars(&challenges, &inversion, R0, R1, R2);
+    let polynomials = &proof.polynomials;
+    let [c1, c2, w1, w2] = [
+        polynomials.c1,
+        polynomials.c2,
+        polynomials.w1,
+        polynomials.w2,
+    ]
+    .map(|p| encode_g1(&p.into_affine()));
+    let zero = encode_g1(&G1Affine::zero());
+
+    let f = evm.g1_mul_acc(&encode_g1(&vk.c0), &c1, &quotient1)?;
+    let f = evm.g1_mul_acc(&f, &c2, &quotient2)?;
+    let e = evm.g1_mul_acc(&zero, &encode_g1(&vk.g1), &e_scalar)?;
+    let j = evm.g1_mul_acc(&zero, &w1, &numerator)?;
+
+    // checkPairing: A1 = F - E - J + y·W2, then e(A1, [1]_2)·e(-W2, X_2) = 1
+    evm.enter(Stage::Pairing);
+    evm.charge(opcode_gas(Stage::Pairing, vk.power));
+    let a1 = evm.ec_add(&[f.as_slice(), &negate_g1(&e)].concat())?;
+    let a1 = evm.ec_add(&[a1.as_slice(), &negate_g1(&j)].concat())?;
+    let a1 = evm.g1_mul_acc(&a1, &w2, &challenges.y)?;
+
+    let input = [
+        a1.as_slice(),
+        &encode_g2(&vk.g2),
+        &negate_g1(&w2),
+        &encode_g2(&vk.x2),
+    ]
+    .concat();
+    let res = evm.ec_pairing(&input)?;
+    Ok(res == encode_bool(true))
+}
+
+// -P of the contract: (x, (q - y) mod q)
+fn negate_g1(p: &[u8; 64]) -> [u8; 64] {
+    let y = Fq::from_be_bytes_mod_order(&p[WORD..]);
+    let mut negated = *p;
+    negated[WORD..].copy_from_slice(&encode_fq(&-y));
+    negated
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+    use ark_bn254::G1Projective;
+    use ark_ff::UniformRand;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_ec_pairing() {
+        let rng = &mut ark_std::test_rng();
+        let (p, q, x) = (G1Affine::rand(rng), G2Affine::rand(rng), Fr::rand(rng));
+
+        // e(x·P, Q)·e(-P, x·Q) = 1
+        let input = [
+            encode_g1(&(p * x).into_affine()).to_vec(),
+            encode_g2(&q).to_vec(),
+            encode_g1(&(-p)).to_vec(),
+            encode_g2(&(q * x).into_affine()).to_vec(),
+        ]
+        .concat();
+        assert_eq!(ec_pairing(&input), Ok(encode_bool(true)));
+        assert_eq!(ec_pairing(&input[..PAIR_SIZE]), Ok(encode_bool(false)));
+        // the empty product is 1
+        assert_eq!(ec_pairing(&[]), Ok(encode_bool(true)));
+
+        assert_eq!(
+            ec_pairing(&input[1..]),
+            Err(PrecompileError::InvalidInputLength)
+        );
+
+        let mut not_on_curve = input.clone();
+        not_on_curve[G1_SIZE - 1] ^= 1;
+        assert_eq!(ec_pairing(&not_on_curve), Err(PrecompileError::NotOnCurve));
+
+        let mut not_canonical = input.clone();
+        not_canonical[..WORD].copy_from_slice(&[0xff; WORD]);
+        assert_eq!(
+            ec_pairing(&not_canonical),
+            Err(PrecompileError::InvalidFieldElement)
+        );
+    }
+
+    #[test]
+    fn test_ec_add_and_ec_mul() {
+        let rng = &mut ark_std::test_rng();
+        let (p, q, s) = (G1Affine::rand(rng), G1Affine::rand(rng), Fr::rand(rng));
+
+        let sum = ec_add(&[encode_g1(&p), encode_g1(&q)].concat()).unwrap();
+        assert_eq!(decode_g1(&sum), Ok((p + q).into_affine()));
+        // right-padded: P + 0
+        assert_eq!(ec_add(&encode_g1(&p)), Ok(encode_g1(&p)));
+
+        let product = ec_mul(&[encode_g1(&p).as_slice(), &encode_fr(&s)].concat()).unwrap();
+        assert_eq!(decode_g1(&product), Ok((p * s).into_affine()));
+        // the scalar isn't reduced: (r + 1)·P = P
+        let mut r_plus_one = [0u8; WORD];
+        r_plus_one.copy_from_slice(&Fr::MODULUS.to_bytes_be());
+        r_plus_one[WORD - 1] += 1;
+        assert_eq!(
+            ec_mul(&[encode_g1(&p).as_slice(), &r_plus_one].concat()),
+            Ok(encode_g1(&p))
+        );
+
+        let mut not_on_curve = encode_g1(&p);
+        not_on_curve[WORD] ^= 1;
+        assert_eq!(ec_add(&not_on_curve), Err(PrecompileError::NotOnCurve));
+    }
+
+    #[test]
+    fn test_fflonk_evm_verifier() {
+        let vk = VerificationKey::default();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+
+        let (res, report) = fflonk_evm_verifier(&vk, &proof, &pub_input);
+        assert!(res);
+
+        // 5 ecMul, 7 ecAdd and 1 ecPairing of 2 pairs
+        let fej = report.stage(Stage::FEJ);
+        assert_eq!((fej.ec_mul, fej.ec_add), (4, 4));
+        assert_eq!(
+            fej.gas,
+            opcode_gas(Stage::FEJ, vk.power) + 4 * (6_000 + 100) + 4 * (150 + 100)
+        );
+        let pairing = report.stage(Stage::Pairing);
+        assert_eq!((pairing.ec_mul, pairing.ec_add), (1, 3));
+        assert_eq!((pairing.ec_pairing, pairing.pairs), (1, 2));
+        assert_eq!(
+            pairing.gas,
+            opcode_gas(Stage::Pairing, vk.power) + 6_100 + 3 * 250 + 100 + 45_000 + 2 * 34_000
+        );
+        // beta over 160 bytes, gamma over 32, xi_seed over 96, alpha over 512 and y over 96
+        let challenges = report.stage(Stage::Challenges);
+        assert_eq!(challenges.keccak256, 5);
+        // and the memory up to the transcript of alpha, 80 words
+        assert_eq!(
+            challenges.gas,
+            opcode_gas(Stage::Challenges, vk.power)
+                + 5 * 30
+                + (5 + 1 + 3 + 16 + 3) * 6
+                + (3 * 80 + 80 * 80 / 512)
+        );
+
+        let calldata = encode_calldata(&proof, &pub_input);
+        assert_eq!(calldata.len(), 4 + 25 * 32);
+        let bound = gas_lower_bound(vk.power);
+        assert!(report.stage(Stage::Intrinsic).gas <= bound.stage(Stage::Intrinsic).gas);
+        for stage in &Stage::ALL[1..] {
+            assert_eq!(report.stage(*stage), bound.stage(*stage));
+        }
+
+        // the field arithmetic and the memory of inverseArray
+        assert_eq!(
+            report.stage(Stage::Inversions).gas,
+            opcode_gas(Stage::Inversions, vk.power) + 20
+        );
+        for stage in [Stage::CheckInput, Stage::Lagrange, Stage::Pi, Stage::R] {
+            assert_eq!(report.stage(stage).gas, opcode_gas(stage, vk.power));
+        }
+
+        let (res, report) = fflonk_evm_verifier(&vk, &proof, &(pub_input + Fr::one()));
+        assert!(!res);
+        assert_eq!(report.stage(Stage::Pairing), StageGas::default());
+    }
+
+    #[test]
+    fn test_gas_lower_bound() {
+        let report = gas_lower_bound(3);
+        assert_eq!(
+            report.total(),
+            // intrinsic + calldata
+            21_000 + (4 + 25 * 32) * 16
+                // keccak256
+                + 5 * 30 + 28 * 6
+                // 5 ecMul, 7 ecAdd and ecPairing
+                + 5 * 6_100 + 7 * 250 + 100 + 45_000 + 2 * 34_000
+                // the opcodes of the stages
+                + 332 + 714 + 3_285 + 26 + 34 + 3_541 + 632 + 352
+                // memory: 80 words for the transcript, then 86 for inverseArray
+                + (3 * 80 + 80 * 80 / 512) + 20
+        );
+        // a mulmod for each squaring of xi
+        assert_eq!(gas_lower_bound(24).total() - report.total(), 21 * 8);
+    }
+
+    #[test]
+    fn test_memory_expansion() {
+        let mut evm = Evm::default();
+        evm.expand_memory(32 * WORD);
+        assert_eq!(evm.report().total(), 3 * 32 + 2);
+        // no expansion within the memory so far
+        evm.expand_memory(WORD);
+        evm.expand_memory(32 * WORD - 1);
+        assert_eq!(evm.report().total(), 3 * 32 + 2);
+        evm.expand_memory(32 * WORD + 1);
+        assert_eq!(evm.report().total(), 3 * 33 + 2);
+    }
+
+    // The end of the block opened before `start`.
+    fn block_end(s: &str, start: usize) -> usize {
+        let mut depth = 1;
+        for (i, c) in s[start..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return start + i;
+            }
+        }
+        panic!("unbalanced block");
+    }
+
+    // The bodies of the Yul functions of the contract by name, without the comments and the
+    // branches of the `if` statements, which all revert.
+    fn yul_functions(sol: &str) -> HashMap<String, String> {
+        let assembly = &sol[sol.find("assembly {").unwrap()..];
+        let code = assembly
+            .lines()
+            .map(|line| line.split("//").next().unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let without_ifs = |body: &str| {
+            let mut out = String::new();
+            let mut rest = body;
+            while let Some(i) = rest
+                .match_indices("if ")
+                .map(|(i, _)| i)
+                .find(|i| *i == 0 || !rest.as_bytes()[i - 1].is_ascii_alphanumeric())
+            {
+                let open = i + rest[i..].find('{').unwrap();
+                out.push_str(&rest[..open]);
+                rest = &rest[block_end(rest, open + 1) + 1..];
+            }
+            out + rest
+        };
+
+        let mut functions = HashMap::new();
+        let mut rest = code.as_str();
+        while let Some(i) = rest.find("function ") {
+            let name = rest[i + 9..].split('(').next().unwrap().to_string();
+            let open = i + rest[i..].find('{').unwrap() + 1;
+            let end = block_end(rest, open);
+            functions.insert(name, without_ifs(&rest[open..end]));
+            rest = &rest[end + 1..];
+        }
+        functions
+    }
+
+    // The opcode gas of a Yul function, with the one of the functions it calls.
+    fn yul_gas(functions: &HashMap<String, String>, name: &str) -> u64 {
+        let body = &functions[name];
+        let mut gas = 0;
+        for (i, _) in body.match_indices('(') {
+            let call = body[..i]
+                .rsplit(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .next()
+                .unwrap();
+            gas += match call {
+                "" | "keccak256" | "staticcall" | "return" => 0,
+                "gas" => 2,
+                "mul" | "mod" => 5,
+                "addmod" | "mulmod" => 8,
+                "add" | "sub" | "lt" | "eq" | "iszero" | "and" | "mload" | "mstore"
+                | "calldataload" => 3,
+                // and 3 a word copied
+                "calldatacopy" => {
+                    let args = &body[i + 1..i + body[i..].find(")\n").unwrap()];
+                    let size: u64 = args.rsplit(',').next().unwrap().trim().parse().unwrap();
+                    3 + 3 * size.div_ceil(WORD as u64)
+                }
+                _ => yul_gas(functions, call),
+            };
+        }
+        gas
+    }
+
+    #[test]
+    fn test_opcode_gas() {
+        // the contract of a 2^3 domain
+        let functions = yul_functions(include_str!("../resources/circom/verifier.sol"));
+        let gas =
+            |names: &[&str]| -> u64 { names.iter().map(|name| yul_gas(&functions, name)).sum() };
+        let power = 3;
+        for (stage, names) in [
+            (Stage::CheckInput, &["checkInput"][..]),
+            (Stage::Challenges, &["computeChallenges"]),
+            (Stage::Inversions, &["computeInversions"]),
+            (Stage::Lagrange, &["computeLagrange"]),
+            (Stage::Pi, &["computePi"]),
+            (Stage::R, &["computeR0", "computeR1", "computeR2"]),
+            (Stage::FEJ, &["computeFEJ"]),
+            (Stage::Pairing, &["checkPairing"]),
+        ] {
+            assert_eq!(opcode_gas(stage, power), gas(names), "{stage:?}");
+        }
+    }
+
+    #[test]
+    fn test_encoding() {
+        let rng = &mut ark_std::test_rng();
+        let p = G1Projective::rand(rng).into_affine();
+        let q = G2Affine::rand(rng);
+        assert_eq!(decode_g1(&encode_g1(&p)), Ok(p));
+        assert_eq!(decode_g2(&encode_g2(&q)), Ok(q));
+        assert_eq!(decode_g1(&[0u8; G1_SIZE]), Ok(G1Affine::zero()));
+        assert_eq!(decode_g2(&[0u8; G2_SIZE]), Ok(G2Affine::zero()));
+        // a truncated point
+        let short = encode_g1(&p);
+        assert_eq!(
+            decode_g1(&short[..WORD]),
+            Err(PrecompileError::InvalidInputLength)
+        );
+        assert_eq!(decode_g2(&[]), Err(PrecompileError::InvalidInputLength));
+    }
+
+    #[test]
+    fn test_decode_calldata() {
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        let calldata = encode_calldata(&proof, &pub_input);
+        assert_eq!(decode_calldata(&calldata), Ok((proof, pub_input)));
+
+        // the selector of two public inputs, which the contract doesn't have, and the calldata
+        // of a public input more or a truncated word
+        let mut hasher = Keccak::v256();
+        hasher.update(b"verifyProof(bytes32[24],uint256[2])");
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        let two = [&hash[..4], &calldata[4..], &encode_fr(&Fr::from(33u64))].concat();
+        assert_eq!(
+            decode_calldata(&two),
+            Err(PrecompileError::InvalidInputLength)
+        );
+        let wrong = [&hash[..4], &calldata[4..]].concat();
+        assert_eq!(
+            decode_calldata(&wrong),
+            Err(PrecompileError::InvalidSelector)
+        );
+        assert_eq!(
+            decode_calldata(&calldata[..calldata.len() - 1]),
+            Err(PrecompileError::InvalidInputLength)
+        );
+        // an evaluation of the modulus, and a point off the curve
+        let mut modulus = calldata.clone();
+        let offset = 4 + 4 * G1_SIZE;
+        modulus[offset..offset + WORD].copy_from_slice(&Fr::MODULUS.to_bytes_be());
+        assert_eq!(
+            decode_calldata(&modulus),
+            Err(PrecompileError::InvalidFieldElement)
+        );
+        let mut off_curve = calldata;
+        off_curve[4 + WORD - 1] ^= 1;
+        assert_eq!(
+            decode_calldata(&off_curve),
+            Err(PrecompileError::NotOnCurve)
+        );
+    }
+}
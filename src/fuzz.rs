@@ -0,0 +1,272 @@
+//! The bodies of the libFuzzer targets of `fuzz/`, one function per target over the raw input.
+//!
+//! They live in the library so that `tests/fuzz_regressions.rs` replays the seeds and the
+//! regressions of `fuzz/` under `cargo test`, without the nightly toolchain of cargo-fuzz.
+//! An input is untrusted: a target may reject it, but must never panic on it, except on the
+//! assertions of its own oracle.
+//!
+//! Only built for the tests, or with the feature `test-utils`, which `fuzz/` enables.
+use crate::circom::{wtns_from_bytes, CircomCircuit, R1CS};
+use crate::evm::{
+    decode_calldata, ec_add, ec_mul, ec_pairing, encode_calldata, encode_g1, fflonk_evm_verifier,
+};
+use crate::mock::MOCK_PROOF_DATA;
+use crate::pairing::{ArkworksBackend, EvmPrecompileBackend};
+use crate::proof::{Evaluations, Polynomials, Proof};
+use crate::ptau::Ptau;
+use crate::stepwise::VerifierState;
+use crate::transcript::Keccak256TranscriptHash;
+use crate::verifier::{fflonk_verifier, fflonk_verifier_with_backend};
+use crate::vk::{SnarkJSVK, VerificationKey};
+use crate::zkey::FflonkZKey;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+
+const WORD: usize = 32;
+
+// The G1 powers read from a `.ptau`, as many as a ceremony of power 1 has.
+const PTAU_G1: usize = 3;
+
+/// The verification keys of the target `verifier`, picked by the first byte of its input: the
+/// one of `MOCK_PROOF_DATA`, then the ones of the BN254 proofs of `resources/`.
+pub static VERIFICATION_KEYS: Lazy<Vec<VerificationKey>> = Lazy::new(|| {
+    let json = [
+        include_str!("../resources/circom/verification_key.json"),
+        include_str!("../resources/trapdoor/bn254/verification_key.json"),
+    ];
+    std::iter::once(VerificationKey::default())
+        .chain(json.map(|json| serde_json::from_str::<SnarkJSVK>(json).unwrap().into()))
+        .collect()
+});
+
+/// The target `proof_json`: the JSON of a snarkjs proof on BN254 and BLS12-381, and the 24
+/// values of `Proof::construct`. A parsed proof is written back as it was read, and goes
+/// through the verifier.
+pub fn proof_json(data: &[u8]) {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Some(proof) = round_trip::<Proof<Bn254>>(json) {
+        let vk = &VERIFICATION_KEYS[0];
+        let pub_input = Fr::from(33u64);
+        fflonk_verifier::<Keccak256TranscriptHash>(vk, &proof, &pub_input, false);
+    }
+    round_trip::<Proof<Bls12_381>>(json);
+
+    if let Ok(values) = serde_json::from_str::<Vec<String>>(json) {
+        let values = values.iter().map(String::as_str).collect::<Vec<_>>();
+        let _ = Proof::<Bn254>::try_construct(&values);
+    }
+}
+
+/// The target `vk_json`: the JSON of a snarkjs verification key on BN254 and BLS12-381, written
+/// back as it was read. The key of BN254 verifies `MOCK_PROOF_DATA`, whatever its domain.
+pub fn vk_json(data: &[u8]) {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Some(snarkjs) = round_trip::<SnarkJSVK<Bn254>>(json) {
+        let vk = VerificationKey::from(snarkjs);
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from(33u64);
+        fflonk_verifier::<Keccak256TranscriptHash>(&vk, &proof, &pub_input, false);
+    }
+    if let Some(snarkjs) = round_trip::<SnarkJSVK<Bls12_381>>(json) {
+        VerificationKey::from(snarkjs).digest::<Keccak256TranscriptHash>();
+    }
+}
+
+/// The target `binfile`: the binary files of iden3, `.r1cs`, `.wtns`, `.zkey` and `.ptau`, which
+/// are told apart by their magic.
+pub fn binfile(data: &[u8]) {
+    if let Ok(r1cs) = R1CS::<Fr>::from_bytes(data) {
+        CircomCircuit::from_r1cs(&r1cs);
+    }
+    let _ = wtns_from_bytes::<Fr>(data);
+    let _ = FflonkZKey::<Bn254>::from_bytes(data, false);
+    let _ = FflonkZKey::<Bn254>::from_bytes(data, true);
+    if let Ok(ptau) = Ptau::<Bn254>::from_bytes(data, PTAU_G1) {
+        let _ = ptau.check(&mut ark_std::test_rng());
+    }
+}
+
+/// The target `calldata`: the calldata of `verifyProof`, which a decoded proof encodes back to,
+/// and the inputs of the precompiles ecAdd, ecMul and ecPairing.
+pub fn calldata(data: &[u8]) {
+    if let Ok((proof, pub_input)) = decode_calldata(data) {
+        assert_eq!(encode_calldata(&proof, &pub_input), data);
+    }
+    let _ = ec_add(data);
+    let _ = ec_mul(data);
+    let _ = ec_pairing(data);
+}
+
+/// The target `verifier`: the verification key of the first byte, then the proof and the public
+/// input of `structured_proof`. Every way of verifying must come to the same result.
+pub fn verifier(data: &[u8]) {
+    let Some((index, data)) = data.split_first() else {
+        return;
+    };
+    let vk = &VERIFICATION_KEYS[*index as usize % VERIFICATION_KEYS.len()];
+    let (proof, pub_input) = structured_proof(data);
+
+    let verified = fflonk_verifier::<Keccak256TranscriptHash>(vk, &proof, &pub_input, false);
+    assert_eq!(
+        fflonk_verifier::<Keccak256TranscriptHash>(vk, &proof, &pub_input, true),
+        verified
+    );
+    assert_eq!(
+        fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+            &EvmPrecompileBackend,
+            vk,
+            &proof,
+            &pub_input
+        ),
+        verified
+    );
+    assert_eq!(fflonk_evm_verifier(vk, &proof, &pub_input).0, verified);
+    assert_eq!(
+        VerifierState::default()
+            .run::<Keccak256TranscriptHash, _>(&ArkworksBackend, vk, &proof, &pub_input)
+            .unwrap(),
+        verified
+    );
+}
+
+/// The proof and the public input of 25 big-endian words, as in the calldata without its
+/// selector, the missing bytes being zeros: any input is a proof, which the fuzzer mutates
+/// word by word.
+///
+/// Every word is reduced into its field, and the coordinates (x, y) off the curve are the point
+/// x·[1]_1 instead, so that the proof goes past the checks of its points.
+pub fn structured_proof(data: &[u8]) -> (Proof, Fr) {
+    let mut words = data.chunks(WORD).map(|chunk| {
+        let mut word = [0u8; WORD];
+        word[..chunk.len()].copy_from_slice(chunk);
+        word
+    });
+    let mut word = || words.next().unwrap_or([0u8; WORD]);
+    let mut point = || {
+        let (x, y) = (word(), word());
+        let p = G1Affine::new_unchecked(
+            Fq::from_be_bytes_mod_order(&x),
+            Fq::from_be_bytes_mod_order(&y),
+        );
+        if p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve() {
+            p.into_group()
+        } else {
+            G1Affine::generator() * Fr::from_be_bytes_mod_order(&x)
+        }
+    };
+    let polynomials = Polynomials {
+        c1: point(),
+        c2: point(),
+        w1: point(),
+        w2: point(),
+    };
+    let mut fr = || Fr::from_be_bytes_mod_order(&word());
+    let evaluations = Evaluations {
+        ql: fr(),
+        qr: fr(),
+        qm: fr(),
+        qo: fr(),
+        qc: fr(),
+        s1: fr(),
+        s2: fr(),
+        s3: fr(),
+        a: fr(),
+        b: fr(),
+        c: fr(),
+        z: fr(),
+        zw: fr(),
+        t1w: fr(),
+        t2w: fr(),
+        inv: fr(),
+    };
+    let proof = Proof {
+        polynomials,
+        evaluations,
+    };
+    (proof, fr())
+}
+
+/// The input of the target `verifier` for the key at `index` of `VERIFICATION_KEYS`, the
+/// inverse of `structured_proof` for the points on the curve.
+pub fn encode_structured_proof(index: u8, proof: &Proof, pub_input: &Fr) -> Vec<u8> {
+    let (polynomials, evaluations) = (&proof.polynomials, &proof.evaluations);
+    let points = [
+        polynomials.c1,
+        polynomials.c2,
+        polynomials.w1,
+        polynomials.w2,
+    ];
+    // the point at infinity is 0·[1]_1
+    let points = G1Projective::normalize_batch(&points)
+        .iter()
+        .flat_map(encode_g1)
+        .collect::<Vec<_>>();
+    let evals = [
+        evaluations.ql,
+        evaluations.qr,
+        evaluations.qm,
+        evaluations.qo,
+        evaluations.qc,
+        evaluations.s1,
+        evaluations.s2,
+        evaluations.s3,
+        evaluations.a,
+        evaluations.b,
+        evaluations.c,
+        evaluations.z,
+        evaluations.zw,
+        evaluations.t1w,
+        evaluations.t2w,
+        evaluations.inv,
+        *pub_input,
+    ];
+    let evals = evals.iter().flat_map(|f| f.into_bigint().to_bytes_be());
+    std::iter::once(index).chain(points).chain(evals).collect()
+}
+
+// Parse the JSON, and check that it's written back as it was read.
+fn round_trip<T: Serialize + DeserializeOwned + Debug + PartialEq>(json: &str) -> Option<T> {
+    let value = serde_json::from_str::<T>(json).ok()?;
+    let written = serde_json::to_string(&value).unwrap();
+    assert_eq!(serde_json::from_str::<T>(&written).unwrap(), value);
+    Some(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::MOCK_PUB_INPUT;
+    use ark_ff::{One, Zero};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_structured_proof() {
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        let data = encode_structured_proof(0, &proof, &pub_input);
+        assert_eq!(data.len(), 1 + 25 * WORD);
+        assert_eq!(structured_proof(&data[1..]), (proof, pub_input));
+        verifier(&data);
+
+        // zeros, and the point (1, 1) off the curve which is [1]_1
+        let (proof, pub_input) = structured_proof(&[]);
+        assert!(proof.polynomials.c1.is_zero());
+        assert_eq!(pub_input, Fr::from(0u64));
+        let mut data = [0u8; 2 * WORD];
+        data[WORD - 1] = 1;
+        data[2 * WORD - 1] = 1;
+        let (proof, _) = structured_proof(&data);
+        assert_eq!(proof.polynomials.c1, G1Affine::generator() * Fr::one());
+        verifier(&[]);
+    }
+}
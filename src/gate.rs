@@ -0,0 +1,700 @@
+use crate::challenge::Challenges;
+use crate::curve::SWPoint;
+use crate::opening::{CommitmentConfig, OpeningChallenges, OpeningProof, OpeningSetConfig};
+use crate::proof::{Polynomials, Proof};
+use crate::transcript::TranscriptHash;
+use crate::utils::{compute_pi, compute_zero_poly_evals, LangrangePolynomialEvaluation};
+use crate::vk::VerificationKey;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_ff::{Field, One, PrimeField, Zero};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// A monomial of a `GateEquation`: coefficient · ∏ selectors · ∏ wires,
+/// where the selectors and the wires are indices into the ones of the equation.
+///
+/// Eg: `{"selectors": [4], "wires": [0, 1]}` is qm·a·b of the vanilla gate.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GateTerm {
+    #[serde(default = "GateTerm::default_coefficient")]
+    pub coefficient: i64,
+    #[serde(default)]
+    pub selectors: Vec<usize>,
+    #[serde(default)]
+    pub wires: Vec<usize>,
+}
+
+impl GateTerm {
+    pub fn new(coefficient: i64, selectors: Vec<usize>, wires: Vec<usize>) -> Self {
+        Self {
+            coefficient,
+            selectors,
+            wires,
+        }
+    }
+
+    fn default_coefficient() -> i64 {
+        1
+    }
+}
+
+/// A gate equation read from a descriptor: the sum of its `terms`.
+///
+/// The selectors are committed in C0 in the listed order (then the permutation polynomials),
+/// and the wires in C1 (then T0).
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GateEquation {
+    pub selectors: Vec<String>,
+    pub wires: Vec<String>,
+    pub terms: Vec<GateTerm>,
+}
+
+impl GateEquation {
+    // The snarkjs gate: ql·a + qr·b + qo·c + qm·a·b + qc, the one of `compute_r::compute_t0`.
+    pub fn vanilla() -> Self {
+        Self {
+            selectors: ["ql", "qr", "qo", "qm", "qc"].map(String::from).to_vec(),
+            wires: ["a", "b", "c"].map(String::from).to_vec(),
+            terms: vec![
+                GateTerm::new(1, vec![0], vec![0]),
+                GateTerm::new(1, vec![1], vec![1]),
+                GateTerm::new(1, vec![2], vec![2]),
+                GateTerm::new(1, vec![3], vec![0, 1]),
+                GateTerm::new(1, vec![4], vec![]),
+            ],
+        }
+    }
+
+    // The 4-wire gate: ql·a + qr·b + qo·c + q4·d + qm·a·b + qc.
+    pub fn four_wire() -> Self {
+        Self {
+            selectors: ["ql", "qr", "qo", "q4", "qm", "qc"]
+                .map(String::from)
+                .to_vec(),
+            wires: ["a", "b", "c", "d"].map(String::from).to_vec(),
+            terms: vec![
+                GateTerm::new(1, vec![0], vec![0]),
+                GateTerm::new(1, vec![1], vec![1]),
+                GateTerm::new(1, vec![2], vec![2]),
+                GateTerm::new(1, vec![3], vec![3]),
+                GateTerm::new(1, vec![4], vec![0, 1]),
+                GateTerm::new(1, vec![5], vec![]),
+            ],
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(gate_path: P) -> anyhow::Result<Self> {
+        let mut file = File::open(gate_path)?;
+        let mut gate_json = String::new();
+        file.read_to_string(&mut gate_json)?;
+        let gate: Self = serde_json::from_str(&gate_json)?;
+        gate.validate()?;
+        Ok(gate)
+    }
+
+    // Check every term only refers to the selectors and the wires of the equation.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.wires.is_empty() {
+            anyhow::bail!("The gate has no wire");
+        }
+        for (i, term) in self.terms.iter().enumerate() {
+            if let Some(s) = term.selectors.iter().find(|s| **s >= self.selectors.len()) {
+                anyhow::bail!("Term {i}: unknown selector {s}");
+            }
+            if let Some(w) = term.wires.iter().find(|w| **w >= self.wires.len()) {
+                anyhow::bail!("Term {i}: unknown wire {w}");
+            }
+        }
+        Ok(())
+    }
+
+    // The number of selectors and of wires of the gate.
+    pub fn arity(&self) -> (usize, usize) {
+        (self.selectors.len(), self.wires.len())
+    }
+
+    // The gate over the selectors and the wires, the verifier evaluates at xi for
+    //      T0(xi) = (gate(selectors(xi), wires(xi)) + PI(xi)) / Z_H(xi)
+    // `None` if they don't match `arity`, or if a term refers to an unknown selector or wire,
+    // see `validate`.
+    pub fn evaluate<F: Field>(&self, selectors: &[F], wires: &[F]) -> Option<F> {
+        if (selectors.len(), wires.len()) != self.arity() || self.validate().is_err() {
+            return None;
+        }
+        let gate = self
+            .terms
+            .iter()
+            .map(|term| {
+                let coefficient = if term.coefficient >= 0 {
+                    F::from(term.coefficient as u64)
+                } else {
+                    -F::from(term.coefficient.unsigned_abs())
+                };
+                term.selectors.iter().map(|s| selectors[*s]).product::<F>()
+                    * term.wires.iter().map(|w| wires[*w]).product::<F>()
+                    * coefficient
+            })
+            .sum();
+        Some(gate)
+    }
+}
+
+/// The evaluations at xi of a proof of a custom gate arithmetization.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CustomGateEvaluations<E: Pairing = Bn254> {
+    // In the order of `GateEquation::selectors`.
+    #[serde(with = "crate::serde::frs")]
+    pub selectors: Vec<E::ScalarField>,
+    // The permutation polynomials, one per wire.
+    #[serde(with = "crate::serde::frs")]
+    pub sigmas: Vec<E::ScalarField>,
+    // In the order of `GateEquation::wires`.
+    #[serde(with = "crate::serde::frs")]
+    pub wires: Vec<E::ScalarField>,
+    #[serde(with = "crate::serde::fr")]
+    pub z: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub zw: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub t1w: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub t2w: E::ScalarField,
+}
+
+/// A fflonk proof of a custom gate arithmetization, see `Arithmetization`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "E::G1: SWPoint<BaseField = E::BaseField>")]
+pub struct CustomGateProof<E: Pairing = Bn254> {
+    pub polynomials: Polynomials<E>,
+    pub evaluations: CustomGateEvaluations<E>,
+}
+
+// The snarkjs proof, as the one of `Arithmetization::vanilla`.
+impl<E: Pairing> From<Proof<E>> for CustomGateProof<E> {
+    fn from(proof: Proof<E>) -> Self {
+        let evaluations = proof.evaluations;
+        Self {
+            polynomials: proof.polynomials,
+            evaluations: CustomGateEvaluations {
+                selectors: vec![
+                    evaluations.ql,
+                    evaluations.qr,
+                    evaluations.qo,
+                    evaluations.qm,
+                    evaluations.qc,
+                ],
+                sigmas: vec![evaluations.s1, evaluations.s2, evaluations.s3],
+                wires: vec![evaluations.a, evaluations.b, evaluations.c],
+                z: evaluations.z,
+                zw: evaluations.zw,
+                t1w: evaluations.t1w,
+                t2w: evaluations.t2w,
+            },
+        }
+    }
+}
+
+/// The challenges of a custom gate proof, up to xi: the opening argument squeezes the others
+/// from gamma, see `opening::verify_openings`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct GateChallenges<E: Pairing = Bn254> {
+    pub beta: E::ScalarField,
+    pub gamma: E::ScalarField,
+    pub xi_seed: E::ScalarField,
+    pub xi: E::ScalarField,
+    pub zh: E::ScalarField,
+}
+
+impl<E: Pairing> GateChallenges<E> {
+    // The challenges of the snarkjs transcript, for `Arithmetization::vanilla`.
+    pub fn from_plonk<T: TranscriptHash>(proof: &Proof<E>, challenges: &Challenges<E>) -> Self {
+        Self {
+            beta: challenges.beta,
+            gamma: challenges.gamma,
+            xi_seed: OpeningChallenges::<E>::from_plonk::<T>(proof, challenges).xi_seed,
+            xi: challenges.xi,
+            zh: challenges.zh,
+        }
+    }
+
+    // The transcript of snarkjs up to gamma, then the one of `OpeningChallenges::compute` with
+    // gamma as the seed:
+    //      beta    = H(C0 || PI || C1)
+    //      gamma   = H(beta)
+    //      xi_seed = H(gamma || C0 || C1 || C2)
+    //      xi      = xi_seed^lcm, where lcm is the `OpeningSetConfig::xi_exponent` of the arithmetization
+    pub fn compute<T: TranscriptHash>(
+        arithmetization: &Arithmetization<E>,
+        vk: &VerificationKey<E>,
+        proof: &CustomGateProof<E>,
+        pub_input: &E::ScalarField,
+    ) -> anyhow::Result<Self> {
+        let config = arithmetization.opening_set_config()?;
+        let polynomials = &proof.polynomials;
+
+        let beta =
+            Challenges::<E>::compute_beta::<T>(&vk.c0, &polynomials.c1.into_affine(), pub_input);
+        let gamma = Challenges::<E>::compute_gamma::<T>(&beta);
+        let xi_seed = OpeningChallenges::compute_xi_seed::<T>(
+            vk,
+            &[polynomials.c1.into_affine(), polynomials.c2.into_affine()],
+            &gamma,
+        );
+        let xi = xi_seed.pow([config.xi_exponent()?]);
+
+        Ok(Self {
+            beta,
+            gamma,
+            xi_seed,
+            xi,
+            zh: compute_zero_poly_evals(&xi, &vk.n),
+        })
+    }
+}
+
+/// A PLONK arithmetization with a custom gate, laid out as:
+///     C0: the selectors and the permutation polynomials,
+///     C1: the wires and T0,
+///     C2: z, T1 and T2, opened at xi and xi·w,
+/// where C0 and C1 are padded with zero polynomials to a size with a root of unity.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Arithmetization<E: Pairing = Bn254> {
+    pub gate: GateEquation,
+    // The coset shifts of the wires in the permutation argument, eg: [1, k1, k2].
+    pub ks: Vec<E::ScalarField>,
+}
+
+impl<E: Pairing> Arithmetization<E> {
+    // The snarkjs arithmetization, the proofs of which pass `OpeningSetConfig::plonk()`.
+    pub fn vanilla(vk: &VerificationKey<E>) -> Self {
+        Self {
+            gate: GateEquation::vanilla(),
+            ks: vec![E::ScalarField::one(), vk.k1, vk.k2],
+        }
+    }
+
+    // The 4-wire arithmetization, k3 is the coset shift of the 4th wire.
+    pub fn four_wire(vk: &VerificationKey<E>, k3: E::ScalarField) -> Self {
+        Self {
+            gate: GateEquation::four_wire(),
+            ks: vec![E::ScalarField::one(), vk.k1, vk.k2, k3],
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.gate.validate()?;
+        if self.ks.len() != self.gate.wires.len() {
+            anyhow::bail!(
+                "Expect {} coset shifts, got {}",
+                self.gate.wires.len(),
+                self.ks.len()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn opening_set_config(&self) -> anyhow::Result<OpeningSetConfig> {
+        self.validate()?;
+        let wires = self.gate.wires.len();
+        Ok(OpeningSetConfig {
+            commitments: vec![
+                CommitmentConfig {
+                    polynomials: batch_size::<E::ScalarField>(self.gate.selectors.len() + wires),
+                    openings: vec![0],
+                },
+                CommitmentConfig {
+                    polynomials: batch_size::<E::ScalarField>(wires + 1),
+                    openings: vec![0],
+                },
+                CommitmentConfig {
+                    polynomials: 3,
+                    openings: vec![0, 1],
+                },
+            ],
+        })
+    }
+
+    // Compute T0(xi) = (gate(selectors, wires) + PI(xi)) / Z_H(xi)
+    // Returns `None` if the evaluations don't match the gate.
+    pub fn compute_t0(
+        &self,
+        evaluations: &CustomGateEvaluations<E>,
+        pi: &E::ScalarField,
+        zh_inv: &E::ScalarField,
+    ) -> Option<E::ScalarField> {
+        let gate = self
+            .gate
+            .evaluate(&evaluations.selectors, &evaluations.wires)?;
+        Some((gate + pi) * zh_inv)
+    }
+
+    // Compute T2(xi), the copy constraint over all the wires:
+    //      (∏ (wire_i + beta·k_i·xi + gamma)·z - ∏ (wire_i + beta·sigma_i + gamma)·zw) / Z_H(xi)
+    pub fn compute_t2(
+        &self,
+        evaluations: &CustomGateEvaluations<E>,
+        challenges: &GateChallenges<E>,
+        zh_inv: &E::ScalarField,
+    ) -> E::ScalarField {
+        let beta_xi = challenges.beta * challenges.xi;
+        let num = evaluations
+            .wires
+            .iter()
+            .zip(&self.ks)
+            .map(|(wire, k)| *wire + beta_xi * k + challenges.gamma)
+            .product::<E::ScalarField>();
+        let den = evaluations
+            .wires
+            .iter()
+            .zip(&evaluations.sigmas)
+            .map(|(wire, sigma)| *wire + challenges.beta * sigma + challenges.gamma)
+            .product::<E::ScalarField>();
+
+        (num * evaluations.z - den * evaluations.zw) * zh_inv
+    }
+
+    pub fn check_evaluations(&self, evaluations: &CustomGateEvaluations<E>) -> anyhow::Result<()> {
+        if evaluations.selectors.len() != self.gate.selectors.len()
+            || evaluations.wires.len() != self.gate.wires.len()
+            || evaluations.sigmas.len() != self.gate.wires.len()
+        {
+            anyhow::bail!(
+                "Expect {} selectors, {} wires and {} sigmas",
+                self.gate.selectors.len(),
+                self.gate.wires.len(),
+                self.gate.wires.len()
+            );
+        }
+        Ok(())
+    }
+
+    // Lay out the proof by `opening_set_config`, computing T0, T1 and T2 at xi.
+    pub fn opening_proof(
+        &self,
+        vk: &VerificationKey<E>,
+        proof: &CustomGateProof<E>,
+        challenges: &GateChallenges<E>,
+        pub_input: &E::ScalarField,
+    ) -> anyhow::Result<OpeningProof<E>> {
+        let config = self.opening_set_config()?;
+        let evaluations = &proof.evaluations;
+        self.check_evaluations(evaluations)?;
+        let (zh_inv, L_1, pi) = compute_lagrange(vk, challenges, pub_input)?;
+
+        let t0 = self
+            .compute_t0(evaluations, &pi, &zh_inv)
+            .ok_or_else(|| anyhow::anyhow!("The evaluations don't match the gate"))?;
+        let t1 = (evaluations.z - E::ScalarField::one()) * L_1 * zh_inv;
+        let t2 = self.compute_t2(evaluations, challenges, &zh_inv);
+
+        let c0 = [evaluations.selectors.clone(), evaluations.sigmas.clone()].concat();
+        let c1 = [evaluations.wires.clone(), vec![t0]].concat();
+
+        let polynomials = &proof.polynomials;
+        Ok(OpeningProof {
+            commitments: vec![polynomials.c1.into_affine(), polynomials.c2.into_affine()],
+            evaluations: vec![
+                vec![padded(c0, &config.commitments[0])],
+                vec![padded(c1, &config.commitments[1])],
+                vec![
+                    vec![evaluations.z, t1, t2],
+                    vec![evaluations.zw, evaluations.t1w, evaluations.t2w],
+                ],
+            ],
+            w1: polynomials.w1.into_affine(),
+            w2: polynomials.w2.into_affine(),
+        })
+    }
+}
+
+// Compute 1/Z_H(xi), L_1(xi) and PI(xi).
+pub(crate) fn compute_lagrange<E: Pairing>(
+    vk: &VerificationKey<E>,
+    challenges: &GateChallenges<E>,
+    pub_input: &E::ScalarField,
+) -> anyhow::Result<(E::ScalarField, E::ScalarField, E::ScalarField)> {
+    let zh_inv = challenges
+        .zh
+        .inverse()
+        .ok_or_else(|| anyhow::anyhow!("xi is a root of Z_H"))?;
+    let L_1 = challenges.zh
+        * LangrangePolynomialEvaluation::compute_L1_base(&challenges.xi, &vk.n)
+            .inverse()
+            .ok_or_else(|| anyhow::anyhow!("xi is 1"))?;
+    let pi = compute_pi(&[*pub_input], &[L_1]);
+    Ok((zh_inv, L_1, pi))
+}
+
+// Pad the evaluations of a commitment with the ones of zero polynomials.
+pub(crate) fn padded<F: Field>(mut evals: Vec<F>, commitment: &CommitmentConfig) -> Vec<F> {
+    evals.resize(commitment.polynomials, F::zero());
+    evals
+}
+
+// The smallest batch size t >= n with a t-th root of unity in F, eg: 8 for 8 and 12 for 10 on BN254.
+pub(crate) fn batch_size<F: PrimeField>(n: usize) -> usize {
+    let modulus_minus_one: BigUint = Into::<BigUint>::into(F::MODULUS) - 1u32;
+    (n.max(1)..)
+        .find(|t| (&modulus_minus_one % *t).is_zero())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compute_r::compute_t0;
+    use crate::inversion::Inversion;
+    use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+    use crate::opening::verify_openings_with_challenges;
+    use crate::prover::gate::{
+        fflonk_custom_gate_prover, CustomGateCircuit, CustomGateProvingKey, CustomGateWitness,
+    };
+    use crate::prover::Srs;
+    use crate::setup::compute_ks;
+    use crate::transcript::{Blake3TranscriptHash, Keccak256TranscriptHash};
+    use crate::verifier::fflonk_custom_gate_verifier;
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_gate_equation() {
+        let rng = &mut ark_std::test_rng();
+        let selectors = (0..5).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        let wires = (0..3).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        let (ql, qr, qo, qm, qc) = (
+            selectors[0],
+            selectors[1],
+            selectors[2],
+            selectors[3],
+            selectors[4],
+        );
+        let (a, b, c) = (wires[0], wires[1], wires[2]);
+        assert_eq!(
+            GateEquation::vanilla().evaluate(&selectors, &wires),
+            Some(ql * a + qr * b + qo * c + qm * a * b + qc)
+        );
+
+        // the selectors and the wires must match the arity of the gate
+        assert_eq!(GateEquation::four_wire().arity(), (6, 4));
+        let vanilla = GateEquation::vanilla();
+        assert_eq!(vanilla.evaluate(&selectors, &wires[..2]), None);
+        assert_eq!(vanilla.evaluate(&selectors[..4], &wires), None);
+        assert_eq!(GateEquation::four_wire().evaluate(&selectors, &wires), None);
+
+        // a range gate: qrange·(a^2 - a) + qarith·(a + b - c)
+        let gate: GateEquation = serde_json::from_str(
+            r#"{
+                "selectors": ["qrange", "qarith"],
+                "wires": ["a", "b", "c"],
+                "terms": [
+                    {"selectors": [0], "wires": [0, 0]},
+                    {"coefficient": -1, "selectors": [0], "wires": [0]},
+                    {"selectors": [1], "wires": [0]},
+                    {"selectors": [1], "wires": [1]},
+                    {"coefficient": -1, "selectors": [1], "wires": [2]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert!(gate.validate().is_ok());
+        let (one, zero) = (Fr::one(), Fr::zero());
+        let bits = [zero, one].map(|a| gate.evaluate(&[one, zero], &[a, zero, zero]));
+        assert_eq!(bits, [Some(zero), Some(zero)]);
+        assert_ne!(
+            gate.evaluate(&[one, zero], &[Fr::from(2u64), zero, zero]),
+            Some(zero)
+        );
+        let arith = gate.evaluate(
+            &[zero, one],
+            &[Fr::from(2u64), Fr::from(3u64), Fr::from(5u64)],
+        );
+        assert_eq!(arith, Some(zero));
+
+        let mut invalid = gate.clone();
+        invalid.terms[0].wires.push(3);
+        assert!(invalid.validate().is_err());
+        assert_eq!(invalid.evaluate(&[one, zero], &[one, zero, zero]), None);
+    }
+
+    #[test]
+    fn test_vanilla_gate_t0() {
+        let vk = VerificationKey::default();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+
+        let challenges = Challenges::compute::<Keccak256TranscriptHash>(&vk, &proof, &pub_input);
+        let inversion = Inversion::build(&vk, &proof, &challenges);
+        let L_1 = LangrangePolynomialEvaluation::compute_L1_polynomial_evaluation(
+            &challenges.zh,
+            &inversion.eval_l1,
+        );
+        let pi = compute_pi(&[pub_input], &[L_1]);
+
+        // the vanilla arithmetization agrees with the T0(xi) of the snarkjs verifier
+        let arithmetization = Arithmetization::vanilla(&vk);
+        let custom_proof = CustomGateProof::from(proof.clone());
+        assert_eq!(
+            arithmetization.compute_t0(&custom_proof.evaluations, &pi, &inversion.zh_inv),
+            Some(compute_t0(&proof, &pi, &inversion.zh_inv))
+        );
+
+        // the snarkjs proof has no evaluation of the 4th wire
+        let four_wire = Arithmetization {
+            gate: GateEquation::four_wire(),
+            ..arithmetization
+        };
+        assert_eq!(
+            four_wire.compute_t0(&custom_proof.evaluations, &pi, &inversion.zh_inv),
+            None
+        );
+    }
+
+    #[test]
+    fn test_vanilla_arithmetization() {
+        let vk = VerificationKey::default();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+
+        let arithmetization = Arithmetization::vanilla(&vk);
+        let config = arithmetization.opening_set_config().unwrap();
+        assert_eq!(config, OpeningSetConfig::plonk());
+
+        let challenges = Challenges::compute::<Keccak256TranscriptHash>(&vk, &proof, &pub_input);
+        let opening_challenges =
+            OpeningChallenges::from_plonk::<Keccak256TranscriptHash>(&proof, &challenges);
+        let gate_challenges =
+            GateChallenges::from_plonk::<Keccak256TranscriptHash>(&proof, &challenges);
+
+        let custom_proof = CustomGateProof::from(proof.clone());
+        let opening = arithmetization
+            .opening_proof(&vk, &custom_proof, &gate_challenges, &pub_input)
+            .unwrap();
+        assert_eq!(
+            opening,
+            OpeningProof::from_plonk(&vk, &proof, &challenges, &pub_input).unwrap()
+        );
+        assert!(
+            verify_openings_with_challenges(&config, &vk, &opening, &opening_challenges).unwrap()
+        );
+    }
+
+    // A random circuit of the 4-wire gate of 2^power rows: after the public input, every row is
+    // a random gate whose a is the c of the row before, and the wires of equal values are copied
+    // to each other.
+    fn four_wire_circuit<R: ark_std::rand::RngCore>(
+        power: u8,
+        pub_input: Fr,
+        rng: &mut R,
+    ) -> (CustomGateCircuit<Fr>, CustomGateWitness<Fr>) {
+        let n = 1 << power;
+        let mut selectors = vec![vec![Fr::zero(); n]; 6];
+        let mut wires = vec![vec![Fr::zero(); n]; 4];
+
+        // ql·a + PI = 0
+        selectors[0][0] = Fr::one();
+        wires[0][0] = pub_input;
+        for row in 1..n {
+            // ql·a + qr·b + qo·c + q4·d + qm·a·b + qc = 0, with qo = -1
+            let [a, b, d] = [wires[2][row - 1], Fr::rand(rng), Fr::rand(rng)];
+            let [ql, qr, q4, qm, qc] = [(); 5].map(|_| Fr::rand(rng));
+            for (selector, q) in selectors.iter_mut().zip([ql, qr, -Fr::one(), q4, qm, qc]) {
+                selector[row] = q;
+            }
+            let c = ql * a + qr * b + q4 * d + qm * a * b + qc;
+            for (wire, value) in wires.iter_mut().zip([a, b, c, d]) {
+                wire[row] = value;
+            }
+        }
+
+        // a cycle through the wires of every value
+        let mut permutation = (0..4 * n).collect::<Vec<_>>();
+        let mut cycles = std::collections::HashMap::<Fr, Vec<usize>>::new();
+        for (wire, value) in wires.concat().into_iter().enumerate() {
+            cycles.entry(value).or_default().push(wire);
+        }
+        for cycle in cycles.values() {
+            for (i, wire) in cycle.iter().enumerate() {
+                permutation[*wire] = cycle[(i + 1) % cycle.len()];
+            }
+        }
+        let circuit = CustomGateCircuit {
+            power,
+            selectors,
+            permutation,
+        };
+        (circuit, CustomGateWitness { wires })
+    }
+
+    #[test]
+    fn test_four_wire_arithmetization() {
+        let rng = &mut ark_std::test_rng();
+        let power = 4;
+        let ks = compute_ks::<Fr>(power, 4);
+        let arithmetization = Arithmetization::<Bn254> {
+            gate: GateEquation::four_wire(),
+            ks: ks.clone(),
+        };
+
+        // C0: 6 selectors and 4 sigmas padded to 12, C1: 4 wires and T0 padded to 6.
+        let config = arithmetization.opening_set_config().unwrap();
+        let sizes = config
+            .commitments
+            .iter()
+            .map(|commitment| commitment.polynomials)
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![12, 6, 3]);
+        assert_eq!(config.xi_exponent().unwrap(), 12);
+
+        let pub_input = Fr::rand(rng);
+        let (circuit, witness) = four_wire_circuit(power, pub_input, rng);
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), 32 << power);
+        let pk = CustomGateProvingKey::new(arithmetization, &circuit, &srs).unwrap();
+        assert_eq!(
+            Arithmetization::four_wire(&pk.vk, ks[3]),
+            pk.arithmetization
+        );
+
+        let proof = fflonk_custom_gate_prover::<_, Keccak256TranscriptHash, _>(
+            &pk, &srs, &witness, &pub_input, rng,
+        )
+        .unwrap();
+        let verify = |proof: &CustomGateProof, pub_input: &Fr| {
+            fflonk_custom_gate_verifier::<_, Keccak256TranscriptHash>(
+                &pk.arithmetization,
+                &pk.vk,
+                proof,
+                pub_input,
+            )
+            .unwrap()
+        };
+        assert!(verify(&proof, &pub_input));
+        assert!(!verify(&proof, &(pub_input + Fr::one())));
+        assert!(!fflonk_custom_gate_verifier::<_, Blake3TranscriptHash>(
+            &pk.arithmetization,
+            &pk.vk,
+            &proof,
+            &pub_input
+        )
+        .unwrap());
+
+        // the 4th wire is bound by the gate
+        let mut wrong = proof.clone();
+        wrong.evaluations.wires[3] += Fr::one();
+        assert!(!verify(&wrong, &pub_input));
+
+        // evaluations of another arity are a wrong proof
+        let mut wrong = proof.clone();
+        wrong.evaluations.wires.pop();
+        assert!(!verify(&wrong, &pub_input));
+        let mut wrong = proof.clone();
+        wrong.evaluations.selectors.push(Fr::one());
+        assert!(!verify(&wrong, &pub_input));
+    }
+}
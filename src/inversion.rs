@@ -2,39 +2,72 @@ use crate::challenge::{root::Roots, Challenges};
 use crate::proof::Proof;
 use crate::utils::LangrangePolynomialEvaluation;
 use crate::vk::VerificationKey;
-use ark_bn254::Fr;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
 use ark_ff::{Field, One, Zero};
+use serde::{Deserialize, Serialize};
 use std::ops::Mul;
 
-#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
-pub struct Inversion {
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Inversion<E: Pairing = Bn254> {
     // L[1], it's related with pub_input numbers.
-    pub eval_l1: Fr,
-    pub lis_values: LISValues,
-    pub den_h1: Fr,
-    pub den_h2: Fr,
+    #[serde(with = "crate::serde::fr")]
+    pub eval_l1: E::ScalarField,
+    pub lis_values: LISValues<E>,
+    #[serde(with = "crate::serde::fr")]
+    pub den_h1: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub den_h2: E::ScalarField,
     // ZH
-    pub zh_inv: Fr,
+    #[serde(with = "crate::serde::fr")]
+    pub zh_inv: E::ScalarField,
 }
 
-#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
-pub struct LISValues {
-    pub li_s0_inv: [Fr; 8],
-    pub li_s1_inv: [Fr; 4],
-    pub li_s2_inv: [Fr; 6],
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LISValues<E: Pairing = Bn254> {
+    #[serde(with = "crate::serde::fr_array")]
+    pub li_s0_inv: [E::ScalarField; 8],
+    #[serde(with = "crate::serde::fr_array")]
+    pub li_s1_inv: [E::ScalarField; 4],
+    #[serde(with = "crate::serde::fr_array")]
+    pub li_s2_inv: [E::ScalarField; 6],
 }
 
-impl Inversion {
-    pub fn compute_den_h1_base(roots: &Roots, y: &Fr) -> Fr {
-        (y - &roots.h1w4[0]) * (y - &roots.h1w4[1]) * (y - &roots.h1w4[2]) * (y - &roots.h1w4[3])
+impl<E: Pairing> Default for Inversion<E> {
+    fn default() -> Self {
+        Inversion {
+            eval_l1: E::ScalarField::zero(),
+            lis_values: LISValues::default(),
+            den_h1: E::ScalarField::zero(),
+            den_h2: E::ScalarField::zero(),
+            zh_inv: E::ScalarField::zero(),
+        }
+    }
+}
+
+impl<E: Pairing> Default for LISValues<E> {
+    fn default() -> Self {
+        LISValues {
+            li_s0_inv: [E::ScalarField::zero(); 8],
+            li_s1_inv: [E::ScalarField::zero(); 4],
+            li_s2_inv: [E::ScalarField::zero(); 6],
+        }
+    }
+}
+
+impl<E: Pairing> Inversion<E> {
+    pub fn compute_den_h1_base(roots: &Roots<E>, y: &E::ScalarField) -> E::ScalarField {
+        (*y - roots.h1w4[0]) * (*y - roots.h1w4[1]) * (*y - roots.h1w4[2]) * (*y - roots.h1w4[3])
     }
-    pub fn compute_den_h2_base(roots: &Roots, y: &Fr) -> Fr {
-        (y - &roots.h2w3[0])
-            * (y - &roots.h2w3[1])
-            * (y - &roots.h2w3[2])
-            * (y - &roots.h3w3[0])
-            * (y - &roots.h3w3[1])
-            * (y - &roots.h3w3[2])
+    pub fn compute_den_h2_base(roots: &Roots<E>, y: &E::ScalarField) -> E::ScalarField {
+        (*y - roots.h2w3[0])
+            * (*y - roots.h2w3[1])
+            * (*y - roots.h2w3[2])
+            * (*y - roots.h3w3[0])
+            * (*y - roots.h3w3[1])
+            * (*y - roots.h3w3[2])
     }
 
     // To divide prime fields the Extended Euclidean Algorithm for computing modular inverses is needed.
@@ -45,9 +78,81 @@ impl Inversion {
     //      1) Prepare all the denominators to inverse
     //      2) Check the inverse sent by the prover it is what it should be
     //      3) Compute the others inverses using the Montgomery Batched Algorithm using the inverse sent to avoid the inversion operation it does.
-    pub fn build(vk: &VerificationKey, proof: &Proof, challenges: &Challenges) -> Inversion {
+    // Panics if the inverse sent by the prover is wrong, see `try_build`.
+    pub fn build(
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        challenges: &Challenges<E>,
+    ) -> Inversion<E> {
+        Self::try_build(vk, proof, challenges).expect("Inversion::check_accumulator failed.")
+    }
+
+    // Same as `build`, but returns `None` rather than panicking when the inverse
+    // `proof.evaluations.inv` sent by the prover is wrong.
+    pub fn try_build(
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        challenges: &Challenges<E>,
+    ) -> Option<Inversion<E>> {
+        let zh = challenges.zh;
+        let (den_h1_base, den_h2_base, li_s0, li_s1, li_s2, eval_l1_base) =
+            Self::compute_denominators(vk, challenges);
+
+        let mut accumulator = Self::accumulator(
+            &den_h1_base,
+            &den_h2_base,
+            &zh,
+            &li_s0,
+            &li_s1,
+            &li_s2,
+            &eval_l1_base,
+        );
+
+        if *accumulator.last().unwrap() * proof.evaluations.inv != E::ScalarField::one() {
+            return None;
+        }
+
+        // As the accumulator checks out, all the denominators are invertible.
+        Some(Self::inverse_with_accumulator(
+            &mut accumulator,
+            proof,
+            &den_h1_base,
+            &den_h2_base,
+            &zh,
+            &li_s0,
+            &li_s1,
+            &li_s2,
+            &eval_l1_base,
+        ))
+    }
+
+    // Check the inverse `proof.evaluations.inv` sent by the prover against the challenges.
+    // Unlike `build`, it doesn't panic, it's cheap (no field inversion, no pairing) and is used
+    // to tell whether the challenges are derived from the same transcript as the prover's.
+    pub fn check_inverse(
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        challenges: &Challenges<E>,
+    ) -> bool {
+        Self::try_build(vk, proof, challenges).is_some()
+    }
+
+    // Prepare all the denominators to inverse:
+    //      (den_h1_base, den_h2_base, li_s0, li_s1, li_s2, eval_l1_base)
+    #[allow(clippy::type_complexity)]
+    pub fn compute_denominators(
+        vk: &VerificationKey<E>,
+        challenges: &Challenges<E>,
+    ) -> (
+        E::ScalarField,
+        E::ScalarField,
+        [E::ScalarField; 8],
+        [E::ScalarField; 4],
+        [E::ScalarField; 6],
+        E::ScalarField,
+    ) {
         let roots = &challenges.roots;
-        let (y, xi, zh) = (challenges.y, challenges.xi, challenges.zh);
+        let (y, xi) = (challenges.y, challenges.xi);
 
         // 1. compute den_h1_base
         let den_h1_base = Self::compute_den_h1_base(roots, &y);
@@ -63,27 +168,14 @@ impl Inversion {
         // TODO move it outside.
         let eval_l1_base = LangrangePolynomialEvaluation::compute_L1_base(&xi, &vk.n);
 
-        let res = Self::inverse_array(
-            proof,
-            &den_h1_base,
-            &den_h2_base,
-            &zh,
-            &li_s0,
-            &li_s1,
-            &li_s2,
-            &eval_l1_base,
-        );
-
-        assert_eq!(eval_l1_base * res.eval_l1, Fr::one());
-
-        res
+        (den_h1_base, den_h2_base, li_s0, li_s1, li_s2, eval_l1_base)
     }
 
-    pub fn compute_li_s0(y: Fr, h0w8: &[Fr]) -> [Fr; 8] {
+    pub fn compute_li_s0(y: E::ScalarField, h0w8: &[E::ScalarField]) -> [E::ScalarField; 8] {
         // root0^6 * 8
-        let den1 = h0w8[0].pow([6]) * Fr::from(8);
+        let den1 = h0w8[0].pow([6]) * E::ScalarField::from(8u64);
 
-        let mut li_s0_inv: [Fr; 8] = [Fr::zero(); 8];
+        let mut li_s0_inv: [E::ScalarField; 8] = [E::ScalarField::zero(); 8];
 
         for i in 0..8 {
             li_s0_inv[i] = den1 * h0w8[(i * 7) % 8] * (y - h0w8[i]);
@@ -92,10 +184,10 @@ impl Inversion {
         li_s0_inv
     }
 
-    pub fn compute_li_s1(y: Fr, h1w4: &[Fr]) -> [Fr; 4] {
-        let den1 = h1w4[0].pow([2]) * Fr::from(4);
+    pub fn compute_li_s1(y: E::ScalarField, h1w4: &[E::ScalarField]) -> [E::ScalarField; 4] {
+        let den1 = h1w4[0].pow([2]) * E::ScalarField::from(4u64);
 
-        let mut li_s1_inv: [Fr; 4] = [Fr::zero(); 4];
+        let mut li_s1_inv: [E::ScalarField; 4] = [E::ScalarField::zero(); 4];
 
         for i in 0..4 {
             li_s1_inv[i] = den1 * h1w4[(i * 3) % 4] * (y - h1w4[i]);
@@ -104,18 +196,24 @@ impl Inversion {
         li_s1_inv
     }
 
-    pub fn compute_li_s2(vk: &VerificationKey, y: Fr, xi: Fr, h2w3: &[Fr], h3w3: &[Fr]) -> [Fr; 6] {
+    pub fn compute_li_s2(
+        vk: &VerificationKey<E>,
+        y: E::ScalarField,
+        xi: E::ScalarField,
+        h2w3: &[E::ScalarField],
+        h3w3: &[E::ScalarField],
+    ) -> [E::ScalarField; 6] {
         let xiw = xi * vk.omega.w;
 
-        let den1 = Fr::from(3) * h2w3[0] * (xi - xiw);
+        let den1 = E::ScalarField::from(3u64) * h2w3[0] * (xi - xiw);
 
-        let mut li_s2_inv: [Fr; 6] = [Fr::zero(); 6];
+        let mut li_s2_inv: [E::ScalarField; 6] = [E::ScalarField::zero(); 6];
 
         for i in 0..3 {
             li_s2_inv[i] = den1 * h2w3[(i * 2) % 3] * (y - h2w3[i]);
         }
 
-        let den1 = Fr::from(3) * h3w3[0] * (xiw - xi);
+        let den1 = E::ScalarField::from(3u64) * h3w3[0] * (xiw - xi);
         for i in 0..3 {
             li_s2_inv[i + 3] = den1 * h3w3[(i * 2) % 3] * (y - h3w3[i]);
         }
@@ -132,19 +230,19 @@ impl Inversion {
     //      [15..20]=zh*den_h1_base*den_h2_base*MUL(li_s0[i])*MUL(li_s1[i])*MUL(li_s2[i])
     //      [21]=zh*den_h1_base*den_h2_base*MUL(li_s0[i])*MUL(li_s1[i])*MUL(li_s2[i])*eval_l1
     pub fn accumulator(
-        den_h1_base: &Fr,
-        den_h2_base: &Fr,
-        zh: &Fr,
-        li_s0: &[Fr; 8],
-        li_s1: &[Fr; 4],
-        li_s2: &[Fr; 6],
-        eval_l1: &Fr,
-    ) -> Vec<Fr> {
-        let mut accumulator: Vec<Fr> = Vec::new();
+        den_h1_base: &E::ScalarField,
+        den_h2_base: &E::ScalarField,
+        zh: &E::ScalarField,
+        li_s0: &[E::ScalarField; 8],
+        li_s1: &[E::ScalarField; 4],
+        li_s2: &[E::ScalarField; 6],
+        eval_l1: &E::ScalarField,
+    ) -> Vec<E::ScalarField> {
+        let mut accumulator: Vec<E::ScalarField> = Vec::new();
         accumulator.push(*zh);
 
         // acc = zh*den_h1
-        let mut acc = zh.mul(den_h1_base);
+        let mut acc = zh.mul(*den_h1_base);
         accumulator.push(acc);
 
         // acc = zh*den_h1*den_h2
@@ -173,11 +271,11 @@ impl Inversion {
         accumulator
     }
 
-    pub fn check_accumulator(accumulator: &[Fr], proof: &Proof) {
+    pub fn check_accumulator(accumulator: &[E::ScalarField], proof: &Proof<E>) {
         // check `zh*den_h1*den_h2 * MUL(li_s0[i]) * MUL(li_s1[i]) * MUL(li_s2[i])* eval_l1 * proof.inv = 1`
         assert_eq!(
-            accumulator.last().unwrap() * &proof.evaluations.inv,
-            Fr::one(),
+            *accumulator.last().unwrap() * proof.evaluations.inv,
+            E::ScalarField::one(),
             "Inversion::check_accumulator failed."
         );
     }
@@ -193,15 +291,15 @@ impl Inversion {
     #[allow(clippy::too_many_arguments)]
     #[allow(unused_assignments)]
     pub fn inverse_with_accumulator(
-        accumulator: &mut Vec<Fr>,
-        proof: &Proof,
-        den_h1_base: &Fr,
-        den_h2_base: &Fr,
-        zh: &Fr,
-        li_s0: &[Fr; 8],
-        li_s1: &[Fr; 4],
-        li_s2: &[Fr; 6],
-        eval_l1_base: &Fr,
+        accumulator: &mut Vec<E::ScalarField>,
+        proof: &Proof<E>,
+        den_h1_base: &E::ScalarField,
+        den_h2_base: &E::ScalarField,
+        zh: &E::ScalarField,
+        li_s0: &[E::ScalarField; 8],
+        li_s1: &[E::ScalarField; 4],
+        li_s2: &[E::ScalarField; 6],
+        eval_l1_base: &E::ScalarField,
     ) -> Self {
         // Start Inverse:
 
@@ -226,9 +324,9 @@ impl Inversion {
         acc = acc.mul(*eval_l1_base);
         let eval_l1_inv = inv;
 
-        assert_eq!(eval_l1_inv * eval_l1_base, Fr::one());
+        assert_eq!(eval_l1_inv * eval_l1_base, E::ScalarField::one());
 
-        let mut local_li_s2_inv = [Fr::zero(); 6];
+        let mut local_li_s2_inv = [E::ScalarField::zero(); 6];
 
         for i in (0..6).rev() {
             inv = acc * accumulator.pop().unwrap();
@@ -236,7 +334,7 @@ impl Inversion {
             local_li_s2_inv[i] = inv;
         }
 
-        let mut local_li_s1_inv = [Fr::zero(); 4];
+        let mut local_li_s1_inv = [E::ScalarField::zero(); 4];
         for i in (0..4).rev() {
             inv = acc * accumulator.pop().unwrap();
 
@@ -244,7 +342,7 @@ impl Inversion {
             local_li_s1_inv[i] = inv;
         }
 
-        let mut local_li_s0_inv = [Fr::zero(); 8];
+        let mut local_li_s0_inv = [E::ScalarField::zero(); 8];
         for i in (0..8).rev() {
             inv = acc * accumulator.pop().unwrap();
             acc = acc.mul(li_s0[i]);
@@ -267,10 +365,10 @@ impl Inversion {
             li_s2_inv: local_li_s2_inv,
         };
 
-        assert_eq!(eval_l1_inv * eval_l1_base, Fr::one());
-        assert_eq!(zh * &Z_H, Fr::one());
-        assert_eq!(local_den_h1 * den_h1_base, Fr::one());
-        assert_eq!(local_den_h2 * den_h2_base, Fr::one());
+        assert_eq!(eval_l1_inv * eval_l1_base, E::ScalarField::one());
+        assert_eq!(*zh * Z_H, E::ScalarField::one());
+        assert_eq!(local_den_h1 * den_h1_base, E::ScalarField::one());
+        assert_eq!(local_den_h2 * den_h2_base, E::ScalarField::one());
 
         Self {
             eval_l1: eval_l1_inv,
@@ -286,14 +384,14 @@ impl Inversion {
     // To save the inverse to be computed on chain the prover sends the inverse as an evaluation in commits.eval_inv
     #[allow(clippy::too_many_arguments)]
     pub fn inverse_array(
-        proof: &Proof,
-        den_h1_base: &Fr,
-        den_h2_base: &Fr,
-        zh: &Fr,
-        li_s0: &[Fr; 8],
-        li_s1: &[Fr; 4],
-        li_s2: &[Fr; 6],
-        eval_l1_base: &Fr,
+        proof: &Proof<E>,
+        den_h1_base: &E::ScalarField,
+        den_h2_base: &E::ScalarField,
+        zh: &E::ScalarField,
+        li_s0: &[E::ScalarField; 8],
+        li_s1: &[E::ScalarField; 4],
+        li_s2: &[E::ScalarField; 6],
+        eval_l1_base: &E::ScalarField,
     ) -> Self {
         let mut accumulator = Self::accumulator(
             den_h1_base,
@@ -1,15 +1,35 @@
 #![allow(non_snake_case)]
 pub mod challenge;
+pub mod circom;
 pub mod compute_fej;
 pub mod compute_r;
+pub mod curve;
+pub mod dispute;
+pub mod evm;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod fuzz;
+pub mod gate;
 pub mod inversion;
+pub mod lookup;
 pub mod mock;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mutation;
+pub mod opening;
 pub mod pairing;
 pub mod proof;
+pub mod prover;
+pub mod ptau;
+#[cfg(feature = "r1cs")]
+pub mod r1cs;
 pub(crate) mod serde;
+pub mod setup;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod simulator;
+pub mod stepwise;
 #[cfg(test)]
 pub mod test;
 pub mod transcript;
 pub mod utils;
 pub mod verifier;
 pub mod vk;
+pub mod zkey;
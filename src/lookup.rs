@@ -0,0 +1,335 @@
+//! The fflonk variant with a logUp lookup argument of a wire into a fixed table.
+//!
+//! With f the looked-up wire, q the lookup selector, t the table and m the multiplicities,
+//! the accumulator phi runs over the rows of the domain H:
+//!     phi(Xw) - phi(X) = q(X)/(zeta + f(X)) - m(X)/(zeta + t(X)),  phi(1) = 0
+//! so that ∑ q/(zeta + f) = ∑ m/(zeta + t), ie: every looked-up value is in the table.
+//!
+//! The verifier checks it with the two quotients:
+//!     T3(xi) = ((phi(xi·w) - phi(xi))·(zeta + f)·(zeta + t) - q·(zeta + t) + m·(zeta + f)) / Z_H(xi)
+//!     T4(xi) = L_1(xi)·phi(xi) / Z_H(xi)
+//!
+//! The commitments are the ones of `Arithmetization`, extended as:
+//!     C0: the selectors, q, t and the permutation polynomials,
+//!     C1: the wires, m and T0,
+//!     C2: z, T1, T2, phi, T3 and T4, opened at xi and xi·w.
+//!
+//! The transcript extends the one of `GateChallenges::compute`, with zeta as the seed of the
+//! opening argument, see `OpeningChallenges::compute`:
+//!     beta    = H(C0 || PI || C1)
+//!     gamma   = H(beta)
+//!     zeta    = H(beta || gamma)
+//!     xi_seed = H(zeta || C0 || C1 || C2)
+//!     xi      = xi_seed^lcm
+//!     alpha   = H(xi_seed || the evaluations of C0, C1 and C2, in the order above)
+//!     y       = H(alpha || W1)
+use crate::challenge::Challenges;
+use crate::curve::{field_to_bytes_be, SWPoint};
+use crate::gate::{
+    batch_size, compute_lagrange, padded, Arithmetization, CustomGateEvaluations, GateChallenges,
+};
+use crate::opening::{CommitmentConfig, OpeningChallenges, OpeningProof, OpeningSetConfig};
+use crate::proof::Polynomials;
+use crate::transcript::TranscriptHash;
+use crate::utils::compute_zero_poly_evals;
+use crate::vk::VerificationKey;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_ff::{Field, One};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The looked-up wire, as an index into `GateEquation::wires`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LookupArgument {
+    pub wire: usize,
+}
+
+/// The evaluations of the lookup polynomials, on top of the ones of `CustomGateEvaluations`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct LookupEvaluations<E: Pairing = Bn254> {
+    // The lookup selector q(xi).
+    #[serde(with = "crate::serde::fr")]
+    pub q_lookup: E::ScalarField,
+    // The table t(xi).
+    #[serde(with = "crate::serde::fr")]
+    pub table: E::ScalarField,
+    // The multiplicities m(xi).
+    #[serde(with = "crate::serde::fr")]
+    pub m: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub phi: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub phiw: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub t3w: E::ScalarField,
+    #[serde(with = "crate::serde::fr")]
+    pub t4w: E::ScalarField,
+}
+
+/// A fflonk proof of a `LookupArithmetization`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "E::G1: SWPoint<BaseField = E::BaseField>")]
+pub struct LookupProof<E: Pairing = Bn254> {
+    pub polynomials: Polynomials<E>,
+    pub evaluations: CustomGateEvaluations<E>,
+    pub lookup: LookupEvaluations<E>,
+}
+
+impl<E: Pairing> LookupProof<E>
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+{
+    pub fn load<P: AsRef<Path>>(proof_path: P) -> anyhow::Result<Self> {
+        let mut file = File::open(proof_path)?;
+        let mut proof_json = String::new();
+        file.read_to_string(&mut proof_json)?;
+        Ok(serde_json::from_str(&proof_json)?)
+    }
+}
+
+/// The challenges of a lookup proof: the ones of the gate, plus the lookup challenge `zeta`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct LookupChallenges<E: Pairing = Bn254> {
+    pub gate: GateChallenges<E>,
+    pub zeta: E::ScalarField,
+}
+
+impl<E: Pairing> LookupChallenges<E> {
+    // See the transcript in the module documentation.
+    pub fn compute<T: TranscriptHash>(
+        arithmetization: &LookupArithmetization<E>,
+        vk: &VerificationKey<E>,
+        proof: &LookupProof<E>,
+        pub_input: &E::ScalarField,
+    ) -> anyhow::Result<Self> {
+        let config = arithmetization.opening_set_config()?;
+        let polynomials = &proof.polynomials;
+
+        let beta =
+            Challenges::<E>::compute_beta::<T>(&vk.c0, &polynomials.c1.into_affine(), pub_input);
+        let gamma = Challenges::<E>::compute_gamma::<T>(&beta);
+        let zeta = T::hash_to_fr([beta, gamma].iter().flat_map(field_to_bytes_be).collect());
+        let xi_seed = OpeningChallenges::compute_xi_seed::<T>(
+            vk,
+            &[polynomials.c1.into_affine(), polynomials.c2.into_affine()],
+            &zeta,
+        );
+        let xi = xi_seed.pow([config.xi_exponent()?]);
+
+        Ok(Self {
+            gate: GateChallenges {
+                beta,
+                gamma,
+                xi_seed,
+                xi,
+                zh: compute_zero_poly_evals(&xi, &vk.n),
+            },
+            zeta,
+        })
+    }
+}
+
+/// An `Arithmetization` with a logUp lookup argument, see the module documentation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LookupArithmetization<E: Pairing = Bn254> {
+    pub arithmetization: Arithmetization<E>,
+    pub lookup: LookupArgument,
+}
+
+impl<E: Pairing> LookupArithmetization<E> {
+    // The snarkjs arithmetization, looking up the given wire.
+    pub fn vanilla(vk: &VerificationKey<E>, wire: usize) -> Self {
+        Self {
+            arithmetization: Arithmetization::vanilla(vk),
+            lookup: LookupArgument { wire },
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.arithmetization.validate()?;
+        let wires = self.arithmetization.gate.wires.len();
+        if self.lookup.wire >= wires {
+            anyhow::bail!("Unknown looked-up wire {}", self.lookup.wire);
+        }
+        Ok(())
+    }
+
+    pub fn opening_set_config(&self) -> anyhow::Result<OpeningSetConfig> {
+        self.validate()?;
+        let gate = &self.arithmetization.gate;
+        let wires = gate.wires.len();
+        Ok(OpeningSetConfig {
+            commitments: vec![
+                CommitmentConfig {
+                    polynomials: batch_size::<E::ScalarField>(gate.selectors.len() + 2 + wires),
+                    openings: vec![0],
+                },
+                CommitmentConfig {
+                    polynomials: batch_size::<E::ScalarField>(wires + 2),
+                    openings: vec![0],
+                },
+                CommitmentConfig {
+                    polynomials: 6,
+                    openings: vec![0, 1],
+                },
+            ],
+        })
+    }
+
+    // Compute T3(xi) and T4(xi), see the module documentation.
+    pub fn compute_t3_t4(
+        &self,
+        proof: &LookupProof<E>,
+        challenges: &LookupChallenges<E>,
+        L_1: &E::ScalarField,
+        zh_inv: &E::ScalarField,
+    ) -> (E::ScalarField, E::ScalarField) {
+        let lookup = &proof.lookup;
+        let f = challenges.zeta + proof.evaluations.wires[self.lookup.wire];
+        let t = challenges.zeta + lookup.table;
+
+        let t3 = ((lookup.phiw - lookup.phi) * f * t - lookup.q_lookup * t + lookup.m * f) * zh_inv;
+        let t4 = *L_1 * lookup.phi * zh_inv;
+        (t3, t4)
+    }
+
+    // Lay out the proof by `opening_set_config`, computing T0 to T4 at xi.
+    pub fn opening_proof(
+        &self,
+        vk: &VerificationKey<E>,
+        proof: &LookupProof<E>,
+        challenges: &LookupChallenges<E>,
+        pub_input: &E::ScalarField,
+    ) -> anyhow::Result<OpeningProof<E>> {
+        let config = self.opening_set_config()?;
+        let (evaluations, lookup) = (&proof.evaluations, &proof.lookup);
+        self.arithmetization.check_evaluations(evaluations)?;
+        let (zh_inv, L_1, pi) = compute_lagrange(vk, &challenges.gate, pub_input)?;
+
+        let t0 = self
+            .arithmetization
+            .compute_t0(evaluations, &pi, &zh_inv)
+            .ok_or_else(|| anyhow::anyhow!("The evaluations don't match the gate"))?;
+        let t1 = (evaluations.z - E::ScalarField::one()) * L_1 * zh_inv;
+        let t2 = self
+            .arithmetization
+            .compute_t2(evaluations, &challenges.gate, &zh_inv);
+        let (t3, t4) = self.compute_t3_t4(proof, challenges, &L_1, &zh_inv);
+
+        let c0 = [
+            evaluations.selectors.clone(),
+            vec![lookup.q_lookup, lookup.table],
+            evaluations.sigmas.clone(),
+        ]
+        .concat();
+        let c1 = [evaluations.wires.clone(), vec![lookup.m, t0]].concat();
+
+        let polynomials = &proof.polynomials;
+        Ok(OpeningProof {
+            commitments: vec![polynomials.c1.into_affine(), polynomials.c2.into_affine()],
+            evaluations: vec![
+                vec![padded(c0, &config.commitments[0])],
+                vec![padded(c1, &config.commitments[1])],
+                vec![
+                    vec![evaluations.z, t1, t2, lookup.phi, t3, t4],
+                    vec![
+                        evaluations.zw,
+                        evaluations.t1w,
+                        evaluations.t2w,
+                        lookup.phiw,
+                        lookup.t3w,
+                        lookup.t4w,
+                    ],
+                ],
+            ],
+            w1: polynomials.w1.into_affine(),
+            w2: polynomials.w2.into_affine(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::multiplier2_lookup;
+    use crate::prover::lookup::{fflonk_lookup_prover, LookupProvingKey};
+    use crate::prover::Srs;
+    use crate::transcript::{Blake3TranscriptHash, Keccak256TranscriptHash};
+    use crate::verifier::fflonk_lookup_verifier;
+    use ark_bn254::Fr;
+    use ark_ff::UniformRand;
+
+    #[test]
+    fn test_lookup_opening_set_config() {
+        let vk = VerificationKey::default();
+        let arithmetization = LookupArithmetization::vanilla(&vk, 0);
+
+        // C0: 5 selectors, q, t and 3 sigmas padded to 12, C1: 3 wires, m and T0 padded to 6.
+        let config = arithmetization.opening_set_config().unwrap();
+        let sizes = config
+            .commitments
+            .iter()
+            .map(|commitment| commitment.polynomials)
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![12, 6, 6]);
+        assert!(config.validate::<Fr>().is_ok());
+
+        assert!(LookupArithmetization::vanilla(&vk, 3)
+            .opening_set_config()
+            .is_err());
+    }
+
+    #[test]
+    fn test_lookup_verifier() {
+        let rng = &mut ark_std::test_rng();
+        let (arithmetization, circuit, witness) = multiplier2_lookup::<Bn254>();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), 32 << circuit.gate.power);
+        let pk = LookupProvingKey::new(arithmetization, &circuit, &srs).unwrap();
+        let (arithmetization, vk) = (&pk.arithmetization, &pk.gate.vk);
+        let pub_input = Fr::from(33u64);
+
+        let proof = fflonk_lookup_prover::<_, Keccak256TranscriptHash, _>(
+            &pk, &srs, &witness, &pub_input, rng,
+        )
+        .unwrap();
+        let verify =
+            |arithmetization: &LookupArithmetization, proof: &LookupProof, pub_input: &Fr| {
+                fflonk_lookup_verifier::<_, Keccak256TranscriptHash>(
+                    arithmetization,
+                    vk,
+                    proof,
+                    pub_input,
+                )
+                .unwrap()
+            };
+        assert!(verify(arithmetization, &proof, &pub_input));
+        assert!(!verify(arithmetization, &proof, &(pub_input + Fr::one())));
+        assert!(!fflonk_lookup_verifier::<_, Blake3TranscriptHash>(
+            arithmetization,
+            vk,
+            &proof,
+            &pub_input
+        )
+        .unwrap());
+
+        // the lookup evaluations are bound by the transcript and the quotients.
+        let mut wrong = proof.clone();
+        wrong.lookup.m += Fr::one();
+        assert!(!verify(arithmetization, &wrong, &pub_input));
+        let mut wrong = proof.clone();
+        wrong.lookup.phiw += Fr::one();
+        assert!(!verify(arithmetization, &wrong, &pub_input));
+
+        // the same proof doesn't pass as a lookup into another wire.
+        let other_wire = LookupArithmetization {
+            lookup: LookupArgument { wire: 1 },
+            ..arithmetization.clone()
+        };
+        assert!(!verify(&other_wire, &proof, &pub_input));
+    }
+}
@@ -26,3 +26,60 @@ pub static MOCK_PROOF_DATA: [&str; 24] = [
     "11804645688707233673914574834599506530652461017683048951953032091830492459803",
     "6378827379501409574366452872421073840754012879130221505294134572417254316105",
 ];
+
+/// Multiplier2 of `resources/circom` as circom writes c <== a·b: (-a)·b - (-c) = 0, on the
+/// signals [1, c, a, b].
+#[cfg(any(test, feature = "test-utils"))]
+pub fn multiplier2<F: ark_ff::FftField>() -> crate::setup::SnarkJSCircuit<F> {
+    crate::setup::SnarkJSCircuit {
+        n_vars: 4,
+        n_public: 1,
+        constraints: vec![crate::setup::PlonkConstraint {
+            signals: [2, 3, 1],
+            qm: -F::one(),
+            ql: F::zero(),
+            qr: F::zero(),
+            qo: F::one(),
+            qc: F::zero(),
+        }],
+    }
+}
+
+/// Multiplier2 with a = 3 and b = 11 under the vanilla gate, looking up its wire a into the table
+/// [0, 1, 2, 3, 0, ...] on every row but the one of the public input 33.
+#[cfg(any(test, feature = "test-utils"))]
+#[allow(clippy::type_complexity)]
+pub fn multiplier2_lookup<E: ark_ec::pairing::Pairing>() -> (
+    crate::lookup::LookupArithmetization<E>,
+    crate::prover::lookup::LookupCircuit<E::ScalarField>,
+    crate::prover::gate::CustomGateWitness<E::ScalarField>,
+) {
+    use ark_ff::{One, Zero};
+
+    let circuit = multiplier2::<E::ScalarField>();
+    let witness = circuit
+        .witness(&[1u64, 33, 3, 11].map(E::ScalarField::from))
+        .unwrap();
+    let circuit = circuit.plonk_circuit().unwrap();
+    let arithmetization = crate::lookup::LookupArithmetization {
+        arithmetization: crate::gate::Arithmetization {
+            gate: crate::gate::GateEquation::vanilla(),
+            ks: vec![E::ScalarField::one(), circuit.k1, circuit.k2],
+        },
+        lookup: crate::lookup::LookupArgument { wire: 0 },
+    };
+
+    let n = circuit.n();
+    let mut q_lookup = vec![E::ScalarField::one(); n];
+    q_lookup[0] = E::ScalarField::zero();
+    let mut table = vec![E::ScalarField::zero(); n];
+    for (i, value) in table.iter_mut().take(4).enumerate() {
+        *value = E::ScalarField::from(i as u64);
+    }
+    let circuit = crate::prover::lookup::LookupCircuit {
+        gate: circuit.into(),
+        q_lookup,
+        table,
+    };
+    (arithmetization, circuit, witness.into())
+}
@@ -0,0 +1,400 @@
+use crate::curve::SWPoint;
+use crate::proof::Proof;
+use crate::vk::{Omega, VerificationKey};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, Zero};
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// The 24 elements of a proof, in the order of `Proof::construct` and of the calldata of the
+/// Solidity verifier.
+pub const PROOF_ELEMENTS: [&str; 24] = [
+    "C1.x", "C1.y", "C2.x", "C2.y", "W1.x", "W1.y", "W2.x", "W2.y", "ql", "qr", "qm", "qo", "qc",
+    "s1", "s2", "s3", "a", "b", "c", "z", "zw", "t1w", "t2w", "inv",
+];
+
+/// The fields of a verification key, as snarkjs writes them, along with n and the generators.
+/// The points are mutated through their affine coordinates.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum VkField {
+    Power,
+    N,
+    K1,
+    K2,
+    W,
+    W3,
+    W4,
+    W8,
+    Wr,
+    X2X,
+    X2Y,
+    C0X,
+    C0Y,
+    G1X,
+    G1Y,
+    G2X,
+    G2Y,
+}
+
+impl VkField {
+    pub const ALL: [VkField; 17] = [
+        VkField::Power,
+        VkField::N,
+        VkField::K1,
+        VkField::K2,
+        VkField::W,
+        VkField::W3,
+        VkField::W4,
+        VkField::W8,
+        VkField::Wr,
+        VkField::X2X,
+        VkField::X2Y,
+        VkField::C0X,
+        VkField::C0Y,
+        VkField::G1X,
+        VkField::G1Y,
+        VkField::G2X,
+        VkField::G2Y,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VkField::Power => "power",
+            VkField::N => "n",
+            VkField::K1 => "k1",
+            VkField::K2 => "k2",
+            VkField::W => "w",
+            VkField::W3 => "w3",
+            VkField::W4 => "w4",
+            VkField::W8 => "w8",
+            VkField::Wr => "wr",
+            VkField::X2X => "X_2.x",
+            VkField::X2Y => "X_2.y",
+            VkField::C0X => "C0.x",
+            VkField::C0Y => "C0.y",
+            VkField::G1X => "g1.x",
+            VkField::G1Y => "g1.y",
+            VkField::G2X => "g2.x",
+            VkField::G2Y => "g2.y",
+        }
+    }
+}
+
+/// What a mutant changes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Target {
+    /// The element of the proof at the index of `PROOF_ELEMENTS`
+    Proof(usize),
+    Vk(VkField),
+    PubInput,
+}
+
+/// How a mutant changes its target.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Mutation {
+    AddOne,
+    Negate,
+    Zero,
+}
+
+impl Mutation {
+    pub const ALL: [Mutation; 3] = [Mutation::AddOne, Mutation::Negate, Mutation::Zero];
+
+    pub fn apply<F: Field>(&self, f: F) -> F {
+        match self {
+            Mutation::AddOne => f + F::one(),
+            Mutation::Negate => -f,
+            Mutation::Zero => F::zero(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Mutant {
+    pub target: Target,
+    pub mutation: Mutation,
+}
+
+impl fmt::Display for Mutant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let target = match self.target {
+            Target::Proof(i) => format!("proof.{}", PROOF_ELEMENTS[i]),
+            Target::Vk(field) => format!("vk.{}", field.name()),
+            Target::PubInput => "pub_input".to_string(),
+        };
+        match self.mutation {
+            Mutation::AddOne => write!(f, "{target} + 1"),
+            Mutation::Negate => write!(f, "-{target}"),
+            Mutation::Zero => write!(f, "{target} = 0"),
+        }
+    }
+}
+
+impl Mutant {
+    /// Every mutant: each mutation of each element of the proof, field of the key, and of the
+    /// public input.
+    pub fn all() -> Vec<Mutant> {
+        let targets = (0..PROOF_ELEMENTS.len())
+            .map(Target::Proof)
+            .chain(VkField::ALL.map(Target::Vk))
+            .chain([Target::PubInput]);
+        targets
+            .flat_map(|target| Mutation::ALL.map(|mutation| Mutant { target, mutation }))
+            .collect()
+    }
+
+    // -w4 = w4^3 and -w8 = w8^5 are the other primitive roots of the same opening sets, which
+    // the verifier may then accept: unless it binds the whole key, see `FiatShamirProfile::Strong`.
+    pub fn is_equivalent(&self) -> bool {
+        matches!(
+            (self.target, self.mutation),
+            (Target::Vk(VkField::W4 | VkField::W8), Mutation::Negate)
+        )
+    }
+
+    /// The mutated key, proof and public input.
+    pub fn apply<E: Pairing>(
+        &self,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> (VerificationKey<E>, Proof<E>, E::ScalarField)
+    where
+        E::G1: SWPoint<BaseField = E::BaseField>,
+        E::G2: SWPoint,
+    {
+        let (mut vk, mut proof, mut pub_input) = (vk.clone(), proof.clone(), *pub_input);
+        let m = self.mutation;
+        match self.target {
+            Target::Proof(i) if i < 8 => {
+                let polynomials = &mut proof.polynomials;
+                let point = [
+                    &mut polynomials.c1,
+                    &mut polynomials.c2,
+                    &mut polynomials.w1,
+                    &mut polynomials.w2,
+                ]
+                .into_iter()
+                .nth(i / 2)
+                .unwrap();
+                *point = mutate_point(point, i % 2, m);
+            }
+            Target::Proof(i) => {
+                let evaluations = &mut proof.evaluations;
+                let evaluation = [
+                    &mut evaluations.ql,
+                    &mut evaluations.qr,
+                    &mut evaluations.qm,
+                    &mut evaluations.qo,
+                    &mut evaluations.qc,
+                    &mut evaluations.s1,
+                    &mut evaluations.s2,
+                    &mut evaluations.s3,
+                    &mut evaluations.a,
+                    &mut evaluations.b,
+                    &mut evaluations.c,
+                    &mut evaluations.z,
+                    &mut evaluations.zw,
+                    &mut evaluations.t1w,
+                    &mut evaluations.t2w,
+                    &mut evaluations.inv,
+                ]
+                .into_iter()
+                .nth(i - 8)
+                .unwrap();
+                *evaluation = m.apply(*evaluation);
+            }
+            Target::Vk(field) => mutate_vk(&mut vk, field, m),
+            Target::PubInput => pub_input = m.apply(pub_input),
+        }
+        (vk, proof, pub_input)
+    }
+}
+
+// The power as a field element: +1, and 0. Its negation isn't a power.
+fn mutate_power(power: u8, m: Mutation) -> u8 {
+    match m {
+        Mutation::AddOne => power.wrapping_add(1),
+        Mutation::Negate => power,
+        Mutation::Zero => 0,
+    }
+}
+
+fn mutate_vk<E: Pairing>(vk: &mut VerificationKey<E>, field: VkField, m: Mutation)
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+    E::G2: SWPoint,
+{
+    // the powers of the roots are precomputed from them
+    let omega = &vk.omega;
+    let (mut w, mut wr, mut w3, mut w4, mut w8) =
+        (omega.w, omega.wr, omega.w3, omega.w4, omega.w8_1);
+    match field {
+        VkField::Power => vk.power = mutate_power(vk.power, m),
+        VkField::N => vk.n = m.apply(vk.n),
+        VkField::K1 => vk.k1 = m.apply(vk.k1),
+        VkField::K2 => vk.k2 = m.apply(vk.k2),
+        VkField::W => w = m.apply(w),
+        VkField::W3 => w3 = m.apply(w3),
+        VkField::W4 => w4 = m.apply(w4),
+        VkField::W8 => w8 = m.apply(w8),
+        VkField::Wr => wr = m.apply(wr),
+        VkField::X2X | VkField::X2Y => {
+            let x2 = mutate_point(&vk.x2.into_group(), (field == VkField::X2Y) as usize, m);
+            vk.x2 = x2.into_affine();
+        }
+        VkField::C0X | VkField::C0Y => {
+            let c0 = mutate_point(&vk.c0.into_group(), (field == VkField::C0Y) as usize, m);
+            vk.c0 = c0.into_affine();
+        }
+        VkField::G1X | VkField::G1Y => {
+            let g1 = mutate_point(&vk.g1.into_group(), (field == VkField::G1Y) as usize, m);
+            vk.g1 = g1.into_affine();
+        }
+        VkField::G2X | VkField::G2Y => {
+            let g2 = mutate_point(&vk.g2.into_group(), (field == VkField::G2Y) as usize, m);
+            vk.g2 = g2.into_affine();
+        }
+    }
+    vk.omega = Omega::from_roots(w, wr, w3, w4, w8);
+}
+
+// Mutate the affine x (coordinate 0) or y (coordinate 1) of the point, which is then off the
+// curve unless y is negated. The point at infinity has no affine coordinates to mutate.
+fn mutate_point<G: CurveGroup + SWPoint>(point: &G, coordinate: usize, m: Mutation) -> G {
+    let [x, y, z] = point.into_affine().into_group().coordinates();
+    if z.is_zero() {
+        return *point;
+    }
+    match coordinate {
+        0 => G::from_coordinates(m.apply(x), y, z),
+        _ => G::from_coordinates(x, m.apply(y), z),
+    }
+}
+
+/// The outcome of every mutant of a valid proof.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct MutationReport {
+    // The mutants the verifier rejected
+    pub killed: Vec<Mutant>,
+    // The mutants the verifier accepted
+    pub survived: Vec<Mutant>,
+    // The mutants the verifier panicked on, instead of rejecting them
+    pub panicked: Vec<Mutant>,
+    // The mutants which don't change anything, eg: zeroing qc when it's already 0, or which
+    // the verifier accepts for the same reason as the original, see `Mutant::is_equivalent`
+    pub equivalent: Vec<Mutant>,
+}
+
+impl MutationReport {
+    /// Every mutant is rejected, without a panic.
+    pub fn is_sound(&self) -> bool {
+        self.survived.is_empty() && self.panicked.is_empty()
+    }
+}
+
+impl fmt::Display for MutationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} killed, {} survived, {} panicked, {} equivalent",
+            self.killed.len(),
+            self.survived.len(),
+            self.panicked.len(),
+            self.equivalent.len()
+        )?;
+        for mutant in &self.survived {
+            writeln!(f, "survived: {mutant}")?;
+        }
+        for mutant in &self.panicked {
+            writeln!(f, "panicked: {mutant}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Run the `verify` function, eg: `fflonk_verifier` with a transcript, on every mutant of the
+/// valid `proof` of `pub_input` under `vk`.
+///
+/// Panics if the proof itself doesn't pass, as its mutants would then be rejected for nothing.
+pub fn mutation_report<E: Pairing, V>(
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    pub_input: &E::ScalarField,
+    verify: V,
+) -> MutationReport
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+    E::G2: SWPoint,
+    V: Fn(&VerificationKey<E>, &Proof<E>, &E::ScalarField) -> bool,
+{
+    assert!(verify(vk, proof, pub_input), "The proof doesn't pass");
+
+    let mut report = MutationReport::default();
+    for mutant in Mutant::all() {
+        let (mutated_vk, mutated_proof, mutated_pub_input) = mutant.apply(vk, proof, pub_input);
+        if (&mutated_vk, &mutated_proof, &mutated_pub_input) == (vk, proof, pub_input) {
+            report.equivalent.push(mutant);
+            continue;
+        }
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            verify(&mutated_vk, &mutated_proof, &mutated_pub_input)
+        }));
+        match outcome {
+            Ok(false) => report.killed.push(mutant),
+            Ok(true) if mutant.is_equivalent() => report.equivalent.push(mutant),
+            Ok(true) => report.survived.push(mutant),
+            Err(_) => report.panicked.push(mutant),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+    use crate::transcript::Keccak256TranscriptHash;
+    use crate::verifier::fflonk_verifier;
+    use ark_bn254::{Bn254, Fr};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_mutant() {
+        let mutants = Mutant::all();
+        assert_eq!(mutants.len(), (24 + 17 + 1) * 3);
+        assert_eq!(mutants[0].to_string(), "proof.C1.x + 1");
+        assert_eq!(mutants[3 * 23 + 1].to_string(), "-proof.inv");
+        assert_eq!(mutants.last().unwrap().to_string(), "pub_input = 0");
+
+        // the mutated element of the proof is the one of `Proof::construct`
+        let vk = VerificationKey::<Bn254>::default();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        for (i, value) in MOCK_PROOF_DATA.into_iter().enumerate() {
+            let mutant = Mutant {
+                target: Target::Proof(i),
+                mutation: Mutation::Zero,
+            };
+            let mut expect = MOCK_PROOF_DATA.to_vec();
+            expect[i] = "0";
+            let (_, actual, _) = mutant.apply(&vk, &proof, &pub_input);
+            assert_eq!(actual, Proof::construct(expect), "{mutant} of {value}");
+        }
+    }
+
+    #[test]
+    fn test_mutation_report() {
+        let vk = VerificationKey::<Bn254>::default();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        let report = mutation_report(&vk, &proof, &pub_input, |vk, proof, pub_input| {
+            fflonk_verifier::<Keccak256TranscriptHash>(vk, proof, pub_input, false)
+        });
+        println!("{report}");
+        assert!(report.is_sound());
+        // the negation of the power is the power, and -w4 and -w8 are roots of the same sets
+        assert_eq!(report.equivalent.len(), 3);
+    }
+}
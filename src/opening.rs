@@ -0,0 +1,683 @@
+use crate::challenge::Challenges;
+use crate::compute_fej::FEJ;
+use crate::compute_r::{compute_t0, compute_t1, compute_t2};
+use crate::curve::{field_to_bytes_be, point_to_bytes_be};
+use crate::pairing::check_pairing_with_w2;
+use crate::proof::Proof;
+use crate::transcript::TranscriptHash;
+use crate::utils::{compute_pi, LangrangePolynomialEvaluation};
+use crate::vk::VerificationKey;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{batch_inversion, Field, One, PrimeField, Zero};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The largest number of polynomials a commitment of an `OpeningSetConfig` may batch: the
+/// verifier computes as many roots for every opening.
+pub const MAX_BATCH_SIZE: usize = 1 << 10;
+
+/// The layout of a fflonk proof: which commitments there are, how many polynomials each one
+/// batches and at which points they are opened. Eg: `OpeningSetConfig::plonk()` is the one of snarkjs.
+///
+/// A commitment `C_i` batches `t_i` polynomials as `C_i(X) = ∑_j f_j(X^t_i)·X^j`, so that opening
+/// `f_0..f_{t_i-1}` at `z` is opening `C_i` at the `t_i` roots of `X^t_i = z`.
+/// All the openings are at `xi·w^s`, where `xi = xi_seed^lcm(t_0, t_1, ...)`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct OpeningSetConfig {
+    pub commitments: Vec<CommitmentConfig>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CommitmentConfig {
+    // The number of polynomials batched into the commitment, eg: 8 for C0.
+    pub polynomials: usize,
+    // The opening points as powers of the domain omega `w`: 0 for xi, 1 for xi·w.
+    pub openings: Vec<u64>,
+}
+
+impl OpeningSetConfig {
+    // The layout of the snarkjs PLONK proof:
+    //      C0: ql, qr, qo, qm, qc, s1, s2, s3 opened at xi (h0w8),
+    //      C1: a, b, c, T0 opened at xi (h1w4),
+    //      C2: z, T1, T2 opened at xi (h2w3) and xi·w (h3w3).
+    pub fn plonk() -> Self {
+        Self {
+            commitments: vec![
+                CommitmentConfig {
+                    polynomials: 8,
+                    openings: vec![0],
+                },
+                CommitmentConfig {
+                    polynomials: 4,
+                    openings: vec![0],
+                },
+                CommitmentConfig {
+                    polynomials: 3,
+                    openings: vec![0, 1],
+                },
+            ],
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(config_path: P) -> anyhow::Result<Self> {
+        let mut file = File::open(config_path)?;
+        let mut config_json = String::new();
+        file.read_to_string(&mut config_json)?;
+        let config: Self = serde_json::from_str(&config_json)?;
+        Ok(config)
+    }
+
+    // Check the descriptor makes sense over the scalar field F.
+    pub fn validate<F: PrimeField>(&self) -> anyhow::Result<()> {
+        if self.commitments.is_empty() {
+            anyhow::bail!("No commitment in the opening set config");
+        }
+
+        let modulus_minus_one = Into::<BigUint>::into(F::MODULUS) - 1u32;
+        for (i, commitment) in self.commitments.iter().enumerate() {
+            let t = commitment.polynomials;
+            if t == 0 {
+                anyhow::bail!("Commitment {i} batches no polynomial");
+            }
+            if t > MAX_BATCH_SIZE {
+                anyhow::bail!("Commitment {i} batches {t} polynomials, more than {MAX_BATCH_SIZE}");
+            }
+            if &modulus_minus_one % t != BigUint::zero() {
+                anyhow::bail!("Commitment {i}: no {t}-th root of unity in the scalar field");
+            }
+            if commitment.openings.is_empty() {
+                anyhow::bail!("Commitment {i} is never opened");
+            }
+            let mut openings = commitment.openings.clone();
+            openings.sort_unstable();
+            openings.dedup();
+            if openings.len() != commitment.openings.len() {
+                anyhow::bail!("Commitment {i} is opened twice at the same point");
+            }
+        }
+        self.xi_exponent()?;
+        Ok(())
+    }
+
+    // The exponent of xi = xi_seed^lcm(t_0, t_1, ...), eg: 24 for PLONK.
+    pub fn xi_exponent(&self) -> anyhow::Result<u64> {
+        self.commitments
+            .iter()
+            .map(|commitment| commitment.polynomials as u64)
+            .try_fold(1u64, |acc, t| (acc / gcd(acc, t.max(1))).checked_mul(t))
+            .ok_or_else(|| anyhow::anyhow!("The lcm of the batch sizes overflows u64"))
+    }
+}
+
+/// The commitments and the evaluations of a fflonk proof laid out by an `OpeningSetConfig`.
+///
+/// C0 is the preprocessed commitment of the verification key `vk.c0`, the proof only holds the
+/// commitments sent by the prover.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OpeningProof<E: Pairing = Bn254> {
+    // The commitments C_1, C_2, ... sent by the prover, eg: [C1, C2].
+    pub commitments: Vec<E::G1Affine>,
+    // evaluations[i][k][j]: the polynomial j of C_i at xi·w^s, where s = openings[k] of C_i.
+    pub evaluations: Vec<Vec<Vec<E::ScalarField>>>,
+    pub w1: E::G1Affine,
+    pub w2: E::G1Affine,
+}
+
+impl<E: Pairing> OpeningProof<E> {
+    // Lay out the snarkjs proof by `OpeningSetConfig::plonk()`, computing T0, T1 and T2 at xi.
+    // Returns `None` if xi is a root of Z_H.
+    pub fn from_plonk(
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        challenges: &Challenges<E>,
+        pub_input: &E::ScalarField,
+    ) -> Option<Self> {
+        let zh_inv = challenges.zh.inverse()?;
+        let L_1 = challenges.zh
+            * LangrangePolynomialEvaluation::compute_L1_base(&challenges.xi, &vk.n).inverse()?;
+        let pi = compute_pi(&[*pub_input], &[L_1]);
+
+        let t0 = compute_t0(proof, &pi, &zh_inv);
+        let t1 = compute_t1(proof, &L_1, &zh_inv);
+        let t2 = compute_t2(vk, proof, challenges, &zh_inv);
+
+        let evaluations = &proof.evaluations;
+        let polynomials = &proof.polynomials;
+        Some(Self {
+            commitments: vec![polynomials.c1.into_affine(), polynomials.c2.into_affine()],
+            evaluations: vec![
+                vec![vec![
+                    evaluations.ql,
+                    evaluations.qr,
+                    evaluations.qo,
+                    evaluations.qm,
+                    evaluations.qc,
+                    evaluations.s1,
+                    evaluations.s2,
+                    evaluations.s3,
+                ]],
+                vec![vec![evaluations.a, evaluations.b, evaluations.c, t0]],
+                vec![
+                    vec![evaluations.z, t1, t2],
+                    vec![evaluations.zw, evaluations.t1w, evaluations.t2w],
+                ],
+            ],
+            w1: polynomials.w1.into_affine(),
+            w2: polynomials.w2.into_affine(),
+        })
+    }
+
+    // Check the proof is laid out by the config: C0 and the commitments of the proof.
+    pub fn check_layout(&self, config: &OpeningSetConfig) -> anyhow::Result<()> {
+        if self.commitments.len() + 1 != config.commitments.len()
+            || self.evaluations.len() != config.commitments.len()
+        {
+            anyhow::bail!(
+                "Expect {} commitments, got C0, {} commitments and {} evaluation sets",
+                config.commitments.len(),
+                self.commitments.len(),
+                self.evaluations.len()
+            );
+        }
+        for (i, (commitment, evaluations)) in
+            config.commitments.iter().zip(&self.evaluations).enumerate()
+        {
+            if evaluations.len() != commitment.openings.len()
+                || evaluations
+                    .iter()
+                    .any(|evals| evals.len() != commitment.polynomials)
+            {
+                anyhow::bail!(
+                    "Commitment {i}: expect {} openings of {} evaluations",
+                    commitment.openings.len(),
+                    commitment.polynomials
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The challenges of the opening argument, see `OpeningChallenges::compute`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct OpeningChallenges<E: Pairing = Bn254> {
+    pub xi_seed: E::ScalarField,
+    pub alpha: E::ScalarField,
+    pub y: E::ScalarField,
+}
+
+impl<E: Pairing> OpeningChallenges<E> {
+    // The transcript of the opening argument, from the `seed` squeezed by the rounds before
+    // (eg: gamma, which binds the public input):
+    //      xi_seed = H(seed || C0 || C1 || ...), with C0 = vk.c0
+    //      alpha   = H(xi_seed || the evaluations, in the order of `OpeningProof::evaluations`)
+    //      y       = H(alpha || W1)
+    pub fn compute<T: TranscriptHash>(
+        vk: &VerificationKey<E>,
+        opening: &OpeningProof<E>,
+        seed: &E::ScalarField,
+    ) -> Self {
+        let xi_seed = Self::compute_xi_seed::<T>(vk, &opening.commitments, seed);
+        let alpha = Self::compute_alpha::<T>(&xi_seed, &opening.evaluations);
+        let y = Challenges::<E>::compute_y::<T>(&alpha, &opening.w1);
+        Self { xi_seed, alpha, y }
+    }
+
+    // xi_seed = H(seed || C0 || C1 || ...), which the caller needs to compute the evaluations
+    // derived at xi (eg: T0) before the opening proof.
+    pub fn compute_xi_seed<T: TranscriptHash>(
+        vk: &VerificationKey<E>,
+        commitments: &[E::G1Affine],
+        seed: &E::ScalarField,
+    ) -> E::ScalarField {
+        let mut concatenated = field_to_bytes_be(seed);
+        for commitment in std::iter::once(&vk.c0).chain(commitments) {
+            concatenated.extend(point_to_bytes_be(commitment));
+        }
+        T::hash_to_fr(concatenated)
+    }
+
+    // alpha = H(xi_seed || evaluations), the evaluations being laid out as `OpeningProof::evaluations`.
+    pub fn compute_alpha<T: TranscriptHash>(
+        xi_seed: &E::ScalarField,
+        evaluations: &[Vec<Vec<E::ScalarField>>],
+    ) -> E::ScalarField {
+        let concatenated = std::iter::once(xi_seed)
+            .chain(evaluations.iter().flatten().flatten())
+            .flat_map(field_to_bytes_be)
+            .collect::<Vec<_>>();
+        T::hash_to_fr(concatenated)
+    }
+
+    // The opening challenges of the snarkjs transcript.
+    pub fn from_plonk<T: TranscriptHash>(proof: &Proof<E>, challenges: &Challenges<E>) -> Self {
+        Self {
+            xi_seed: Challenges::<E>::compute_xiseed::<T>(
+                &challenges.gamma,
+                proof.polynomials.c2.into_affine(),
+            ),
+            alpha: challenges.alpha,
+            y: challenges.y,
+        }
+    }
+}
+
+// Compute the opening sets: roots[i][k] are the t_i roots of X^t_i = xi·w^s,
+// where t_i = polynomials and s = openings[k] of the commitment i.
+//
+// The roots of X^t_i = xi are h_i·wt^j, where h_i = xi_seed^(lcm/t_i) and wt is a primitive t_i-th root of unity.
+// Those of X^t_i = xi·w^s are h_i·(w^s)^(1/t_i)·wt^j, eg: h3w3 = h2·wr·w3^j for PLONK.
+pub fn compute_opening_roots<F: PrimeField>(
+    config: &OpeningSetConfig,
+    xi_seed: &F,
+    w: &F,
+) -> anyhow::Result<Vec<Vec<Vec<F>>>> {
+    config.validate::<F>()?;
+    let xi_exponent = config.xi_exponent()?;
+
+    config
+        .commitments
+        .iter()
+        .map(|commitment| {
+            let t = commitment.polynomials as u64;
+            let h = xi_seed.pow([xi_exponent / t]);
+            let wt = primitive_root_of_unity::<F>(t);
+
+            commitment
+                .openings
+                .iter()
+                .map(|s| {
+                    let shift = root_of_omega(w.pow([*s]), t).ok_or_else(|| {
+                        anyhow::anyhow!("No {t}-th root of w^{s} in the scalar field")
+                    })?;
+                    Ok(
+                        std::iter::successors(Some(h * shift), |root| Some(*root * wt))
+                            .take(t as usize)
+                            .collect(),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Compute [F]_1, [E]_1 and [J]_1 of any opening set:
+//      F = ∑ alpha^i·Z_0(y)/Z_i(y)·C_i
+//      E = ∑ alpha^i·Z_0(y)/Z_i(y)·r_i(y)·[1]_1
+//      J = Z_0(y)·W1
+// where Z_i is the vanishing polynomial of the opening set of C_i and r_i its interpolation of C_i.
+//
+// Returns `None` if y hits an opening set, or the opening sets have a duplicated point.
+pub fn compute_opening_fej<E: Pairing>(
+    config: &OpeningSetConfig,
+    vk: &VerificationKey<E>,
+    opening: &OpeningProof<E>,
+    challenges: &OpeningChallenges<E>,
+) -> anyhow::Result<Option<FEJ<E>>> {
+    let roots = compute_opening_roots(config, &challenges.xi_seed, &vk.omega.w)?;
+    opening.check_layout(config)?;
+    let y = challenges.y;
+
+    let mut zs = Vec::with_capacity(roots.len());
+    let mut rs = Vec::with_capacity(roots.len());
+    for (sets, evaluations) in roots.iter().zip(&opening.evaluations) {
+        let points = sets.concat();
+        // C_i(x) = ∑_j f_j(x^t_i)·x^j
+        let values = sets
+            .iter()
+            .zip(evaluations)
+            .flat_map(|(set, evals)| set.iter().map(|x| horner(evals, x)))
+            .collect::<Vec<_>>();
+
+        let Some(r) = interpolate_at(&points, &values, &y) else {
+            return Ok(None);
+        };
+        zs.push(points.iter().map(|x| y - x).product::<E::ScalarField>());
+        rs.push(r);
+    }
+
+    if zs.iter().any(|z| z.is_zero()) {
+        return Ok(None);
+    }
+    let numerator = zs[0];
+    let mut zs_inv = zs;
+    batch_inversion(&mut zs_inv);
+
+    let mut f = E::G1::zero();
+    let mut e_scalar = E::ScalarField::zero();
+    let mut alpha_i = E::ScalarField::one();
+    let commitments = std::iter::once(&vk.c0).chain(&opening.commitments);
+    for ((commitment, r), z_inv) in commitments.zip(rs).zip(zs_inv) {
+        let quotient = alpha_i * numerator * z_inv;
+        f += *commitment * quotient;
+        e_scalar += quotient * r;
+        alpha_i *= challenges.alpha;
+    }
+    let e = vk.g1 * e_scalar;
+    let j = opening.w1 * numerator;
+
+    Ok(Some(FEJ {
+        F: f.into_affine(),
+        E: e.into_affine(),
+        J: j.into_affine(),
+    }))
+}
+
+/// Verify the opening argument of a fflonk proof laid out by `config`:
+///     e(F - E - J + y·W2, [1]_2)·e(W2, -X_2) = 1
+///
+/// The challenges are squeezed from `seed` by the transcript of `OpeningChallenges::compute`,
+/// with C0 from the verification key. The caller computes the evaluations of the quotient-derived
+/// polynomials (eg: T0, T1 and T2 for PLONK) into `opening`, at the xi of
+/// `OpeningChallenges::compute_xi_seed`.
+///
+/// Errors on a malformed config or an `opening` not laid out by it, while a wrong proof is `Ok(false)`.
+pub fn verify_openings<E: Pairing, T: TranscriptHash>(
+    config: &OpeningSetConfig,
+    vk: &VerificationKey<E>,
+    opening: &OpeningProof<E>,
+    seed: &E::ScalarField,
+) -> anyhow::Result<bool> {
+    opening.check_layout(config)?;
+    let challenges = OpeningChallenges::compute::<T>(vk, opening, seed);
+    verify_openings_with_challenges(config, vk, opening, &challenges)
+}
+
+/// Same as `verify_openings`, with the challenges of another transcript, eg: the ones of a snarkjs
+/// proof laid out by `OpeningProof::from_plonk`, see `OpeningChallenges::from_plonk`.
+pub fn verify_openings_with_challenges<E: Pairing>(
+    config: &OpeningSetConfig,
+    vk: &VerificationKey<E>,
+    opening: &OpeningProof<E>,
+    challenges: &OpeningChallenges<E>,
+) -> anyhow::Result<bool> {
+    let Some(fej) = compute_opening_fej(config, vk, opening, challenges)? else {
+        return Ok(false);
+    };
+
+    // A1 = F - E - J + y·W2
+    let a1 = (fej.F.into_group() - fej.E - fej.J + opening.w2 * challenges.y).into_affine();
+
+    Ok(check_pairing_with_w2(vk, &a1, &opening.w2))
+}
+
+// A primitive t-th root of unity: g^((r-1)/t), where g is the multiplicative generator.
+fn primitive_root_of_unity<F: PrimeField>(t: u64) -> F {
+    let exponent = (Into::<BigUint>::into(F::MODULUS) - 1u32) / t;
+    F::GENERATOR.pow(exponent.to_u64_digits())
+}
+
+// A t-th root of a, whose order is a power of 2 (eg: a power of the domain omega w).
+//      t = 2^e·m with m odd: a^(m^-1 mod 2^64) is a m-th root of a, then take e square roots.
+fn root_of_omega<F: Field>(a: F, t: u64) -> Option<F> {
+    let twos = t.trailing_zeros();
+    let odd = t >> twos;
+
+    // Newton iteration for odd^-1 mod 2^64: each step doubles the correct low bits.
+    let mut odd_inv = odd;
+    for _ in 0..6 {
+        odd_inv = odd_inv.wrapping_mul(2u64.wrapping_sub(odd.wrapping_mul(odd_inv)));
+    }
+
+    let mut root = a.pow([odd_inv]);
+    for _ in 0..twos {
+        root = root.sqrt()?;
+    }
+    Some(root)
+}
+
+// ∑_j coefficients[j]·x^j
+fn horner<F: Field>(coefficients: &[F], x: &F) -> F {
+    coefficients
+        .iter()
+        .rev()
+        .fold(F::zero(), |acc, coefficient| acc * x + coefficient)
+}
+
+// Evaluate at y the polynomial interpolating (points[k], values[k]):
+//      r(y) = ∑_k values[k]·∏_{m≠k} (y - points[m])/(points[k] - points[m])
+// Returns `None` if the points aren't distinct.
+fn interpolate_at<F: Field>(points: &[F], values: &[F], y: &F) -> Option<F> {
+    let others = |k: usize| (0..points.len()).filter(move |m| *m != k);
+
+    let mut dens = (0..points.len())
+        .map(|k| others(k).map(|m| points[k] - points[m]).product::<F>())
+        .collect::<Vec<_>>();
+    if dens.iter().any(|den| den.is_zero()) {
+        return None;
+    }
+    batch_inversion(&mut dens);
+
+    Some(
+        (0..points.len())
+            .map(|k| values[k] * dens[k] * others(k).map(|m| *y - points[m]).product::<F>())
+            .sum(),
+    )
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+    use crate::simulator::Simulator;
+    use crate::transcript::Keccak256TranscriptHash;
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ff::UniformRand;
+    use std::str::FromStr;
+
+    fn mock() -> (VerificationKey, Proof, Fr) {
+        let vk = VerificationKey::default();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        (vk, proof, pub_input)
+    }
+
+    fn sorted(mut roots: Vec<Fr>) -> Vec<Fr> {
+        roots.sort();
+        roots
+    }
+
+    #[test]
+    fn test_plonk_opening_roots() {
+        let (vk, proof, pub_input) = mock();
+        let config = OpeningSetConfig::plonk();
+        assert_eq!(config.xi_exponent().unwrap(), 24);
+
+        let challenges = Challenges::compute::<Keccak256TranscriptHash>(&vk, &proof, &pub_input);
+        let opening_challenges =
+            OpeningChallenges::from_plonk::<Keccak256TranscriptHash>(&proof, &challenges);
+        let roots =
+            compute_opening_roots(&config, &opening_challenges.xi_seed, &vk.omega.w).unwrap();
+
+        let expect = challenges.roots;
+        assert_eq!(sorted(roots[0][0].clone()), sorted(expect.h0w8.to_vec()));
+        assert_eq!(sorted(roots[1][0].clone()), sorted(expect.h1w4.to_vec()));
+        assert_eq!(sorted(roots[2][0].clone()), sorted(expect.h2w3.to_vec()));
+        assert_eq!(sorted(roots[2][1].clone()), sorted(expect.h3w3.to_vec()));
+    }
+
+    #[test]
+    fn test_verify_plonk_openings() {
+        let (vk, proof, pub_input) = mock();
+        let config = OpeningSetConfig::plonk();
+        let challenges = Challenges::compute::<Keccak256TranscriptHash>(&vk, &proof, &pub_input);
+        let opening_challenges =
+            OpeningChallenges::from_plonk::<Keccak256TranscriptHash>(&proof, &challenges);
+
+        let opening = OpeningProof::from_plonk(&vk, &proof, &challenges, &pub_input).unwrap();
+        let verify = |opening: &OpeningProof| {
+            verify_openings_with_challenges(&config, &vk, opening, &opening_challenges)
+        };
+        assert!(verify(&opening).unwrap());
+
+        // the same [F]_1, [E]_1 and [J]_1 as the PLONK-specific pipeline.
+        let inversion = crate::inversion::Inversion::build(&vk, &proof, &challenges);
+        let L_1 = LangrangePolynomialEvaluation::compute_L1_polynomial_evaluation(
+            &challenges.zh,
+            &inversion.eval_l1,
+        );
+        let pi = compute_pi(&[pub_input], &[L_1]);
+        let (R0, R1, R2) =
+            crate::compute_r::compute_r(&vk, &proof, &challenges, &inversion, &L_1, &pi);
+        let expect = FEJ::compute(&vk, &proof, &challenges, &inversion, R0, R1, R2);
+        let actual = compute_opening_fej(&config, &vk, &opening, &opening_challenges)
+            .unwrap()
+            .unwrap();
+        assert_eq!(actual, expect);
+
+        let mut wrong = opening.clone();
+        wrong.evaluations[2][1][0] += Fr::one();
+        assert!(!verify(&wrong).unwrap());
+
+        let mut wrong = opening.clone();
+        wrong.evaluations[1][0].pop();
+        assert!(verify(&wrong).is_err());
+
+        // C0 is the one of the key, not of the proof
+        let mut wrong = opening.clone();
+        wrong.commitments.insert(0, vk.c0);
+        assert!(verify(&wrong).is_err());
+
+        // the snarkjs challenges aren't the ones of the opening transcript
+        assert!(!verify_openings::<_, Keccak256TranscriptHash>(
+            &config,
+            &vk,
+            &opening,
+            &challenges.gamma
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_opening_set_config() {
+        let config: OpeningSetConfig = serde_json::from_str(
+            r#"{"commitments": [
+                {"polynomials": 8, "openings": [0]},
+                {"polynomials": 4, "openings": [0]},
+                {"polynomials": 3, "openings": [0, 1]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(config, OpeningSetConfig::plonk());
+        assert!(config.validate::<Fr>().is_ok());
+
+        let invalid = |polynomials, openings| OpeningSetConfig {
+            commitments: vec![CommitmentConfig {
+                polynomials,
+                openings,
+            }],
+        };
+        assert!(invalid(0, vec![0]).validate::<Fr>().is_err());
+        // 5 doesn't divide r-1 of BN254.
+        assert!(invalid(5, vec![0]).validate::<Fr>().is_err());
+        assert!(invalid(4, vec![]).validate::<Fr>().is_err());
+        assert!(invalid(4, vec![1, 1]).validate::<Fr>().is_err());
+        // 2^11 divides r-1, but is too many roots to compute.
+        assert!(invalid(MAX_BATCH_SIZE, vec![0]).validate::<Fr>().is_ok());
+        assert!(invalid(2 * MAX_BATCH_SIZE, vec![0])
+            .validate::<Fr>()
+            .is_err());
+        // lcm(2^40, 3^30) doesn't fit in u64.
+        let overflow = OpeningSetConfig {
+            commitments: [1 << 40, 3usize.pow(30)]
+                .map(|polynomials| CommitmentConfig {
+                    polynomials,
+                    openings: vec![0],
+                })
+                .to_vec(),
+        };
+        assert!(overflow.xi_exponent().is_err());
+        assert!(overflow.validate::<Fr>().is_err());
+        assert!(OpeningSetConfig {
+            commitments: vec![]
+        }
+        .validate::<Fr>()
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_custom_openings() {
+        // extra committed columns: C0 batches 2 polynomials, C1 batches 6 and C2 batches 4 opened at xi and xi·w.
+        let config = OpeningSetConfig {
+            commitments: vec![
+                CommitmentConfig {
+                    polynomials: 2,
+                    openings: vec![0],
+                },
+                CommitmentConfig {
+                    polynomials: 6,
+                    openings: vec![0],
+                },
+                CommitmentConfig {
+                    polynomials: 4,
+                    openings: vec![0, 1],
+                },
+            ],
+        };
+        assert_eq!(config.xi_exponent().unwrap(), 12);
+
+        // simulate the proof with the trapdoor of the key.
+        let rng = &mut ark_std::test_rng();
+        let simulator = Simulator::<Bn254>::new(3, Fr::rand(rng), rng).unwrap();
+        let vk = simulator.vk.clone();
+        let mut opening = OpeningProof::<Bn254> {
+            commitments: (0..2).map(|_| G1Affine::rand(rng)).collect(),
+            evaluations: config
+                .commitments
+                .iter()
+                .map(|commitment| {
+                    commitment
+                        .openings
+                        .iter()
+                        .map(|_| (0..commitment.polynomials).map(|_| Fr::rand(rng)).collect())
+                        .collect()
+                })
+                .collect(),
+            w1: G1Affine::rand(rng),
+            w2: G1Affine::zero(),
+        };
+        let seed = Fr::rand(rng);
+        let challenges =
+            OpeningChallenges::compute::<Keccak256TranscriptHash>(&vk, &opening, &seed);
+
+        let roots = compute_opening_roots(&config, &challenges.xi_seed, &vk.omega.w).unwrap();
+        let xi = challenges.xi_seed.pow([12]);
+        assert!(roots[1][0].iter().all(|root| root.pow([6]) == xi));
+        assert!(roots[2][1]
+            .iter()
+            .all(|root| root.pow([4]) == xi * vk.omega.w));
+
+        simulator
+            .open::<Keccak256TranscriptHash>(&config, &mut opening, &seed)
+            .unwrap();
+        let verify = |vk: &VerificationKey, opening: &OpeningProof, seed: &Fr| {
+            verify_openings::<_, Keccak256TranscriptHash>(&config, vk, opening, seed).unwrap()
+        };
+        assert!(verify(&vk, &opening, &seed));
+
+        // the challenges are bound to the seed and C0 of the key
+        assert!(!verify(&vk, &opening, &(seed + Fr::one())));
+        let other_c0 = VerificationKey {
+            c0: G1Affine::rand(rng),
+            ..vk.clone()
+        };
+        assert!(!verify(&other_c0, &opening, &seed));
+
+        // the evaluations are bound by the transcript and the pairing.
+        opening.evaluations[0][0][1] += Fr::one();
+        assert!(!verify(&vk, &opening, &seed));
+    }
+}
@@ -1,52 +1,420 @@
+use crate::curve::point_to_bytes_be;
+use crate::evm;
 use crate::proof::Proof;
+use crate::transcript::TranscriptHash;
 use crate::vk::VerificationKey;
-use ark_bn254::{Bn254, G1Affine};
+use ark_bn254::{Bn254, Fq12, Fr, G1Affine, G1Projective};
 use ark_ec::bn::{G1Prepared, G2Prepared};
 use ark_ec::pairing::Pairing;
-use ark_ec::CurveGroup;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, Zero};
 use num_traits::One;
 use on_proving_pairings::prover::PairingProver;
 use on_proving_pairings::setup::PairingPVKey;
 use on_proving_pairings::verifier::PairingVerifier;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 
-pub fn check_pairing(vk: &VerificationKey, proof: &Proof, a1: &G1Affine) -> bool {
+pub fn check_pairing<E: Pairing>(
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    a1: &E::G1Affine,
+) -> bool {
+    check_pairing_with_w2(vk, a1, &proof.polynomials.w2.into_affine())
+}
+
+// e(p1,vk.g2)·e(w2,-vk.X2) = 1, for the verifiers whose W2 isn't in a `Proof`.
+pub fn check_pairing_with_w2<E: Pairing>(
+    vk: &VerificationKey<E>,
+    a1: &E::G1Affine,
+    w2: &E::G1Affine,
+) -> bool {
     // prepare pairing data
-    let p1 = a1;
-    let p2 = proof.polynomials.w2.into_affine();
+    let p1 = a1.into_group();
+    let p2 = w2.into_group();
 
     // Pi: [p1, proof.w2]
-    let lhs: [G1Prepared<ark_bn254::Config>; 2] = [p1.into(), p2.into()];
+    let lhs = [p1, p2];
     // Qi: [vk.g2, -vk.X2]
-    let rhs: [G2Prepared<ark_bn254::Config>; 2] = [vk.g2.into(), (-vk.x2).into()];
+    let rhs = [vk.g2.into_group(), -vk.x2.into_group()];
+
+    // The Miller loop of points off the curves, eg: of a corrupted vk, may be 0, which has no
+    // final exponentiation: rather than panicking as `multi_pairing`, the check fails.
+    let res = E::final_exponentiation(E::multi_miller_loop(lhs, rhs));
+
+    res.is_some_and(|res| res.0.is_one())
+}
+
+/// The check of e(A1, vk.g2)·e(W2, -vk.X2) = 1 at the end of the verifier, see `fflonk_verifier_with_backend`.
+///
+/// Implement it to plug another pairing in the verifier, eg: the accelerated one of a zkVM guest.
+pub trait PairingBackend<E: Pairing> {
+    fn check_pairing(&self, vk: &VerificationKey<E>, a1: &E::G1Affine, w2: &E::G1Affine) -> bool;
+}
+
+/// The arkworks multi-pairing, ie: `check_pairing`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ArkworksBackend;
+
+impl<E: Pairing> PairingBackend<E> for ArkworksBackend {
+    fn check_pairing(&self, vk: &VerificationKey<E>, a1: &E::G1Affine, w2: &E::G1Affine) -> bool {
+        check_pairing_with_w2(vk, a1, w2)
+    }
+}
+
+/// The on-proving-pairings prover and verifier, ie: `prove_and_verify_pairing`. BN254 only.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OnProvingPairingsBackend;
+
+impl PairingBackend<Bn254> for OnProvingPairingsBackend {
+    fn check_pairing(&self, vk: &VerificationKey, a1: &G1Affine, w2: &G1Affine) -> bool {
+        let artifact = prove_pairing(vk, a1, w2);
+        PairingVerifier::verify(&artifact.pvk(), artifact.final_f)
+    }
+}
+
+/// The designated verifier, who knows the trapdoor x of vk.X2 = x·vk.g2:
+/// the pairing check is then the same as A1 = x·W2, with no pairing at all.
+///
+/// The backend is bound to the X_2 of the key it was built for, and fails the check of any other key.
+#[derive(Debug, Clone, Copy)]
+pub struct DesignatedVerifierBackend<E: Pairing = Bn254> {
+    trapdoor: E::ScalarField,
+    x2: E::G2Affine,
+}
 
-    let res = Bn254::multi_pairing(lhs, rhs);
+impl<E: Pairing> DesignatedVerifierBackend<E> {
+    // Check the trapdoor is the one of the vk, otherwise A1 = x·W2 isn't the pairing check.
+    pub fn new(vk: &VerificationKey<E>, trapdoor: E::ScalarField) -> anyhow::Result<Self> {
+        if vk.g2 * trapdoor != vk.x2.into_group() {
+            anyhow::bail!("The trapdoor doesn't match X_2 of the verification key");
+        }
+        Ok(Self {
+            trapdoor,
+            x2: vk.x2,
+        })
+    }
+}
+
+impl<E: Pairing> PairingBackend<E> for DesignatedVerifierBackend<E> {
+    fn check_pairing(&self, vk: &VerificationKey<E>, a1: &E::G1Affine, w2: &E::G1Affine) -> bool {
+        // A1 = x·W2 is only the pairing check of the key of the trapdoor
+        vk.x2 == self.x2 && a1.into_group() == *w2 * self.trapdoor
+    }
+}
+
+/// The ecPairing precompile of `fflonkVerifier.sol`, over its byte interface:
+///     ecPairing(A1 || vk.g2 || -W2 || vk.X2) = 1
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EvmPrecompileBackend;
 
-    // assert!(res.0.is_one(), "Proof verification failed!");
-    res.0.is_one()
+impl PairingBackend<Bn254> for EvmPrecompileBackend {
+    fn check_pairing(&self, vk: &VerificationKey, a1: &G1Affine, w2: &G1Affine) -> bool {
+        let input = [
+            evm::encode_g1(a1).as_slice(),
+            &evm::encode_g2(&vk.g2),
+            &evm::encode_g1(&(-*w2)),
+            &evm::encode_g2(&vk.x2),
+        ]
+        .concat();
+        // a failing call is a failing check
+        evm::ec_pairing(&input).is_ok_and(|res| res[31] == 1)
+    }
 }
 
 // prove and verify pairings:
 //      e(p1,vk.g2)=e(proof.w2,-vk.X2)
+//
+// on-proving-pairings is BN254-only, see `OnProvingPairingsBackend`.
 pub fn prove_and_verify_pairing(vk: &VerificationKey, proof: &Proof, a1: &G1Affine) -> bool {
-    // prepare pairing data
-    let p1 = a1;
-    let p2 = proof.polynomials.w2.into_affine();
+    OnProvingPairingsBackend.check_pairing(vk, a1, &proof.polynomials.w2.into_affine())
+}
 
-    // Pi: [p1, proof.w2]
-    let lhs: [G1Prepared<ark_bn254::Config>; 2] = [p1.into(), p2.into()];
-    // Qi: [vk.g2, -vk.X2]
-    let rhs: [G2Prepared<ark_bn254::Config>; 2] = [vk.g2.into(), (-vk.x2).into()];
+/// The witness of the on-proving-pairings check of e(A1, vk.g2)·e(W2, -vk.X2) = 1,
+/// for a cheaper verifier (eg: a BitVM script or a circuit) than the pairing itself.
+///
+/// The lines of the fixed [vk.g2, -vk.X2] aren't part of it, as the verifier precomputes them from the vk.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PairingArtifact {
+    // The Miller loop output, shifted by the residue c.
+    #[serde(with = "crate::serde::fqk")]
+    pub final_f: Fq12,
+    // The residue witness: the Miller loop output is c^λ·wi.
+    #[serde(with = "crate::serde::fqk")]
+    pub c: Fq12,
+    #[serde(with = "crate::serde::fqk")]
+    pub wi: Fq12,
+}
+
+impl PairingArtifact {
+    pub fn load<P: AsRef<Path>>(artifact_path: P) -> anyhow::Result<Self> {
+        let mut file = File::open(artifact_path)?;
+        let mut artifact_json = String::new();
+        file.read_to_string(&mut artifact_json)?;
+        Ok(serde_json::from_str(&artifact_json)?)
+    }
+
+    pub fn pvk(&self) -> PairingPVKey {
+        PairingPVKey {
+            c: self.c,
+            c_inv: self.c.inverse().unwrap_or_default(),
+            wi: self.wi,
+        }
+    }
+}
+
+/// The lines of the fixed G2 points of the vk, [vk.g2] and [-vk.X2], precomputed once
+/// and reused across all the pairs on them.
+#[derive(Debug, Clone)]
+pub struct PreparedVerificationKey {
+    pub g2: G2Prepared<ark_bn254::Config>,
+    pub minus_x2: G2Prepared<ark_bn254::Config>,
+}
+
+impl From<&VerificationKey> for PreparedVerificationKey {
+    fn from(vk: &VerificationKey) -> Self {
+        Self {
+            g2: vk.g2.into(),
+            minus_x2: (-vk.x2).into(),
+        }
+    }
+}
+
+impl PreparedVerificationKey {
+    // As Q repeats, ∏ e(A1_i, vk.g2)·e(W2_i, -vk.X2) = e(∑ A1_i, vk.g2)·e(∑ W2_i, -vk.X2):
+    //      Pi: [∑ A1_i, ∑ W2_i], Qi: [vk.g2, -vk.X2]
+    // so the lines are cloned once, whatever the number of pairs.
+    fn pairing_inputs(
+        &self,
+        pairs: &[(G1Affine, G1Affine)],
+    ) -> (Vec<G1Affine>, Vec<G2Prepared<ark_bn254::Config>>) {
+        if pairs.is_empty() {
+            return (vec![], vec![]);
+        }
+        let (a1, w2) = pairs.iter().fold(
+            (G1Projective::zero(), G1Projective::zero()),
+            |(a1, w2), pair| (a1 + pair.0, w2 + pair.1),
+        );
+        (
+            G1Projective::normalize_batch(&[a1, w2]),
+            vec![self.g2.clone(), self.minus_x2.clone()],
+        )
+    }
+}
+
+/// Run the on-proving-pairings prover on the product ∏ e(P_i, Q_i) of N pairs, and keep its witness.
+pub fn prove_multi_pairing(
+    p: &[G1Affine],
+    q: &[G2Prepared<ark_bn254::Config>],
+) -> anyhow::Result<PairingArtifact> {
+    if p.is_empty() || p.len() != q.len() {
+        anyhow::bail!(
+            "Expect as many G1 as G2 points, got {} and {}",
+            p.len(),
+            q.len()
+        );
+    }
 
     // setup: finding_c
-    let pairing_pvk = PairingPVKey::setup(lhs.to_vec(), rhs.to_vec());
+    let lhs = p
+        .iter()
+        .map(|p| (*p).into())
+        .collect::<Vec<G1Prepared<_>>>();
+    let pairing_pvk = PairingPVKey::setup(lhs, q.to_vec());
+
+    // eval_points: [P1..PN], precompute lines: [Q1..QN]
+    let final_f = PairingProver::prove_dual_pairing(p.to_vec(), q, &pairing_pvk);
+
+    Ok(PairingArtifact {
+        final_f,
+        c: pairing_pvk.c,
+        wi: pairing_pvk.wi,
+    })
+}
+
+/// The standalone check of a `PairingArtifact` of ∏ e(P_i, Q_i).
+///
+/// `final_f` is recomputed from the points and the lines, so the artifact can't be
+/// replayed for other points, then checked against the residue witness c and wi.
+pub fn verify_multi_pairing_artifact(
+    p: &[G1Affine],
+    q: &[G2Prepared<ark_bn254::Config>],
+    artifact: &PairingArtifact,
+) -> bool {
+    if p.is_empty() || p.len() != q.len() {
+        return false;
+    }
+    let pvk = artifact.pvk();
+
+    let final_f = PairingProver::prove_dual_pairing(p.to_vec(), q, &pvk);
+    final_f == artifact.final_f && PairingVerifier::verify(&pvk, final_f)
+}
+
+/// Run the on-proving-pairings prover on ∏ e(A1_i, vk.g2)·e(W2_i, -vk.X2), with the lines of the vk.
+///
+/// The product of several fflonk checks passes even if they cancel out each other,
+/// so the pairs of independent proofs should go through `randomize_pairs` first.
+pub fn prove_batch_pairing(
+    pvk: &PreparedVerificationKey,
+    pairs: &[(G1Affine, G1Affine)],
+) -> anyhow::Result<PairingArtifact> {
+    let (p, q) = pvk.pairing_inputs(pairs);
+    prove_multi_pairing(&p, &q)
+}
+
+/// The standalone check of a `PairingArtifact` of `prove_batch_pairing`.
+pub fn verify_batch_pairing_artifact(
+    pvk: &PreparedVerificationKey,
+    pairs: &[(G1Affine, G1Affine)],
+    artifact: &PairingArtifact,
+) -> bool {
+    let (p, q) = pvk.pairing_inputs(pairs);
+    verify_multi_pairing_artifact(&p, &q, artifact)
+}
+
+/// Scale the i-th pair (A1_i, W2_i) by rho^i, with rho = H(A1_0 || W2_0 || A1_1 || W2_1 || ...),
+/// so that ∏ e(A1_i, vk.g2)·e(W2_i, -vk.X2) = 1 implies every single check passes.
+pub fn randomize_pairs<T: TranscriptHash>(
+    pairs: &[(G1Affine, G1Affine)],
+) -> Vec<(G1Affine, G1Affine)> {
+    let concatenated = pairs
+        .iter()
+        .flat_map(|(a1, w2)| [point_to_bytes_be(a1), point_to_bytes_be(w2)].concat())
+        .collect::<Vec<_>>();
+    let rho: Fr = T::hash_to_fr(concatenated);
+
+    let mut scalar = Fr::one();
+    pairs
+        .iter()
+        .map(|(a1, w2)| {
+            let pair = ((*a1 * scalar).into_affine(), (*w2 * scalar).into_affine());
+            scalar *= rho;
+            pair
+        })
+        .collect()
+}
+
+/// Run the on-proving-pairings prover on e(A1, vk.g2)·e(W2, -vk.X2), and keep its witness.
+pub fn prove_pairing(vk: &VerificationKey, a1: &G1Affine, w2: &G1Affine) -> PairingArtifact {
+    prove_batch_pairing(&vk.into(), &[(*a1, *w2)]).expect("a single pair")
+}
+
+/// The standalone check of a `PairingArtifact`, from A1, W2 and the vk only.
+pub fn verify_pairing_artifact(
+    vk: &VerificationKey,
+    a1: &G1Affine,
+    w2: &G1Affine,
+    artifact: &PairingArtifact,
+) -> bool {
+    verify_batch_pairing_artifact(&vk.into(), &[(*a1, *w2)], artifact)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transcript::Keccak256TranscriptHash;
+    use ark_bn254::G2Affine;
+    use ark_ff::UniformRand;
+
+    #[test]
+    fn test_pairing_artifact() {
+        let rng = &mut ark_std::test_rng();
+        let x = Fr::rand(rng);
+        let vk = VerificationKey {
+            x2: (vk_g2() * x).into_affine(),
+            ..Default::default()
+        };
+        // A1 = x·W2
+        let w2 = G1Affine::rand(rng);
+        let a1 = (w2 * x).into_affine();
+
+        let artifact = prove_pairing(&vk, &a1, &w2);
+        assert!(verify_pairing_artifact(&vk, &a1, &w2, &artifact));
+
+        let json = serde_json::to_string(&artifact).unwrap();
+        let decoded: PairingArtifact = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, artifact);
+
+        // bound to A1 and W2
+        let other = G1Affine::rand(rng);
+        assert!(!verify_pairing_artifact(&vk, &other, &w2, &artifact));
+        assert!(!verify_pairing_artifact(&vk, &a1, &other, &artifact));
+        // and to the vk
+        let other_vk = VerificationKey {
+            x2: (vk_g2() * (x + Fr::from(1u64))).into_affine(),
+            ..Default::default()
+        };
+        assert!(!verify_pairing_artifact(&other_vk, &a1, &w2, &artifact));
+    }
+
+    #[test]
+    fn test_batch_pairing_artifact() {
+        let rng = &mut ark_std::test_rng();
+        let x = Fr::rand(rng);
+        let vk = VerificationKey {
+            x2: (vk_g2() * x).into_affine(),
+            ..Default::default()
+        };
+        let pvk = PreparedVerificationKey::from(&vk);
+
+        // N fflonk checks: A1_i = x·W2_i
+        let pairs = (0..4)
+            .map(|_| {
+                let w2 = G1Affine::rand(rng);
+                ((w2 * x).into_affine(), w2)
+            })
+            .collect::<Vec<_>>();
+        for n in 1..=pairs.len() {
+            let artifact = prove_batch_pairing(&pvk, &pairs[..n]).unwrap();
+            assert!(verify_batch_pairing_artifact(&pvk, &pairs[..n], &artifact));
+            assert!(!verify_batch_pairing_artifact(
+                &pvk,
+                &pairs[..n - 1],
+                &artifact
+            ));
+        }
+        assert!(prove_batch_pairing(&pvk, &[]).is_err());
+
+        // two wrong checks cancelling out each other: (A1 + D, W2) and (A1' - D, W2')
+        let d = G1Affine::rand(rng);
+        let mut cancelled = pairs[..2].to_vec();
+        cancelled[0].0 = (cancelled[0].0 + d).into_affine();
+        cancelled[1].0 = (cancelled[1].0 - d).into_affine();
+        let artifact = prove_batch_pairing(&pvk, &cancelled).unwrap();
+        assert!(verify_batch_pairing_artifact(&pvk, &cancelled, &artifact));
+        // which the randomization catches
+        let randomized = randomize_pairs::<Keccak256TranscriptHash>(&cancelled);
+        let artifact = prove_batch_pairing(&pvk, &randomized).unwrap();
+        assert!(!verify_batch_pairing_artifact(&pvk, &randomized, &artifact));
+        let randomized = randomize_pairs::<Keccak256TranscriptHash>(&pairs);
+        let artifact = prove_batch_pairing(&pvk, &randomized).unwrap();
+        assert!(verify_batch_pairing_artifact(&pvk, &randomized, &artifact));
+    }
 
-    // eval_points: [P1,P2]
-    let eval_points = vec![*p1, p2];
-    // precompute lines: [Q1,Q2,Q3]
-    let q_prepared_lines = rhs[0..2].to_vec();
+    #[test]
+    fn test_multi_pairing_artifact() {
+        let rng = &mut ark_std::test_rng();
+        // e(a·P, Q1)·e(-P, a·Q1)·e(b·P, Q2)·e(P, -b·Q2) = 1, with arbitrary Q
+        let (a, b) = (Fr::rand(rng), Fr::rand(rng));
+        let p = G1Affine::rand(rng);
+        let (q1, q2) = (G2Affine::rand(rng), G2Affine::rand(rng));
+        let ps = [p * a, -p.into_group(), p * b, p.into_group()].map(|p| p.into_affine());
+        let qs = [q1.into_group(), q1 * a, q2.into_group(), -(q2 * b)]
+            .map(|q| G2Prepared::from(q.into_affine()));
 
-    let final_f = PairingProver::prove_dual_pairing(eval_points, &q_prepared_lines, &pairing_pvk);
+        let artifact = prove_multi_pairing(&ps, &qs).unwrap();
+        assert!(verify_multi_pairing_artifact(&ps, &qs, &artifact));
+        assert!(!verify_multi_pairing_artifact(
+            &ps[..3],
+            &qs[..3],
+            &artifact
+        ));
+        assert!(prove_multi_pairing(&ps, &qs[..3]).is_err());
+    }
 
-    // verify
-    PairingVerifier::verify(&pairing_pvk, final_f)
+    fn vk_g2() -> ark_bn254::G2Affine {
+        VerificationKey::<Bn254>::default().g2
+    }
 }
@@ -1,4 +1,7 @@
-use ark_bn254::{Fq, Fr, G1Projective};
+use crate::curve::SWPoint;
+use anyhow::{anyhow, ensure};
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
 use ark_ff::One;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
@@ -9,63 +12,72 @@ use std::str::FromStr;
 
 /// The Proof data: use the implemented conversion traits `TryFrom` to build it.
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-pub struct Proof {
-    pub polynomials: Polynomials,
-    pub evaluations: Evaluations,
+#[serde(bound = "E::G1: SWPoint<BaseField = E::BaseField>")]
+pub struct Proof<E: Pairing = Bn254> {
+    pub polynomials: Polynomials<E>,
+    pub evaluations: Evaluations<E>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(
+    rename_all = "SCREAMING_SNAKE_CASE",
+    bound = "E::G1: SWPoint<BaseField = E::BaseField>"
+)]
 /// Proof's Polynomial.
-pub struct Polynomials {
+pub struct Polynomials<E: Pairing = Bn254> {
     #[serde(with = "crate::serde::g1")]
-    pub c1: G1Projective,
+    pub c1: E::G1,
     #[serde(with = "crate::serde::g1")]
-    pub c2: G1Projective,
+    pub c2: E::G1,
     #[serde(with = "crate::serde::g1")]
-    pub w1: G1Projective,
+    pub w1: E::G1,
     #[serde(with = "crate::serde::g1")]
-    pub w2: G1Projective,
+    pub w2: E::G1,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
 // #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 /// Proof's Evaluation values.
-pub struct Evaluations {
+pub struct Evaluations<E: Pairing = Bn254> {
     #[serde(with = "crate::serde::fr")]
-    pub ql: Fr,
+    pub ql: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub qr: Fr,
+    pub qr: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub qm: Fr,
+    pub qm: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub qo: Fr,
+    pub qo: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub qc: Fr,
+    pub qc: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub s1: Fr,
+    pub s1: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub s2: Fr,
+    pub s2: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub s3: Fr,
+    pub s3: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub a: Fr,
+    pub a: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub b: Fr,
+    pub b: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub c: Fr,
+    pub c: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub z: Fr,
+    pub z: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub zw: Fr,
+    pub zw: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub t1w: Fr,
+    pub t1w: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub t2w: Fr,
+    pub t2w: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub inv: Fr,
+    pub inv: E::ScalarField,
 }
-impl Proof {
+
+impl<E: Pairing> Proof<E>
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+{
     pub fn load<P: AsRef<Path>>(vk_path: P) -> anyhow::Result<Self> {
         let mut file = File::open(vk_path)?;
         let mut vk_json = String::new();
@@ -74,47 +86,64 @@ impl Proof {
         Ok(snarkjs_vk)
     }
 
+    // The proof of its 24 values in decimal, as `MOCK_PROOF_DATA`: the affine coordinates of
+    // C1, C2, W1 and W2, then the evaluations. The points aren't checked to be on the curve.
+    // Panics on a malformed value, see `try_construct`.
     pub fn construct(proof_values: Vec<&str>) -> Self {
-        assert_eq!(proof_values.len(), 24);
-        let c1_x = Fq::from_str(proof_values[0]).unwrap();
-        let c1_y = Fq::from_str(proof_values[1]).unwrap();
-        let c1 = G1Projective::new(c1_x, c1_y, Fq::one());
-
-        let c2_x = Fq::from_str(proof_values[2]).unwrap();
-        let c2_y = Fq::from_str(proof_values[3]).unwrap();
-        let c2 = G1Projective::new(c2_x, c2_y, Fq::one());
-
-        let w1_x = Fq::from_str(proof_values[4]).unwrap();
-        let w1_y = Fq::from_str(proof_values[5]).unwrap();
-        let w1 = G1Projective::new(w1_x, w1_y, Fq::one());
+        Self::try_construct(&proof_values).unwrap()
+    }
 
-        let w2_x = Fq::from_str(proof_values[6]).unwrap();
-        let w2_y = Fq::from_str(proof_values[7]).unwrap();
-        let w2 = G1Projective::new(w2_x, w2_y, Fq::one());
+    // Same as `construct`, but fails rather than panicking on a malformed value.
+    pub fn try_construct(proof_values: &[&str]) -> anyhow::Result<Self> {
+        ensure!(
+            proof_values.len() == 24,
+            "The proof has {} values, not 24",
+            proof_values.len()
+        );
+        let base = |i: usize| {
+            E::BaseField::from_str(proof_values[i])
+                .map_err(|_e| anyhow!("The proof value {i} isn't a base field element"))
+        };
+        let point = |i: usize| -> anyhow::Result<E::G1> {
+            Ok(E::G1::from_coordinates(
+                base(i)?,
+                base(i + 1)?,
+                E::BaseField::one(),
+            ))
+        };
+        let fr = |i: usize| {
+            E::ScalarField::from_str(proof_values[i])
+                .map_err(|_e| anyhow!("The proof value {i} isn't a scalar field element"))
+        };
 
-        let polynomials = Polynomials { c1, c2, w1, w2 };
+        let polynomials = Polynomials {
+            c1: point(0)?,
+            c2: point(2)?,
+            w1: point(4)?,
+            w2: point(6)?,
+        };
         let evaluations = Evaluations {
-            ql: Fr::from_str(proof_values[8]).unwrap(),
-            qr: Fr::from_str(proof_values[9]).unwrap(),
-            qm: Fr::from_str(proof_values[10]).unwrap(),
-            qo: Fr::from_str(proof_values[11]).unwrap(),
-            qc: Fr::from_str(proof_values[12]).unwrap(),
-            s1: Fr::from_str(proof_values[13]).unwrap(),
-            s2: Fr::from_str(proof_values[14]).unwrap(),
-            s3: Fr::from_str(proof_values[15]).unwrap(),
-            a: Fr::from_str(proof_values[16]).unwrap(),
-            b: Fr::from_str(proof_values[17]).unwrap(),
-            c: Fr::from_str(proof_values[18]).unwrap(),
-            z: Fr::from_str(proof_values[19]).unwrap(),
-            zw: Fr::from_str(proof_values[20]).unwrap(),
-            t1w: Fr::from_str(proof_values[21]).unwrap(),
-            t2w: Fr::from_str(proof_values[22]).unwrap(),
-            inv: Fr::from_str(proof_values[23]).unwrap(),
+            ql: fr(8)?,
+            qr: fr(9)?,
+            qm: fr(10)?,
+            qo: fr(11)?,
+            qc: fr(12)?,
+            s1: fr(13)?,
+            s2: fr(14)?,
+            s3: fr(15)?,
+            a: fr(16)?,
+            b: fr(17)?,
+            c: fr(18)?,
+            z: fr(19)?,
+            zw: fr(20)?,
+            t1w: fr(21)?,
+            t2w: fr(22)?,
+            inv: fr(23)?,
         };
 
-        Self {
+        Ok(Self {
             polynomials,
             evaluations,
-        }
+        })
     }
 }
@@ -0,0 +1,517 @@
+use super::opening::prove_openings;
+use super::{domain, interleave, scale_variable, PlonkCircuit, PlonkWitness, Srs};
+use crate::challenge::Challenges;
+use crate::gate::{
+    batch_size, Arithmetization, CustomGateEvaluations, CustomGateProof, GateChallenges,
+    GateEquation,
+};
+use crate::opening::OpeningChallenges;
+use crate::proof::Polynomials;
+use crate::transcript::TranscriptHash;
+use crate::utils::compute_zero_poly_evals;
+use crate::vk::{Omega, VerificationKey};
+use anyhow::ensure;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::{batch_inversion, FftField, Field, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use ark_std::rand::RngCore;
+
+/// A circuit of 2^power rows of a custom gate `Arithmetization`: the gates
+///      gate(selectors, wires) + PI = 0
+/// where PI = -pub_input·L_1 as for `PlonkCircuit`, and the copy constraints as a permutation
+/// of the wires.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CustomGateCircuit<F: Field> {
+    pub power: u8,
+    // The values of the selectors on the rows, in the order of `GateEquation::selectors`.
+    pub selectors: Vec<Vec<F>>,
+    // The wires of the rows one after another, in the order of `GateEquation::wires`: the wire
+    // `column·n + row` is copied to the wire `permutation[column·n + row]`.
+    pub permutation: Vec<usize>,
+}
+
+impl<F: Field> CustomGateCircuit<F> {
+    pub fn n(&self) -> usize {
+        1 << self.power
+    }
+
+    pub fn validate(&self, gate: &GateEquation) -> anyhow::Result<()> {
+        gate.validate()?;
+        let n = self.n();
+        ensure!(
+            self.selectors.len() == gate.selectors.len(),
+            "The circuit has {} selectors instead of {}",
+            self.selectors.len(),
+            gate.selectors.len()
+        );
+        for (name, selector) in gate.selectors.iter().zip(&self.selectors) {
+            ensure!(
+                selector.len() == n,
+                "The selector {name} has {} rows instead of {n}",
+                selector.len()
+            );
+        }
+
+        let wires = gate.wires.len() * n;
+        ensure!(
+            self.permutation.len() == wires,
+            "The permutation has {} wires instead of {wires}",
+            self.permutation.len()
+        );
+        let mut seen = vec![false; wires];
+        for &wire in &self.permutation {
+            ensure!(
+                wire < wires && !seen[wire],
+                "The permutation isn't one of the wires"
+            );
+            seen[wire] = true;
+        }
+        Ok(())
+    }
+
+    // The values of the permutation polynomials on the domain: the wire `column·n + row` is
+    // ks[column]·w^row.
+    pub fn sigma_evaluations(&self, ks: &[F], w: F) -> Vec<Vec<F>> {
+        let n = self.n();
+        let mut powers = vec![F::one(); n];
+        for row in 1..n {
+            powers[row] = powers[row - 1] * w;
+        }
+        let id = |wire: usize| ks[wire / n] * powers[wire % n];
+
+        self.permutation
+            .chunks(n)
+            .map(|column| column.iter().map(|&wire| id(wire)).collect())
+            .collect()
+    }
+}
+
+// The circuit of the vanilla gate, for `Arithmetization::vanilla` with the k1 and k2 of the
+// circuit.
+impl<F: Field> From<PlonkCircuit<F>> for CustomGateCircuit<F> {
+    fn from(circuit: PlonkCircuit<F>) -> Self {
+        Self {
+            power: circuit.power,
+            selectors: vec![circuit.ql, circuit.qr, circuit.qo, circuit.qm, circuit.qc],
+            permutation: circuit.permutation,
+        }
+    }
+}
+
+/// The values of the wires on the rows of a `CustomGateCircuit`, in the order of
+/// `GateEquation::wires`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CustomGateWitness<F: Field> {
+    pub wires: Vec<Vec<F>>,
+}
+
+impl<F: Field> From<PlonkWitness<F>> for CustomGateWitness<F> {
+    fn from(witness: PlonkWitness<F>) -> Self {
+        Self {
+            wires: vec![witness.a, witness.b, witness.c],
+        }
+    }
+}
+
+/// What the prover needs of a circuit of a custom gate `Arithmetization`: the preprocessed
+/// polynomials in coefficients, along with the verification key.
+#[derive(Debug, Clone)]
+pub struct CustomGateProvingKey<E: Pairing = Bn254> {
+    pub vk: VerificationKey<E>,
+    pub arithmetization: Arithmetization<E>,
+    pub selectors: Vec<DensePolynomial<E::ScalarField>>,
+    pub sigmas: Vec<DensePolynomial<E::ScalarField>>,
+    // The values of the permutation polynomials on the domain.
+    pub sigma: Vec<Vec<E::ScalarField>>,
+    // The selectors and the permutation polynomials batched as laid out by
+    // `Arithmetization::opening_set_config`.
+    pub c0: DensePolynomial<E::ScalarField>,
+}
+
+impl<E: Pairing> CustomGateProvingKey<E> {
+    /// Preprocess the `circuit` of the `arithmetization` over the `srs`, committing to C0.
+    pub fn new(
+        arithmetization: Arithmetization<E>,
+        circuit: &CustomGateCircuit<E::ScalarField>,
+        srs: &Srs<E>,
+    ) -> anyhow::Result<Self> {
+        Self::with_columns(arithmetization, circuit, &[], srs)
+    }
+
+    // The key with preprocessed `columns` batched in C0 between the selectors and the permutation
+    // polynomials, eg: the lookup selector and the table of `LookupArithmetization`.
+    pub(super) fn with_columns(
+        arithmetization: Arithmetization<E>,
+        circuit: &CustomGateCircuit<E::ScalarField>,
+        columns: &[Vec<E::ScalarField>],
+        srs: &Srs<E>,
+    ) -> anyhow::Result<Self> {
+        arithmetization.validate()?;
+        circuit.validate(&arithmetization.gate)?;
+        let domain = domain::<E::ScalarField>(circuit.power)?;
+        ensure!(
+            columns.iter().all(|column| column.len() == circuit.n()),
+            "A preprocessed column doesn't have {} rows",
+            circuit.n()
+        );
+
+        let sigma = circuit.sigma_evaluations(&arithmetization.ks, domain.group_gen);
+        let interpolate =
+            |evals: &[E::ScalarField]| DensePolynomial::from_coefficients_vec(domain.ifft(evals));
+        let selectors = circuit
+            .selectors
+            .iter()
+            .map(|selector| interpolate(selector))
+            .collect::<Vec<_>>();
+        let sigmas = sigma
+            .iter()
+            .map(|sigma| interpolate(sigma))
+            .collect::<Vec<_>>();
+        let columns = columns
+            .iter()
+            .map(|column| interpolate(column))
+            .collect::<Vec<_>>();
+
+        let batched = selectors.iter().chain(&columns).chain(&sigmas);
+        let c0 = batch(
+            batched,
+            batch_size::<E::ScalarField>(selectors.len() + columns.len() + sigmas.len()),
+        );
+
+        // k1 and k2 of the key are the coset shifts of the second and the third wires, if any
+        let [k1, k2] = [1, 2].map(|i| arithmetization.ks.get(i).copied().unwrap_or_default());
+        let vk = VerificationKey {
+            power: circuit.power,
+            n: E::ScalarField::from(circuit.n() as u64),
+            k1,
+            k2,
+            x2: srs.x2,
+            c0: srs.commit(&c0)?,
+            g1: srs.g1_powers[0],
+            g2: srs.g2,
+            omega: Omega::new(circuit.power)?,
+        };
+
+        Ok(Self {
+            vk,
+            arithmetization,
+            selectors,
+            sigmas,
+            sigma,
+            c0,
+        })
+    }
+}
+
+/// Prove that the `witness` satisfies the circuit of `pk` with the `pub_input`, for
+/// `fflonk_custom_gate_verifier` under the transcript `T` of `GateChallenges::compute`:
+/// the wires and Z are blinded with `rng`, as `fflonk_prover` does.
+///
+/// Fails if the witness doesn't satisfy the gates or the copy constraints, or if the SRS is too
+/// small for the polynomials.
+pub fn fflonk_custom_gate_prover<E: Pairing, T: TranscriptHash, R: RngCore>(
+    pk: &CustomGateProvingKey<E>,
+    srs: &Srs<E>,
+    witness: &CustomGateWitness<E::ScalarField>,
+    pub_input: &E::ScalarField,
+    rng: &mut R,
+) -> anyhow::Result<CustomGateProof<E>> {
+    let arithmetization = &pk.arithmetization;
+    let config = arithmetization.opening_set_config()?;
+    let vk = &pk.vk;
+    let rounds = Rounds::new(pk, witness, pub_input)?;
+
+    // 1. C1: the wires and T0
+    let wires = rounds.wires(witness, rng);
+    let t0 = rounds.gate_quotient(pk, &wires)?;
+    let c1 = batch(wires.iter().chain([&t0]), config.commitments[1].polynomials);
+    let c1_commitment = srs.commit(&c1)?;
+
+    let beta = Challenges::<E>::compute_beta::<T>(&vk.c0, &c1_commitment, pub_input);
+    let gamma = Challenges::<E>::compute_gamma::<T>(&beta);
+
+    // 2. C2: Z, T1 and T2
+    let [z, t1, t2] = rounds.copy_polynomials(pk, witness, &wires, beta, gamma, rng)?;
+    let c2 = interleave(&[&z, &t1, &t2]);
+    let c2_commitment = srs.commit(&c2)?;
+
+    // 3. The evaluations at xi and xi·ω, see `GateChallenges::compute`
+    let xi_seed =
+        OpeningChallenges::compute_xi_seed::<T>(vk, &[c1_commitment, c2_commitment], &gamma);
+    let xi = xi_seed.pow([config.xi_exponent()?]);
+    let challenges = GateChallenges {
+        beta,
+        gamma,
+        xi_seed,
+        xi,
+        zh: compute_zero_poly_evals(&xi, &vk.n),
+    };
+    let xiw = xi * vk.omega.w;
+    let evaluate = |polynomials: &[DensePolynomial<E::ScalarField>]| {
+        polynomials.iter().map(|p| p.evaluate(&xi)).collect()
+    };
+    let mut proof = CustomGateProof {
+        polynomials: Polynomials {
+            c1: c1_commitment.into_group(),
+            c2: c2_commitment.into_group(),
+            w1: E::G1::zero(),
+            w2: E::G1::zero(),
+        },
+        evaluations: CustomGateEvaluations {
+            selectors: evaluate(&pk.selectors),
+            sigmas: evaluate(&pk.sigmas),
+            wires: evaluate(&wires),
+            z: z.evaluate(&xi),
+            zw: z.evaluate(&xiw),
+            t1w: t1.evaluate(&xiw),
+            t2w: t2.evaluate(&xiw),
+        },
+    };
+
+    // 4. W1 and W2, opening C0, C1 and C2
+    let mut opening = arithmetization.opening_proof(vk, &proof, &challenges, pub_input)?;
+    prove_openings::<E, T>(&config, srs, vk, &[&pk.c0, &c1, &c2], &mut opening, &gamma)?;
+    proof.polynomials.w1 = opening.w1.into_group();
+    proof.polynomials.w2 = opening.w2.into_group();
+    Ok(proof)
+}
+
+// The rounds of `fflonk_custom_gate_prover` shared with the lookup prover.
+pub(super) struct Rounds<F: FftField> {
+    pub(super) domain: Radix2EvaluationDomain<F>,
+    // L_1(X) and PI(X) = -pub_input·L_1(X)
+    pub(super) l1: DensePolynomial<F>,
+    pi: DensePolynomial<F>,
+}
+
+impl<F: FftField> Rounds<F> {
+    pub(super) fn new<E: Pairing<ScalarField = F>>(
+        pk: &CustomGateProvingKey<E>,
+        witness: &CustomGateWitness<F>,
+        pub_input: &F,
+    ) -> anyhow::Result<Self> {
+        let n = 1usize << pk.vk.power;
+        let domain = domain::<F>(pk.vk.power)?;
+        ensure!(
+            witness.wires.len() == pk.arithmetization.gate.wires.len()
+                && witness.wires.iter().all(|wire| wire.len() == n),
+            "The witness doesn't have {} wires of {n} rows",
+            pk.arithmetization.gate.wires.len()
+        );
+
+        let mut l1_evals = vec![F::zero(); n];
+        l1_evals[0] = F::one();
+        let l1 = DensePolynomial::from_coefficients_vec(domain.ifft(&l1_evals));
+        let pi = &l1 * -*pub_input;
+        Ok(Self { domain, l1, pi })
+    }
+
+    // The polynomial of the values on the domain, plus a random multiple of Z_H of `blinding`
+    // coefficients.
+    pub(super) fn blinded<R: RngCore>(
+        &self,
+        evals: &[F],
+        blinding: usize,
+        rng: &mut R,
+    ) -> DensePolynomial<F> {
+        let blinding =
+            DensePolynomial::from_coefficients_vec((0..blinding).map(|_| F::rand(rng)).collect());
+        let zh = DensePolynomial::from(self.domain.vanishing_polynomial());
+        DensePolynomial::from_coefficients_vec(self.domain.ifft(evals)) + &blinding * &zh
+    }
+
+    // p / Z_H, which must have no remainder.
+    pub(super) fn divide_by_zh(
+        &self,
+        p: &DensePolynomial<F>,
+        what: &str,
+    ) -> anyhow::Result<DensePolynomial<F>> {
+        let (quotient, remainder) = p.divide_by_vanishing_poly(self.domain).unwrap();
+        ensure!(
+            remainder.is_zero(),
+            "The witness doesn't satisfy the {what}"
+        );
+        Ok(quotient)
+    }
+
+    pub(super) fn wires<R: RngCore>(
+        &self,
+        witness: &CustomGateWitness<F>,
+        rng: &mut R,
+    ) -> Vec<DensePolynomial<F>> {
+        witness
+            .wires
+            .iter()
+            .map(|wire| self.blinded(wire, 2, rng))
+            .collect()
+    }
+
+    // T0 = (gate(selectors, wires) + PI) / Z_H
+    pub(super) fn gate_quotient<E: Pairing<ScalarField = F>>(
+        &self,
+        pk: &CustomGateProvingKey<E>,
+        wires: &[DensePolynomial<F>],
+    ) -> anyhow::Result<DensePolynomial<F>> {
+        let one = DensePolynomial::from_coefficients_vec(vec![F::one()]);
+        let gate = pk
+            .arithmetization
+            .gate
+            .terms
+            .iter()
+            .map(|term| {
+                let coefficient = if term.coefficient >= 0 {
+                    F::from(term.coefficient as u64)
+                } else {
+                    -F::from(term.coefficient.unsigned_abs())
+                };
+                let selectors = term.selectors.iter().map(|s| &pk.selectors[*s]);
+                let wires = term.wires.iter().map(|w| &wires[*w]);
+                &selectors.chain(wires).fold(one.clone(), |acc, p| &acc * p) * coefficient
+            })
+            .fold(self.pi.clone(), |acc, term| &acc + &term);
+        self.divide_by_zh(&gate, "gates")
+    }
+
+    // Z, T1 and T2 of the copy constraints over all the wires:
+    //      T1 = (Z - 1)·L_1 / Z_H
+    //      T2 = (Z·∏ (wire_i + β·k_i·X + γ) - Z(ω·X)·∏ (wire_i + β·S_i + γ)) / Z_H
+    pub(super) fn copy_polynomials<E: Pairing<ScalarField = F>, R: RngCore>(
+        &self,
+        pk: &CustomGateProvingKey<E>,
+        witness: &CustomGateWitness<F>,
+        wires: &[DensePolynomial<F>],
+        beta: F,
+        gamma: F,
+        rng: &mut R,
+    ) -> anyhow::Result<[DensePolynomial<F>; 3]> {
+        let ks = &pk.arithmetization.ks;
+        let n = self.domain.size();
+        let w = self.domain.group_gen;
+
+        let mut numerators = vec![F::one(); n];
+        let mut denominators = vec![F::one(); n];
+        let mut wi = F::one();
+        for i in 0..n {
+            for (j, wire) in witness.wires.iter().enumerate() {
+                numerators[i] *= wire[i] + beta * ks[j] * wi + gamma;
+                denominators[i] *= wire[i] + beta * pk.sigma[j][i] + gamma;
+            }
+            wi *= w;
+        }
+        batch_inversion(&mut denominators);
+        let mut z_evals = vec![F::one()];
+        for i in 0..n {
+            z_evals.push(z_evals[i] * numerators[i] * denominators[i]);
+        }
+        ensure!(
+            z_evals.pop() == Some(F::one()),
+            "The witness doesn't satisfy the copy constraints"
+        );
+        let z = self.blinded(&z_evals, 3, rng);
+
+        let one = DensePolynomial::from_coefficients_vec(vec![F::one()]);
+        let t1 = self.divide_by_zh(&(&(&z - &one) * &self.l1), "first row of Z")?;
+
+        let shifted = wires
+            .iter()
+            .zip(ks)
+            .map(|(wire, k)| wire + &DensePolynomial::from_coefficients_vec(vec![gamma, beta * k]));
+        let permuted = wires
+            .iter()
+            .zip(&pk.sigmas)
+            .map(|(wire, sigma)| &(wire + &(sigma * beta)) + &constant(gamma));
+        let zw = scale_variable(&z, w);
+        let copy =
+            &shifted.fold(z.clone(), |acc, p| &acc * &p) - &permuted.fold(zw, |acc, p| &acc * &p);
+        let t2 = self.divide_by_zh(&copy, "copy constraints")?;
+        Ok([z, t1, t2])
+    }
+}
+
+// The polynomials batched into one commitment of `size` polynomials, padded with zero ones.
+pub(super) fn batch<'a, F: Field>(
+    polynomials: impl Iterator<Item = &'a DensePolynomial<F>>,
+    size: usize,
+) -> DensePolynomial<F> {
+    let zero = DensePolynomial::zero();
+    let mut polynomials = polynomials.collect::<Vec<_>>();
+    polynomials.resize(size.max(polynomials.len()), &zero);
+    interleave(&polynomials)
+}
+
+fn constant<F: Field>(f: F) -> DensePolynomial<F> {
+    DensePolynomial::from_coefficients_vec(vec![f])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prover::ProvingKey;
+    use crate::transcript::{Blake3TranscriptHash, Keccak256TranscriptHash};
+    use crate::verifier::fflonk_custom_gate_verifier;
+    use ark_bn254::Fr;
+    use ark_ff::{One, UniformRand};
+
+    #[test]
+    fn test_fflonk_custom_gate_prover() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = crate::mock::multiplier2::<Fr>();
+        let witness = circuit.witness(&[1u64, 33, 3, 11].map(Fr::from)).unwrap();
+        let circuit = circuit.plonk_circuit().unwrap();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), 32 << circuit.power);
+        let pub_input = Fr::from(33u64);
+
+        // the vanilla arithmetization has the C0 of `ProvingKey`
+        let vk = ProvingKey::new(&circuit, &srs).unwrap().vk;
+        let pk = CustomGateProvingKey::new(Arithmetization::vanilla(&vk), &circuit.into(), &srs)
+            .unwrap();
+        assert_eq!(pk.vk, vk);
+
+        let witness = CustomGateWitness::from(witness);
+        let proof = fflonk_custom_gate_prover::<_, Keccak256TranscriptHash, _>(
+            &pk, &srs, &witness, &pub_input, rng,
+        )
+        .unwrap();
+        let verify = |proof: &CustomGateProof, pub_input: &Fr| {
+            fflonk_custom_gate_verifier::<_, Keccak256TranscriptHash>(
+                &pk.arithmetization,
+                &vk,
+                proof,
+                pub_input,
+            )
+            .unwrap()
+        };
+        assert!(verify(&proof, &pub_input));
+        assert!(!verify(&proof, &Fr::from(34u64)));
+        assert!(!fflonk_custom_gate_verifier::<_, Blake3TranscriptHash>(
+            &pk.arithmetization,
+            &vk,
+            &proof,
+            &pub_input
+        )
+        .unwrap());
+
+        // a witness of another public input fails the first gate
+        assert!(fflonk_custom_gate_prover::<_, Keccak256TranscriptHash, _>(
+            &pk,
+            &srs,
+            &witness,
+            &Fr::from(34u64),
+            rng
+        )
+        .is_err());
+
+        // so does a wire out of its gate, or copied to a wire of another value
+        for (column, row) in [(2, 1), (0, 2)] {
+            let mut wrong = witness.clone();
+            wrong.wires[column][row] += Fr::one();
+            assert!(fflonk_custom_gate_prover::<_, Keccak256TranscriptHash, _>(
+                &pk, &srs, &wrong, &pub_input, rng
+            )
+            .is_err());
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use super::gate::{batch, CustomGateCircuit, CustomGateProvingKey, CustomGateWitness, Rounds};
+use super::opening::prove_openings;
+use super::{domain, interleave, scale_variable, Srs};
+use crate::challenge::Challenges;
+use crate::curve::field_to_bytes_be;
+use crate::gate::{CustomGateEvaluations, GateChallenges};
+use crate::lookup::{LookupArithmetization, LookupChallenges, LookupEvaluations, LookupProof};
+use crate::opening::OpeningChallenges;
+use crate::proof::Polynomials;
+use crate::transcript::TranscriptHash;
+use crate::utils::compute_zero_poly_evals;
+use anyhow::ensure;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::{batch_inversion, Field, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Polynomial};
+use ark_std::rand::RngCore;
+use std::collections::HashMap;
+
+/// A `CustomGateCircuit` with a lookup of a wire into a fixed table: on every row, the wire is
+/// looked up with the weight of the lookup selector, see `lookup`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LookupCircuit<F: Field> {
+    pub gate: CustomGateCircuit<F>,
+    pub q_lookup: Vec<F>,
+    pub table: Vec<F>,
+}
+
+/// What the prover needs of a `LookupCircuit`: the key of the gate, with q and t batched in C0,
+/// and the lookup polynomials in coefficients.
+#[derive(Debug, Clone)]
+pub struct LookupProvingKey<E: Pairing = Bn254> {
+    pub gate: CustomGateProvingKey<E>,
+    pub arithmetization: LookupArithmetization<E>,
+    pub q_lookup: DensePolynomial<E::ScalarField>,
+    pub table: DensePolynomial<E::ScalarField>,
+    // The values of q and t on the domain.
+    pub q_lookup_evals: Vec<E::ScalarField>,
+    pub table_evals: Vec<E::ScalarField>,
+}
+
+impl<E: Pairing> LookupProvingKey<E> {
+    /// Preprocess the `circuit` of the `arithmetization` over the `srs`, committing to C0.
+    pub fn new(
+        arithmetization: LookupArithmetization<E>,
+        circuit: &LookupCircuit<E::ScalarField>,
+        srs: &Srs<E>,
+    ) -> anyhow::Result<Self> {
+        arithmetization.validate()?;
+        let columns = [circuit.q_lookup.clone(), circuit.table.clone()];
+        let gate = CustomGateProvingKey::with_columns(
+            arithmetization.arithmetization.clone(),
+            &circuit.gate,
+            &columns,
+            srs,
+        )?;
+
+        let domain = domain::<E::ScalarField>(circuit.gate.power)?;
+        let [q_lookup, table] = columns
+            .clone()
+            .map(|column| DensePolynomial::from_coefficients_vec(domain.ifft(&column)));
+        let [q_lookup_evals, table_evals] = columns;
+        Ok(Self {
+            gate,
+            arithmetization,
+            q_lookup,
+            table,
+            q_lookup_evals,
+            table_evals,
+        })
+    }
+}
+
+/// Prove that the `witness` satisfies the circuit of `pk` with the `pub_input`, for
+/// `fflonk_lookup_verifier` under the transcript `T` of `LookupChallenges::compute`: the wires,
+/// m, Z and phi are blinded with `rng`, as `fflonk_custom_gate_prover` does.
+///
+/// Fails if the witness doesn't satisfy the gates or the copy constraints, if a looked-up value
+/// isn't in the table, or if the SRS is too small for the polynomials.
+pub fn fflonk_lookup_prover<E: Pairing, T: TranscriptHash, R: RngCore>(
+    pk: &LookupProvingKey<E>,
+    srs: &Srs<E>,
+    witness: &CustomGateWitness<E::ScalarField>,
+    pub_input: &E::ScalarField,
+    rng: &mut R,
+) -> anyhow::Result<LookupProof<E>> {
+    prove::<E, T, R>(pk, srs, witness, pub_input, rng, true)
+}
+
+// The lookup prover: when `check` is false, the values missing from the table are left out of
+// the multiplicities and T3 is the quotient of the lookup whatever its remainder, as a cheating
+// prover would do.
+fn prove<E: Pairing, T: TranscriptHash, R: RngCore>(
+    pk: &LookupProvingKey<E>,
+    srs: &Srs<E>,
+    witness: &CustomGateWitness<E::ScalarField>,
+    pub_input: &E::ScalarField,
+    rng: &mut R,
+    check: bool,
+) -> anyhow::Result<LookupProof<E>> {
+    let arithmetization = &pk.arithmetization;
+    let config = arithmetization.opening_set_config()?;
+    let vk = &pk.gate.vk;
+    let rounds = Rounds::new(&pk.gate, witness, pub_input)?;
+    let looked_up = &witness.wires[arithmetization.lookup.wire];
+
+    // 1. C1: the wires, m and T0
+    let multiplicities = multiplicities(&pk.q_lookup_evals, looked_up, &pk.table_evals, check)?;
+    let wires = rounds.wires(witness, rng);
+    let m = rounds.blinded(&multiplicities, 2, rng);
+    let t0 = rounds.gate_quotient(&pk.gate, &wires)?;
+    let c1 = batch(
+        wires.iter().chain([&m, &t0]),
+        config.commitments[1].polynomials,
+    );
+    let c1_commitment = srs.commit(&c1)?;
+
+    let beta = Challenges::<E>::compute_beta::<T>(&vk.c0, &c1_commitment, pub_input);
+    let gamma = Challenges::<E>::compute_gamma::<T>(&beta);
+    let zeta = T::hash_to_fr([beta, gamma].iter().flat_map(field_to_bytes_be).collect());
+
+    // 2. C2: Z, T1, T2, phi, T3 and T4
+    let [z, t1, t2] = rounds.copy_polynomials(&pk.gate, witness, &wires, beta, gamma, rng)?;
+    let f = &wires[arithmetization.lookup.wire];
+    let [phi, t3, t4] = lookup_polynomials(
+        pk,
+        &rounds,
+        looked_up,
+        &multiplicities,
+        f,
+        &m,
+        zeta,
+        check,
+        rng,
+    )?;
+    let c2 = interleave(&[&z, &t1, &t2, &phi, &t3, &t4]);
+    let c2_commitment = srs.commit(&c2)?;
+
+    // 3. The evaluations at xi and xi·ω, see `LookupChallenges::compute`
+    let xi_seed =
+        OpeningChallenges::compute_xi_seed::<T>(vk, &[c1_commitment, c2_commitment], &zeta);
+    let xi = xi_seed.pow([config.xi_exponent()?]);
+    let challenges = LookupChallenges {
+        gate: GateChallenges {
+            beta,
+            gamma,
+            xi_seed,
+            xi,
+            zh: compute_zero_poly_evals(&xi, &vk.n),
+        },
+        zeta,
+    };
+    let xiw = xi * vk.omega.w;
+    let evaluate = |polynomials: &[DensePolynomial<E::ScalarField>]| {
+        polynomials.iter().map(|p| p.evaluate(&xi)).collect()
+    };
+    let mut proof = LookupProof {
+        polynomials: Polynomials {
+            c1: c1_commitment.into_group(),
+            c2: c2_commitment.into_group(),
+            w1: E::G1::zero(),
+            w2: E::G1::zero(),
+        },
+        evaluations: CustomGateEvaluations {
+            selectors: evaluate(&pk.gate.selectors),
+            sigmas: evaluate(&pk.gate.sigmas),
+            wires: evaluate(&wires),
+            z: z.evaluate(&xi),
+            zw: z.evaluate(&xiw),
+            t1w: t1.evaluate(&xiw),
+            t2w: t2.evaluate(&xiw),
+        },
+        lookup: LookupEvaluations {
+            q_lookup: pk.q_lookup.evaluate(&xi),
+            table: pk.table.evaluate(&xi),
+            m: m.evaluate(&xi),
+            phi: phi.evaluate(&xi),
+            phiw: phi.evaluate(&xiw),
+            t3w: t3.evaluate(&xiw),
+            t4w: t4.evaluate(&xiw),
+        },
+    };
+
+    // 4. W1 and W2, opening C0, C1 and C2
+    let mut opening = arithmetization.opening_proof(vk, &proof, &challenges, pub_input)?;
+    prove_openings::<E, T>(
+        &config,
+        srs,
+        vk,
+        &[&pk.gate.c0, &c1, &c2],
+        &mut opening,
+        &zeta,
+    )?;
+    proof.polynomials.w1 = opening.w1.into_group();
+    proof.polynomials.w2 = opening.w2.into_group();
+    Ok(proof)
+}
+
+// m[j] = ∑ q[i] over the rows i whose looked-up value is t[j], counted on the first row of the
+// table with that value.
+fn multiplicities<F: Field>(
+    q_lookup: &[F],
+    looked_up: &[F],
+    table: &[F],
+    check: bool,
+) -> anyhow::Result<Vec<F>> {
+    let mut rows = HashMap::new();
+    for (j, value) in table.iter().enumerate().rev() {
+        rows.insert(*value, j);
+    }
+
+    let mut m = vec![F::zero(); table.len()];
+    for (q, value) in q_lookup.iter().zip(looked_up) {
+        if q.is_zero() {
+            continue;
+        }
+        match rows.get(value) {
+            Some(j) => m[*j] += q,
+            None => ensure!(!check, "The looked-up value {value} isn't in the table"),
+        }
+    }
+    Ok(m)
+}
+
+// phi, T3 and T4 of the lookup, see the module documentation of `lookup`:
+//      phi(ω·X) - phi(X) = q(X)/(zeta + f(X)) - m(X)/(zeta + t(X)),  phi(1) = 0
+#[allow(clippy::too_many_arguments)]
+fn lookup_polynomials<E: Pairing, R: RngCore>(
+    pk: &LookupProvingKey<E>,
+    rounds: &Rounds<E::ScalarField>,
+    looked_up: &[E::ScalarField],
+    multiplicities: &[E::ScalarField],
+    f: &DensePolynomial<E::ScalarField>,
+    m: &DensePolynomial<E::ScalarField>,
+    zeta: E::ScalarField,
+    check: bool,
+    rng: &mut R,
+) -> anyhow::Result<[DensePolynomial<E::ScalarField>; 3]> {
+    let n = rounds.domain.size();
+    let mut inverses = looked_up
+        .iter()
+        .chain(&pk.table_evals)
+        .map(|value| zeta + value)
+        .collect::<Vec<_>>();
+    batch_inversion(&mut inverses);
+    let (f_inv, t_inv) = inverses.split_at(n);
+
+    let mut phi_evals = vec![E::ScalarField::zero()];
+    for i in 0..n {
+        phi_evals
+            .push(phi_evals[i] + pk.q_lookup_evals[i] * f_inv[i] - multiplicities[i] * t_inv[i]);
+    }
+    // phi wraps around the domain if the sums are equal
+    let wrap = phi_evals.pop();
+    ensure!(
+        !check || wrap == Some(E::ScalarField::zero()),
+        "The looked-up values aren't in the table"
+    );
+    let phi = rounds.blinded(&phi_evals, 3, rng);
+
+    let zeta = DensePolynomial::from_coefficients_vec(vec![zeta]);
+    let (f, t) = (f + &zeta, &pk.table + &zeta);
+    let phiw = scale_variable(&phi, rounds.domain.group_gen);
+    let lookup = &(&(&(&(&phiw - &phi) * &f) * &t) - &(&pk.q_lookup * &t)) + &(m * &f);
+    let t3 = if check {
+        rounds.divide_by_zh(&lookup, "lookup")?
+    } else {
+        lookup.divide_by_vanishing_poly(rounds.domain).unwrap().0
+    };
+    let t4 = rounds.divide_by_zh(&(&rounds.l1 * &phi), "first row of phi")?;
+    Ok([phi, t3, t4])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::multiplier2_lookup;
+    use crate::transcript::Keccak256TranscriptHash;
+    use crate::verifier::fflonk_lookup_verifier;
+    use ark_bn254::Fr;
+    use ark_ff::{One, UniformRand};
+
+    #[test]
+    fn test_fflonk_lookup_prover() {
+        let rng = &mut ark_std::test_rng();
+        let (arithmetization, circuit, witness) = multiplier2_lookup::<Bn254>();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), 32 << circuit.gate.power);
+        let pk = LookupProvingKey::new(arithmetization, &circuit, &srs).unwrap();
+        let pub_input = Fr::from(33u64);
+        let verify = |proof: &LookupProof| {
+            fflonk_lookup_verifier::<_, Keccak256TranscriptHash>(
+                &pk.arithmetization,
+                &pk.gate.vk,
+                proof,
+                &pub_input,
+            )
+            .unwrap()
+        };
+
+        // the cheating path of the prover proves an honest witness
+        for check in [true, false] {
+            let proof =
+                prove::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &pub_input, rng, check)
+                    .unwrap();
+            assert!(verify(&proof));
+        }
+
+        // a witness out of the gates fails before the lookup
+        let mut wrong = witness.clone();
+        wrong.wires[2][1] += Fr::one();
+        assert!(fflonk_lookup_prover::<_, Keccak256TranscriptHash, _>(
+            &pk, &srs, &wrong, &pub_input, rng
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_fflonk_lookup_prover_table_miss() {
+        let rng = &mut ark_std::test_rng();
+        // the public input 33 of the first row is looked up, but isn't in the table
+        let (arithmetization, mut circuit, witness) = multiplier2_lookup::<Bn254>();
+        circuit.q_lookup[0] = Fr::one();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), 32 << circuit.gate.power);
+        let pk = LookupProvingKey::new(arithmetization, &circuit, &srs).unwrap();
+        let pub_input = Fr::from(33u64);
+
+        assert!(fflonk_lookup_prover::<_, Keccak256TranscriptHash, _>(
+            &pk, &srs, &witness, &pub_input, rng
+        )
+        .is_err());
+
+        // a proof of the miss is well-formed, but doesn't verify
+        let proof =
+            prove::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &pub_input, rng, false)
+                .unwrap();
+        assert!(!fflonk_lookup_verifier::<_, Keccak256TranscriptHash>(
+            &pk.arithmetization,
+            &pk.gate.vk,
+            &proof,
+            &pub_input
+        )
+        .unwrap());
+    }
+}
@@ -0,0 +1,759 @@
+use crate::challenge::Challenges;
+use crate::inversion::Inversion;
+use crate::proof::{Evaluations, Polynomials, Proof};
+use crate::transcript::{bind_context, FiatShamirProfile, TranscriptHash};
+use crate::vk::{Omega, VerificationKey};
+use anyhow::{anyhow, ensure};
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{batch_inversion, Field, One, Zero};
+use ark_poly::univariate::{DenseOrSparsePolynomial, DensePolynomial};
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use ark_std::rand::RngCore;
+use ark_std::UniformRand;
+
+pub mod gate;
+pub mod lookup;
+pub mod opening;
+
+/// The powers of tau of a KZG setup: [1]_1, [τ]_1, ..., [τ^(d-1)]_1, [1]_2 and [τ]_2.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Srs<E: Pairing = Bn254> {
+    pub g1_powers: Vec<E::G1Affine>,
+    pub x2: E::G2Affine,
+    // [1]_2
+    pub g2: E::G2Affine,
+}
+
+impl<E: Pairing> Srs<E> {
+    /// A test SRS whose trapdoor is the given `tau`: never use it beyond the tests.
+    pub fn from_trapdoor(tau: E::ScalarField, size: usize) -> Self {
+        Self::from_trapdoor_with_generators(
+            tau,
+            size,
+            E::G1Affine::generator(),
+            E::G2Affine::generator(),
+        )
+    }
+
+    /// A test SRS over the generators `g1` and `g2` instead of the standard ones, as a private
+    /// ceremony may pick.
+    pub fn from_trapdoor_with_generators(
+        tau: E::ScalarField,
+        size: usize,
+        g1: E::G1Affine,
+        g2: E::G2Affine,
+    ) -> Self {
+        let mut power = E::G1::from(g1);
+        let mut g1_powers = Vec::with_capacity(size);
+        for _ in 0..size {
+            g1_powers.push(power);
+            power *= tau;
+        }
+
+        Self {
+            g1_powers: E::G1::normalize_batch(&g1_powers),
+            x2: (g2 * tau).into_affine(),
+            g2,
+        }
+    }
+
+    // The number of powers the prover needs for a 2^power domain: the quotient W2 has a degree
+    // of 9n + 16 and C2 of 9n + 17, with the blinding of the witness.
+    pub fn size_for(power: u8) -> usize {
+        9 * (1 << power) + 18
+    }
+
+    // The KZG commitment [p(τ)]_1.
+    pub fn commit(&self, p: &DensePolynomial<E::ScalarField>) -> anyhow::Result<E::G1Affine> {
+        ensure!(
+            p.coeffs.len() <= self.g1_powers.len(),
+            "The SRS has {} powers, but the polynomial has {} coefficients",
+            self.g1_powers.len(),
+            p.coeffs.len()
+        );
+        Ok(E::G1::msm_unchecked(&self.g1_powers[..p.coeffs.len()], &p.coeffs).into_affine())
+    }
+}
+
+/// A PLONK circuit of 2^power rows, laid out as snarkjs does: the gates
+///      ql·a + qr·b + qo·c + qm·a·b + qc + PI = 0
+/// where PI = -pub_input·L_1 (so the first row reads the public input), and the copy constraints
+/// as a permutation of the 3·2^power wires.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlonkCircuit<F: Field> {
+    pub power: u8,
+    // The shifts of the cosets of the wires b and c, 2 and 3 for snarkjs.
+    pub k1: F,
+    pub k2: F,
+    pub ql: Vec<F>,
+    pub qr: Vec<F>,
+    pub qo: Vec<F>,
+    pub qm: Vec<F>,
+    pub qc: Vec<F>,
+    // The wires a, b and c of the rows one after another: the wire `column·n + row` is
+    // copied to the wire `permutation[column·n + row]`.
+    pub permutation: Vec<usize>,
+}
+
+impl<F: Field> PlonkCircuit<F> {
+    pub fn n(&self) -> usize {
+        1 << self.power
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let n = self.n();
+        for (name, selector) in [
+            ("ql", &self.ql),
+            ("qr", &self.qr),
+            ("qo", &self.qo),
+            ("qm", &self.qm),
+            ("qc", &self.qc),
+        ] {
+            ensure!(
+                selector.len() == n,
+                "The selector {name} has {} rows instead of {n}",
+                selector.len()
+            );
+        }
+
+        ensure!(
+            self.permutation.len() == 3 * n,
+            "The permutation has {} wires instead of {}",
+            self.permutation.len(),
+            3 * n
+        );
+        let mut seen = vec![false; 3 * n];
+        for &wire in &self.permutation {
+            ensure!(
+                wire < 3 * n && !seen[wire],
+                "The permutation isn't one of the wires"
+            );
+            seen[wire] = true;
+        }
+        Ok(())
+    }
+
+    // The values of S1, S2 and S3 on the domain: the wire `column·n + row` is k_column·w^row,
+    // with k_0 = 1, k_1 = k1 and k_2 = k2.
+    pub fn sigma_evaluations(&self, w: F) -> [Vec<F>; 3] {
+        let n = self.n();
+        let mut powers = vec![F::one(); n];
+        for row in 1..n {
+            powers[row] = powers[row - 1] * w;
+        }
+        let id = |wire: usize| [F::one(), self.k1, self.k2][wire / n] * powers[wire % n];
+
+        [0, 1, 2].map(|column| {
+            self.permutation[column * n..(column + 1) * n]
+                .iter()
+                .map(|&wire| id(wire))
+                .collect()
+        })
+    }
+}
+
+/// The values of the wires a, b and c on the rows of a `PlonkCircuit`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlonkWitness<F: Field> {
+    pub a: Vec<F>,
+    pub b: Vec<F>,
+    pub c: Vec<F>,
+}
+
+/// What the prover needs of a circuit: the preprocessed polynomials in coefficients, along with
+/// the verification key.
+#[derive(Debug, Clone)]
+pub struct ProvingKey<E: Pairing = Bn254> {
+    pub vk: VerificationKey<E>,
+    pub ql: DensePolynomial<E::ScalarField>,
+    pub qr: DensePolynomial<E::ScalarField>,
+    pub qo: DensePolynomial<E::ScalarField>,
+    pub qm: DensePolynomial<E::ScalarField>,
+    pub qc: DensePolynomial<E::ScalarField>,
+    pub s1: DensePolynomial<E::ScalarField>,
+    pub s2: DensePolynomial<E::ScalarField>,
+    pub s3: DensePolynomial<E::ScalarField>,
+    // The values of S1, S2 and S3 on the domain.
+    pub sigma: [Vec<E::ScalarField>; 3],
+    // C0(X) = ql(X^8) + X·qr(X^8) + X^2·qo(X^8) + X^3·qm(X^8) + X^4·qc(X^8)
+    //          + X^5·S1(X^8) + X^6·S2(X^8) + X^7·S3(X^8)
+    pub c0: DensePolynomial<E::ScalarField>,
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    /// Preprocess the `circuit` over the `srs`, committing to C0.
+    pub fn new(circuit: &PlonkCircuit<E::ScalarField>, srs: &Srs<E>) -> anyhow::Result<Self> {
+        circuit.validate()?;
+        let domain = domain::<E::ScalarField>(circuit.power)?;
+
+        let sigma = circuit.sigma_evaluations(domain.group_gen);
+        let interpolate =
+            |evals: &[E::ScalarField]| DensePolynomial::from_coefficients_vec(domain.ifft(evals));
+        let [s1, s2, s3] = [0, 1, 2].map(|column| interpolate(&sigma[column]));
+        let (ql, qr, qo, qm, qc) = (
+            interpolate(&circuit.ql),
+            interpolate(&circuit.qr),
+            interpolate(&circuit.qo),
+            interpolate(&circuit.qm),
+            interpolate(&circuit.qc),
+        );
+        let c0 = interleave(&[&ql, &qr, &qo, &qm, &qc, &s1, &s2, &s3]);
+
+        let vk = VerificationKey {
+            power: circuit.power,
+            n: E::ScalarField::from(circuit.n() as u64),
+            k1: circuit.k1,
+            k2: circuit.k2,
+            x2: srs.x2,
+            c0: srs.commit(&c0)?,
+            // C0 has 8n coefficients: the SRS isn't empty
+            g1: srs.g1_powers[0],
+            g2: srs.g2,
+            omega: Omega::new(circuit.power)?,
+        };
+
+        Ok(Self {
+            vk,
+            ql,
+            qr,
+            qo,
+            qm,
+            qc,
+            s1,
+            s2,
+            s3,
+            sigma,
+            c0,
+        })
+    }
+}
+
+/// Prove that the `witness` satisfies the circuit of `pk` with the `pub_input`, under the
+/// transcript `T`, as the prover of snarkjs does: the wires and Z are blinded with `rng`.
+///
+/// Fails if the witness doesn't satisfy the gates or the copy constraints, or if the SRS is
+/// smaller than `Srs::size_for`.
+pub fn fflonk_prover<E: Pairing, T: TranscriptHash, R: RngCore>(
+    pk: &ProvingKey<E>,
+    srs: &Srs<E>,
+    witness: &PlonkWitness<E::ScalarField>,
+    pub_input: &E::ScalarField,
+    rng: &mut R,
+) -> anyhow::Result<Proof<E>> {
+    fflonk_prover_with_profile::<E, T, R>(
+        FiatShamirProfile::SnarkJS,
+        pk,
+        srs,
+        witness,
+        pub_input,
+        rng,
+    )
+}
+
+/// Same as `fflonk_prover`, but beta is squeezed under the given Fiat-Shamir `profile`, for
+/// `fflonk_verifier_with_profile`.
+pub fn fflonk_prover_with_profile<E: Pairing, T: TranscriptHash, R: RngCore>(
+    profile: FiatShamirProfile,
+    pk: &ProvingKey<E>,
+    srs: &Srs<E>,
+    witness: &PlonkWitness<E::ScalarField>,
+    pub_input: &E::ScalarField,
+    rng: &mut R,
+) -> anyhow::Result<Proof<E>> {
+    fflonk_prover_with_context::<E, T, R>(profile, &[], pk, srs, witness, pub_input, rng)
+}
+
+/// Same as `fflonk_prover_with_profile`, with the application `context` bound into beta, for
+/// `fflonk_verifier_with_context`. An empty context is the same as no context.
+pub fn fflonk_prover_with_context<E: Pairing, T: TranscriptHash, R: RngCore>(
+    profile: FiatShamirProfile,
+    context: &[u8],
+    pk: &ProvingKey<E>,
+    srs: &Srs<E>,
+    witness: &PlonkWitness<E::ScalarField>,
+    pub_input: &E::ScalarField,
+    rng: &mut R,
+) -> anyhow::Result<Proof<E>> {
+    let vk = &pk.vk;
+    let n = 1usize << vk.power;
+    let domain = domain::<E::ScalarField>(vk.power)?;
+    let w = domain.group_gen;
+    ensure!(
+        [&witness.a, &witness.b, &witness.c]
+            .iter()
+            .all(|wire| wire.len() == n),
+        "The witness doesn't have {n} rows"
+    );
+
+    let zh = DensePolynomial::from(domain.vanishing_polynomial());
+    let mut blinded = |evals: &[E::ScalarField], blinding: usize| {
+        let blinding = DensePolynomial::from_coefficients_vec(
+            (0..blinding).map(|_| E::ScalarField::rand(rng)).collect(),
+        );
+        DensePolynomial::from_coefficients_vec(domain.ifft(evals)) + &blinding * &zh
+    };
+    let divide_by_zh = |p: &DensePolynomial<E::ScalarField>, what: &str| {
+        let (quotient, remainder) = p.divide_by_vanishing_poly(domain).unwrap();
+        ensure!(
+            remainder.is_zero(),
+            "The witness doesn't satisfy the {what}"
+        );
+        Ok(quotient)
+    };
+
+    // L_1(X) and PI(X) = -pub_input·L_1(X)
+    let mut l1_evals = vec![E::ScalarField::zero(); n];
+    l1_evals[0] = E::ScalarField::one();
+    let l1 = DensePolynomial::from_coefficients_vec(domain.ifft(&l1_evals));
+    let pi = &l1 * -*pub_input;
+
+    // 1. C1(X) = A(X^4) + X·B(X^4) + X^2·C(X^4) + X^3·T0(X^4), with
+    //      T0 = (ql·A + qr·B + qo·C + qm·A·B + qc + PI) / Z_H
+    let a = blinded(&witness.a, 2);
+    let b = blinded(&witness.b, 2);
+    let c = blinded(&witness.c, 2);
+    let gate = &(&(&(&(&pk.ql * &a) + &(&pk.qr * &b)) + &(&pk.qo * &c)) + &(&(&pk.qm * &a) * &b))
+        + &(&pk.qc + &pi);
+    let t0 = divide_by_zh(&gate, "gates")?;
+    let c1 = interleave(&[&a, &b, &c, &t0]);
+    let c1_commitment = srs.commit(&c1)?;
+
+    let preimage =
+        Challenges::<E>::beta_preimage_with_profile::<T>(profile, vk, &c1_commitment, pub_input);
+    let beta = T::hash_to_fr(bind_context(context, preimage));
+    let gamma = Challenges::<E>::compute_gamma::<T>(&beta);
+
+    // 2. C2(X) = Z(X^3) + X·T1(X^3) + X^2·T2(X^3), with the copy constraints
+    //      T1 = (Z - 1)·L_1 / Z_H
+    //      T2 = (Z·(A + β·X + γ)(B + β·k1·X + γ)(C + β·k2·X + γ)
+    //           - Z(ω·X)·(A + β·S1 + γ)(B + β·S2 + γ)(C + β·S3 + γ)) / Z_H
+    let mut numerators = Vec::with_capacity(n);
+    let mut denominators = Vec::with_capacity(n);
+    let mut wi = E::ScalarField::one();
+    for i in 0..n {
+        let (a, b, c) = (witness.a[i], witness.b[i], witness.c[i]);
+        numerators.push(
+            (a + beta * wi + gamma)
+                * (b + beta * vk.k1 * wi + gamma)
+                * (c + beta * vk.k2 * wi + gamma),
+        );
+        denominators.push(
+            (a + beta * pk.sigma[0][i] + gamma)
+                * (b + beta * pk.sigma[1][i] + gamma)
+                * (c + beta * pk.sigma[2][i] + gamma),
+        );
+        wi *= w;
+    }
+    batch_inversion(&mut denominators);
+    let mut z_evals = vec![E::ScalarField::one()];
+    for i in 0..n {
+        z_evals.push(z_evals[i] * numerators[i] * denominators[i]);
+    }
+    ensure!(
+        z_evals.pop() == Some(E::ScalarField::one()),
+        "The witness doesn't satisfy the copy constraints"
+    );
+    let z = blinded(&z_evals, 3);
+
+    let one = DensePolynomial::from_coefficients_vec(vec![E::ScalarField::one()]);
+    let t1 = divide_by_zh(&(&(&z - &one) * &l1), "first row of Z")?;
+
+    let linear = |k: E::ScalarField| DensePolynomial::from_coefficients_vec(vec![gamma, beta * k]);
+    let shifted = |p: &DensePolynomial<E::ScalarField>, k: E::ScalarField| p + &linear(k);
+    let permuted = |p: &DensePolynomial<E::ScalarField>, s: &DensePolynomial<E::ScalarField>| {
+        &(p + &(s * beta)) + &DensePolynomial::from_coefficients_vec(vec![gamma])
+    };
+    let zw = scale_variable(&z, w);
+    let copy = &(&(&(&z * &shifted(&a, E::ScalarField::one())) * &shifted(&b, vk.k1))
+        * &shifted(&c, vk.k2))
+        - &(&(&(&zw * &permuted(&a, &pk.s1)) * &permuted(&b, &pk.s2)) * &permuted(&c, &pk.s3));
+    let t2 = divide_by_zh(&copy, "copy constraints")?;
+    let c2 = interleave(&[&z, &t1, &t2]);
+    let c2_commitment = srs.commit(&c2)?;
+
+    let xi_seed = Challenges::<E>::compute_xiseed::<T>(&gamma, c2_commitment);
+    let xi = xi_seed.pow([24]);
+    let xiw = xi * w;
+
+    // 3. The evaluations at xi and xi·ω
+    let mut evaluations = Evaluations {
+        ql: pk.ql.evaluate(&xi),
+        qr: pk.qr.evaluate(&xi),
+        qm: pk.qm.evaluate(&xi),
+        qo: pk.qo.evaluate(&xi),
+        qc: pk.qc.evaluate(&xi),
+        s1: pk.s1.evaluate(&xi),
+        s2: pk.s2.evaluate(&xi),
+        s3: pk.s3.evaluate(&xi),
+        a: a.evaluate(&xi),
+        b: b.evaluate(&xi),
+        c: c.evaluate(&xi),
+        z: z.evaluate(&xi),
+        zw: z.evaluate(&xiw),
+        t1w: t1.evaluate(&xiw),
+        t2w: t2.evaluate(&xiw),
+        inv: E::ScalarField::zero(),
+    };
+    let alpha = Challenges::<E>::compute_alpha::<T>(&xi_seed, &evaluations);
+
+    // 4. W1 = (C0 - r0)/Z_T0 + α·(C1 - r1)/Z_T1 + α^2·(C2 - r2)/Z_T2, where
+    //      Z_T0 = X^8 - xi, Z_T1 = X^4 - xi, Z_T2 = (X^3 - xi)(X^3 - xi·ω)
+    // and r0, r1, r2 interpolate C0, C1 and C2 on their roots.
+    let e = &evaluations;
+    let r0 = DensePolynomial::from_coefficients_vec(vec![
+        e.ql, e.qr, e.qo, e.qm, e.qc, e.s1, e.s2, e.s3,
+    ]);
+    let r1 = DensePolynomial::from_coefficients_vec(vec![e.a, e.b, e.c, t0.evaluate(&xi)]);
+    // r2 = p(X)·(X^3 - xi·ω)/(xi - xi·ω) + p'(X)·(X^3 - xi)/(xi·ω - xi), with p and p' of
+    // the values at xi and xi·ω of [Z, T1, T2]
+    let p = DensePolynomial::from_coefficients_vec(vec![e.z, t1.evaluate(&xi), t2.evaluate(&xi)]);
+    let pw = DensePolynomial::from_coefficients_vec(vec![e.zw, e.t1w, e.t2w]);
+    let den = (xi - xiw).inverse().unwrap();
+    let r2 = &(&(&p * &x_n_minus(3, xiw)) * den) - &(&(&pw * &x_n_minus(3, xi)) * den);
+
+    let zt0 = x_n_minus(8, xi);
+    let zt1 = x_n_minus(4, xi);
+    let zt2 = &x_n_minus(3, xi) * &x_n_minus(3, xiw);
+    let w1 = &(&divide_exact(&(&pk.c0 - &r0), &zt0)?
+        + &(&divide_exact(&(&c1 - &r1), &zt1)? * alpha))
+        + &(&divide_exact(&(&c2 - &r2), &zt2)? * alpha.square());
+    let w1_commitment = srs.commit(&w1)?;
+
+    let y = Challenges::<E>::compute_y::<T>(&alpha, &w1_commitment);
+
+    // 5. W2 = (C0 - r0(y) + q1·(C1 - r1(y)) + q2·(C2 - r2(y)) - Z_T0(y)·W1) / (X - y), with
+    //      q1 = α·Z_T0(y)/Z_T1(y) and q2 = α^2·Z_T0(y)/Z_T2(y), see `FEJ::compute_scalars`
+    let zt0_y = zt0.evaluate(&y);
+    let q1 = alpha * zt0_y * zt1.evaluate(&y).inverse().unwrap();
+    let q2 = alpha.square() * zt0_y * zt2.evaluate(&y).inverse().unwrap();
+    let constant = |f: E::ScalarField| DensePolynomial::from_coefficients_vec(vec![f]);
+    let l = &(&(&(&pk.c0 - &constant(r0.evaluate(&y)))
+        + &(&(&c1 - &constant(r1.evaluate(&y))) * q1))
+        + &(&(&c2 - &constant(r2.evaluate(&y))) * q2))
+        - &(&w1 * zt0_y);
+    let w2 = divide_exact(
+        &l,
+        &DensePolynomial::from_coefficients_vec(vec![-y, E::ScalarField::one()]),
+    )?;
+    let w2_commitment = srs.commit(&w2)?;
+
+    // The inverse of the accumulator of the denominators, see `Inversion::accumulator`
+    let challenges = Challenges::from_values(vk, beta, gamma, xi_seed, alpha, y);
+    let (den_h1_base, den_h2_base, li_s0, li_s1, li_s2, eval_l1_base) =
+        Inversion::compute_denominators(vk, &challenges);
+    let accumulator = Inversion::<E>::accumulator(
+        &den_h1_base,
+        &den_h2_base,
+        &challenges.zh,
+        &li_s0,
+        &li_s1,
+        &li_s2,
+        &eval_l1_base,
+    );
+    evaluations.inv = accumulator
+        .last()
+        .unwrap()
+        .inverse()
+        .ok_or_else(|| anyhow!("A denominator of the verifier is zero"))?;
+
+    Ok(Proof {
+        polynomials: Polynomials {
+            c1: c1_commitment.into_group(),
+            c2: c2_commitment.into_group(),
+            w1: w1_commitment.into_group(),
+            w2: w2_commitment.into_group(),
+        },
+        evaluations,
+    })
+}
+
+fn domain<F: ark_ff::FftField>(power: u8) -> anyhow::Result<Radix2EvaluationDomain<F>> {
+    Radix2EvaluationDomain::new(1 << power)
+        .ok_or_else(|| anyhow!("The domain 2^{power} is larger than the 2-adicity of the field"))
+}
+
+// p_0(X^k) + X·p_1(X^k) + ... + X^(k-1)·p_(k-1)(X^k) for the k polynomials p_i.
+fn interleave<F: Field>(polynomials: &[&DensePolynomial<F>]) -> DensePolynomial<F> {
+    let k = polynomials.len();
+    let len = polynomials
+        .iter()
+        .map(|p| p.coeffs.len())
+        .max()
+        .unwrap_or(0);
+    let mut coeffs = vec![F::zero(); k * len];
+    for (i, p) in polynomials.iter().enumerate() {
+        for (j, coeff) in p.coeffs.iter().enumerate() {
+            coeffs[k * j + i] = *coeff;
+        }
+    }
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+// p(s·X)
+fn scale_variable<F: Field>(p: &DensePolynomial<F>, s: F) -> DensePolynomial<F> {
+    let mut power = F::one();
+    let coeffs = p
+        .coeffs
+        .iter()
+        .map(|coeff| {
+            let scaled = *coeff * power;
+            power *= s;
+            scaled
+        })
+        .collect();
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+// X^k - s
+fn x_n_minus<F: Field>(k: usize, s: F) -> DensePolynomial<F> {
+    let mut coeffs = vec![F::zero(); k + 1];
+    coeffs[0] = -s;
+    coeffs[k] = F::one();
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+// p / d, which must have no remainder.
+fn divide_exact<F: Field>(
+    p: &DensePolynomial<F>,
+    d: &DensePolynomial<F>,
+) -> anyhow::Result<DensePolynomial<F>> {
+    let (quotient, remainder) = DenseOrSparsePolynomial::from(p)
+        .divide_with_q_and_r(&DenseOrSparsePolynomial::from(d))
+        .ok_or_else(|| anyhow!("Division by the zero polynomial"))?;
+    ensure!(remainder.is_zero(), "The opening doesn't divide");
+    Ok(quotient)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transcript::{Blake3TranscriptHash, Keccak256TranscriptHash};
+    use crate::verifier::{
+        fflonk_verifier, fflonk_verifier_with_context, fflonk_verifier_with_profile,
+    };
+    use ark_bn254::Fr;
+
+    // The Multiplier2 of `resources/circom`, with a = 3 and b = 11.
+    fn multiplier2() -> (PlonkCircuit<Fr>, PlonkWitness<Fr>) {
+        let circuit = crate::mock::multiplier2::<Fr>();
+        let witness = circuit.witness(&[1u64, 33, 3, 11].map(Fr::from)).unwrap();
+        (circuit.plonk_circuit().unwrap(), witness)
+    }
+
+    #[test]
+    fn test_fflonk_prover() {
+        let rng = &mut ark_std::test_rng();
+        let (circuit, witness) = multiplier2();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), Srs::<Bn254>::size_for(3));
+        let pk = ProvingKey::new(&circuit, &srs).unwrap();
+        let pub_input = Fr::from(33u64);
+
+        let proof =
+            fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &pub_input, rng)
+                .unwrap();
+        assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+            &pk.vk, &proof, &pub_input, false
+        ));
+        assert!(!fflonk_verifier::<Blake3TranscriptHash>(
+            &pk.vk, &proof, &pub_input, false
+        ));
+        assert!(!fflonk_verifier::<Keccak256TranscriptHash>(
+            &pk.vk,
+            &proof,
+            &Fr::from(34u64),
+            false
+        ));
+
+        let proof =
+            fflonk_prover::<_, Blake3TranscriptHash, _>(&pk, &srs, &witness, &pub_input, rng)
+                .unwrap();
+        assert!(fflonk_verifier::<Blake3TranscriptHash>(
+            &pk.vk, &proof, &pub_input, true
+        ));
+    }
+
+    #[test]
+    fn test_fflonk_prover_strong_profile() {
+        let rng = &mut ark_std::test_rng();
+        let (circuit, witness) = multiplier2();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), Srs::<Bn254>::size_for(3));
+        let pk = ProvingKey::new(&circuit, &srs).unwrap();
+        let pub_input = Fr::from(33u64);
+        let verify = |profile, vk: &VerificationKey<Bn254>, proof: &Proof<Bn254>| {
+            fflonk_verifier_with_profile::<Keccak256TranscriptHash>(
+                profile, vk, proof, &pub_input, false,
+            )
+        };
+
+        let proof = fflonk_prover_with_profile::<_, Keccak256TranscriptHash, _>(
+            FiatShamirProfile::Strong,
+            &pk,
+            &srs,
+            &witness,
+            &pub_input,
+            rng,
+        )
+        .unwrap();
+        assert!(verify(FiatShamirProfile::Strong, &pk.vk, &proof));
+        assert!(!verify(FiatShamirProfile::SnarkJS, &pk.vk, &proof));
+
+        // and the other way around
+        let proof =
+            fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &pub_input, rng)
+                .unwrap();
+        assert!(verify(FiatShamirProfile::SnarkJS, &pk.vk, &proof));
+        assert!(!verify(FiatShamirProfile::Strong, &pk.vk, &proof));
+    }
+
+    #[test]
+    fn test_fflonk_prover_with_context() {
+        let rng = &mut ark_std::test_rng();
+        let (circuit, witness) = multiplier2();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), Srs::<Bn254>::size_for(3));
+        let pk = ProvingKey::new(&circuit, &srs).unwrap();
+        let pub_input = Fr::from(33u64);
+
+        for profile in [FiatShamirProfile::SnarkJS, FiatShamirProfile::Strong] {
+            let verify = |context: &[u8], proof: &Proof<Bn254>| {
+                fflonk_verifier_with_context::<Keccak256TranscriptHash>(
+                    profile, context, &pk.vk, proof, &pub_input, false,
+                )
+            };
+
+            let proof = fflonk_prover_with_context::<_, Keccak256TranscriptHash, _>(
+                profile, b"chain-1", &pk, &srs, &witness, &pub_input, rng,
+            )
+            .unwrap();
+            assert!(verify(b"chain-1", &proof));
+            assert!(!verify(b"chain-2", &proof));
+            assert!(!verify(b"", &proof));
+
+            // a proof made without context does not verify with one
+            let proof = fflonk_prover_with_profile::<_, Keccak256TranscriptHash, _>(
+                profile, &pk, &srs, &witness, &pub_input, rng,
+            )
+            .unwrap();
+            assert!(verify(b"", &proof));
+            assert!(!verify(b"chain-1", &proof));
+        }
+    }
+
+    // A random circuit of 2^power rows: after the public input, every row is a random gate whose
+    // a is the c of the row before, and the wires of equal values are copied to each other.
+    fn random_circuit<R: RngCore>(
+        power: u8,
+        pub_input: Fr,
+        rng: &mut R,
+    ) -> (PlonkCircuit<Fr>, PlonkWitness<Fr>) {
+        let n = 1 << power;
+        let (mut circuit, mut witness) = multiplier2();
+        circuit.power = power;
+        for selector in [
+            &mut circuit.ql,
+            &mut circuit.qr,
+            &mut circuit.qo,
+            &mut circuit.qm,
+            &mut circuit.qc,
+        ] {
+            *selector = vec![Fr::zero(); n];
+        }
+        witness.a = vec![Fr::zero(); n];
+        witness.b = vec![Fr::zero(); n];
+        witness.c = vec![Fr::zero(); n];
+
+        circuit.ql[0] = Fr::one();
+        witness.a[0] = pub_input;
+        witness.c[0] = pub_input;
+        for row in 1..n {
+            let (a, b) = (witness.c[row - 1], Fr::rand(rng));
+            let [ql, qr, qm, qc] = [(); 4].map(|_| Fr::rand(rng));
+            let qo = -Fr::one();
+            (circuit.ql[row], circuit.qr[row], circuit.qo[row]) = (ql, qr, qo);
+            (circuit.qm[row], circuit.qc[row]) = (qm, qc);
+            witness.a[row] = a;
+            witness.b[row] = b;
+            witness.c[row] = ql * a + qr * b + qm * a * b + qc;
+        }
+
+        // a cycle through the wires of every value
+        let values = [&witness.a, &witness.b, &witness.c]
+            .map(|wire| wire.clone())
+            .concat();
+        circuit.permutation = (0..3 * n).collect();
+        let mut cycles = std::collections::HashMap::<Fr, Vec<usize>>::new();
+        for (wire, value) in values.iter().enumerate() {
+            cycles.entry(*value).or_default().push(wire);
+        }
+        for cycle in cycles.values() {
+            for (i, wire) in cycle.iter().enumerate() {
+                circuit.permutation[*wire] = cycle[(i + 1) % cycle.len()];
+            }
+        }
+        (circuit, witness)
+    }
+
+    #[test]
+    fn test_fflonk_prover_random_circuit() {
+        let rng = &mut ark_std::test_rng();
+        let power = 6;
+        let pub_input = Fr::rand(rng);
+        let (circuit, witness) = random_circuit(power, pub_input, rng);
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), Srs::<Bn254>::size_for(power));
+        let pk = ProvingKey::new(&circuit, &srs).unwrap();
+
+        let proof =
+            fflonk_prover::<_, Blake3TranscriptHash, _>(&pk, &srs, &witness, &pub_input, rng)
+                .unwrap();
+        assert!(fflonk_verifier::<Blake3TranscriptHash>(
+            &pk.vk, &proof, &pub_input, false
+        ));
+        assert!(!fflonk_verifier::<Blake3TranscriptHash>(
+            &pk.vk,
+            &proof,
+            &(pub_input + Fr::one()),
+            false
+        ));
+    }
+
+    #[test]
+    fn test_fflonk_prover_rejects_wrong_witness() {
+        let rng = &mut ark_std::test_rng();
+        let (circuit, witness) = multiplier2();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), Srs::<Bn254>::size_for(3));
+        let pk = ProvingKey::new(&circuit, &srs).unwrap();
+        let prove = |witness: &PlonkWitness<Fr>, pub_input: u64| {
+            fflonk_prover::<_, Keccak256TranscriptHash, _>(
+                &pk,
+                &srs,
+                witness,
+                &Fr::from(pub_input),
+                &mut ark_std::test_rng(),
+            )
+        };
+
+        // 3·11 ≠ 34
+        assert!(prove(&witness, 34).is_err());
+        // a_0 isn't c_1
+        let mut wrong = witness.clone();
+        wrong.a[0] = Fr::from(34u64);
+        wrong.c[1] = Fr::from(34u64);
+        wrong.b[1] = Fr::from(34u64) * Fr::from(3u64).inverse().unwrap();
+        assert!(prove(&wrong, 34).is_ok());
+        wrong.c[1] = Fr::from(33u64);
+        wrong.b[1] = Fr::from(11u64);
+        assert!(prove(&wrong, 34).is_err());
+
+        // the SRS is too small
+        let small = Srs::<Bn254>::from_trapdoor(Fr::from(5u64), 8 * 8);
+        assert!(fflonk_prover::<_, Keccak256TranscriptHash, _>(
+            &pk,
+            &small,
+            &witness,
+            &Fr::from(33u64),
+            &mut ark_std::test_rng()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use super::{divide_exact, Srs};
+use crate::challenge::Challenges;
+use crate::opening::{compute_opening_roots, OpeningChallenges, OpeningProof, OpeningSetConfig};
+use crate::transcript::TranscriptHash;
+use crate::vk::VerificationKey;
+use anyhow::{anyhow, ensure};
+use ark_ec::pairing::Pairing;
+use ark_ff::{batch_inversion, FftField, Field, One, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, Polynomial};
+
+/// Open the polynomials [C0, C1, ...] committed in `opening` and laid out by `config`, under the
+/// transcript of `OpeningChallenges::compute` from `seed`, setting W1 and W2 of `opening`:
+///      W1 = ∑ alpha^i·(C_i - r_i)/Z_i
+///      W2 = (∑ alpha^i·Z_0(y)/Z_i(y)·(C_i - r_i(y)) - Z_0(y)·W1) / (X - y)
+/// where Z_i vanishes on the opening set of C_i and r_i interpolates C_i on it, so that
+/// `opening::verify_openings` holds, see `opening::compute_opening_fej`.
+///
+/// The evaluations of `opening` are the ones the verifier computes: the proof only verifies if
+/// they're the ones of the polynomials.
+pub fn prove_openings<E: Pairing, T: TranscriptHash>(
+    config: &OpeningSetConfig,
+    srs: &Srs<E>,
+    vk: &VerificationKey<E>,
+    polynomials: &[&DensePolynomial<E::ScalarField>],
+    opening: &mut OpeningProof<E>,
+    seed: &E::ScalarField,
+) -> anyhow::Result<()> {
+    opening.check_layout(config)?;
+    ensure!(
+        polynomials.len() == config.commitments.len(),
+        "Expect {} polynomials, got {}",
+        config.commitments.len(),
+        polynomials.len()
+    );
+    let xi_seed = OpeningChallenges::compute_xi_seed::<T>(vk, &opening.commitments, seed);
+    let alpha = OpeningChallenges::<E>::compute_alpha::<T>(&xi_seed, &opening.evaluations);
+    let roots = compute_opening_roots(config, &xi_seed, &vk.omega.w)?;
+
+    let mut zs = Vec::with_capacity(roots.len());
+    let mut rs = Vec::with_capacity(roots.len());
+    let mut w1 = DensePolynomial::zero();
+    let mut alpha_i = E::ScalarField::one();
+    for (polynomial, sets) in polynomials.iter().zip(&roots) {
+        let points = sets.concat();
+        let values = points
+            .iter()
+            .map(|x| polynomial.evaluate(x))
+            .collect::<Vec<_>>();
+        let z = points.iter().fold(constant(E::ScalarField::one()), |z, x| {
+            &z * &DensePolynomial::from_coefficients_vec(vec![-*x, E::ScalarField::one()])
+        });
+        let r = interpolate(&points, &values)?;
+
+        w1 = &w1 + &(&divide_exact(&(*polynomial - &r), &z)? * alpha_i);
+        zs.push(z);
+        rs.push(r);
+        alpha_i *= alpha;
+    }
+    let w1_commitment = srs.commit(&w1)?;
+    let y = Challenges::<E>::compute_y::<T>(&alpha, &w1_commitment);
+
+    let z0_y = zs[0].evaluate(&y);
+    let mut l = &w1 * -z0_y;
+    let mut alpha_i = E::ScalarField::one();
+    for ((polynomial, r), z) in polynomials.iter().zip(&rs).zip(&zs) {
+        let z_inv = z
+            .evaluate(&y)
+            .inverse()
+            .ok_or_else(|| anyhow!("y is in an opening set"))?;
+        l = &l + &(&(*polynomial - &constant(r.evaluate(&y))) * (alpha_i * z0_y * z_inv));
+        alpha_i *= alpha;
+    }
+    let w2 = divide_exact(
+        &l,
+        &DensePolynomial::from_coefficients_vec(vec![-y, E::ScalarField::one()]),
+    )?;
+
+    opening.w1 = w1_commitment;
+    opening.w2 = srs.commit(&w2)?;
+    Ok(())
+}
+
+fn constant<F: Field>(f: F) -> DensePolynomial<F> {
+    DensePolynomial::from_coefficients_vec(vec![f])
+}
+
+// The polynomial interpolating (points[k], values[k]):
+//      r(X) = ∑_k values[k]·∏_{m≠k} (X - points[m])/(points[k] - points[m])
+fn interpolate<F: FftField>(points: &[F], values: &[F]) -> anyhow::Result<DensePolynomial<F>> {
+    let others = |k: usize| (0..points.len()).filter(move |m| *m != k);
+
+    let mut dens = (0..points.len())
+        .map(|k| others(k).map(|m| points[k] - points[m]).product::<F>())
+        .collect::<Vec<_>>();
+    ensure!(
+        dens.iter().all(|den| !den.is_zero()),
+        "The opening sets have a duplicated point"
+    );
+    batch_inversion(&mut dens);
+
+    Ok((0..points.len())
+        .map(|k| {
+            let basis = others(k).fold(constant(dens[k]), |basis, m| {
+                &basis * &DensePolynomial::from_coefficients_vec(vec![-points[m], F::one()])
+            });
+            &basis * values[k]
+        })
+        .fold(DensePolynomial::zero(), |r, term| &r + &term))
+}
@@ -0,0 +1,267 @@
+use crate::circom::{read_prime, read_sections, section};
+use crate::curve::SWPoint;
+use crate::prover::Srs;
+use crate::vk::{SnarkJSVK, VerificationKey};
+use crate::zkey::read_point;
+use anyhow::ensure;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One};
+use ark_std::rand::RngCore;
+use ark_std::UniformRand;
+use std::path::Path;
+
+// The sections of a `.ptau` of snarkjs, see `src/powersoftau_new.js` of snarkjs: the sections
+// after the powers of tau, alphaTauG1, betaTauG1, betaG2 and the contributions, aren't read.
+const PTAU_HEADER: u32 = 1;
+const PTAU_TAU_G1: u32 = 2;
+const PTAU_TAU_G2: u32 = 3;
+
+/// The powers of tau of a `.ptau` of snarkjs, the SRS of a ceremony: [τ^i]_1 for i < 2·2^power - 1,
+/// and [1]_2 and [τ]_2 of its G2 powers.
+///
+/// The points are in the Montgomery form of ffjavascript, as in a `.zkey`. The generators
+/// [1]_1 and [1]_2 are the ones of the file, which a private ceremony may pick.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Ptau<E: Pairing = Bn254> {
+    pub power: u32,
+    pub ceremony_power: u32,
+    // The first G1 powers of the file, as many as read
+    pub tau_g1: Vec<E::G1Affine>,
+    pub tau_g2: [E::G2Affine; 2],
+}
+
+impl<E: Pairing> Ptau<E>
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+    E::G2: SWPoint,
+{
+    /// Read the `.ptau` with its first `n_g1` G1 powers, eg: `Srs::size_for(power)` to prove.
+    pub fn load<P: AsRef<Path>>(ptau_path: P, n_g1: usize) -> anyhow::Result<Self> {
+        Self::from_bytes(&std::fs::read(ptau_path)?, n_g1)
+    }
+
+    pub fn from_bytes(bytes: &[u8], n_g1: usize) -> anyhow::Result<Self> {
+        let sections = read_sections(bytes, b"ptau")?;
+        let mut header = section(&sections, PTAU_HEADER)?;
+        read_prime::<E::BaseField>(&mut header)?;
+        let power = header.u32()?;
+        let ceremony_power = header.u32()?;
+        ensure!(power < 32, "The power {power} of the file is too large");
+        let max_g1 = (2usize << power) - 1;
+        ensure!(
+            (1..=max_g1).contains(&n_g1),
+            "The file has {max_g1} G1 powers, not {n_g1}"
+        );
+
+        let mut g1 = section(&sections, PTAU_TAU_G1)?;
+        let tau_g1 = (0..n_g1)
+            .map(|_| Ok(read_point::<E::G1>(&mut g1)?.into_affine()))
+            .collect::<anyhow::Result<_>>()?;
+        let mut g2 = section(&sections, PTAU_TAU_G2)?;
+        let [g2, x2] = [read_point::<E::G2>(&mut g2)?, read_point::<E::G2>(&mut g2)?];
+
+        Ok(Self {
+            power,
+            ceremony_power,
+            tau_g1,
+            tau_g2: [g2.into_affine(), x2.into_affine()],
+        })
+    }
+}
+
+impl<E: Pairing> Ptau<E> {
+    /// Check that the G1 powers read are the powers of the τ of [τ]_2: for a random ρ,
+    ///      e(Σ ρ^i·[τ^(i+1)]_1, [1]_2) = e(Σ ρ^i·[τ^i]_1, [τ]_2)
+    pub fn check<R: RngCore>(&self, rng: &mut R) -> anyhow::Result<()> {
+        let [g2, x2] = self.tau_g2;
+        ensure!(
+            !self.tau_g1[0].is_zero() && !g2.is_zero(),
+            "[1]_1 or [1]_2 is the point at infinity"
+        );
+        let rho = E::ScalarField::rand(rng);
+        let len = self.tau_g1.len() - 1;
+        let rhos = std::iter::successors(Some(E::ScalarField::ONE), |r| Some(*r * rho))
+            .take(len)
+            .collect::<Vec<_>>();
+        let lower = E::G1::msm_unchecked(&self.tau_g1[..len], &rhos);
+        let upper = E::G1::msm_unchecked(&self.tau_g1[1..], &rhos);
+        ensure!(
+            E::multi_pairing([upper, -lower], [g2, x2]).0.is_one(),
+            "The G1 powers aren't the powers of the τ of [τ]_2"
+        );
+        Ok(())
+    }
+
+    /// Check that the generators and X_2 of the verification key are [1]_1, [1]_2 and [τ]_2 of the
+    /// ceremony.
+    pub fn check_vk(&self, vk: &VerificationKey<E>) -> anyhow::Result<()> {
+        let [g2, x2] = self.tau_g2;
+        ensure!(
+            vk.g1 == self.tau_g1[0],
+            "[1]_1 isn't the one of the ceremony"
+        );
+        ensure!(vk.g2 == g2, "[1]_2 isn't the one of the ceremony");
+        ensure!(vk.x2 == x2, "X_2 isn't [τ]_2 of the ceremony");
+        Ok(())
+    }
+
+    /// Check that the SRS, eg: of a `.zkey`, starts with the powers of tau of the ceremony.
+    pub fn check_srs(&self, srs: &Srs<E>) -> anyhow::Result<()> {
+        let len = srs.g1_powers.len().min(self.tau_g1.len());
+        ensure!(
+            srs.g1_powers[..len] == self.tau_g1[..len],
+            "The G1 powers aren't the ones of the ceremony"
+        );
+        ensure!(
+            [srs.g2, srs.x2] == self.tau_g2,
+            "[1]_2 or [τ]_2 isn't the one of the ceremony"
+        );
+        Ok(())
+    }
+
+    /// The verification key of the JSON of snarkjs over the generators of the ceremony, whose
+    /// X_2 must be its [τ]_2.
+    pub fn verification_key(&self, snarkjs: SnarkJSVK<E>) -> anyhow::Result<VerificationKey<E>> {
+        let vk = VerificationKey {
+            g1: self.tau_g1[0],
+            g2: self.tau_g2[0],
+            ..VerificationKey::from(snarkjs)
+        };
+        self.check_vk(&vk)?;
+        Ok(vk)
+    }
+
+    /// The SRS of the G1 powers read, to set up and prove with.
+    pub fn srs(&self) -> Srs<E> {
+        Srs {
+            g1_powers: self.tau_g1.clone(),
+            x2: self.tau_g2[1],
+            g2: self.tau_g2[0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::multiplier2;
+    use crate::prover::fflonk_prover;
+    use crate::setup::fflonk_setup;
+    use crate::transcript::Keccak256TranscriptHash;
+    use crate::verifier::fflonk_verifier;
+    use crate::zkey::test::{binfile, point, prime};
+    use ark_bn254::{Fq, Fr, G1Affine, G2Affine};
+
+    // The `.ptau` of a ceremony of the given power with the trapdoor tau, over the generators
+    // g1 and g2.
+    fn ptau_bytes(power: u32, tau: Fr, g1: G1Affine, g2: G2Affine) -> Vec<u8> {
+        let mut header = prime::<Fq>();
+        header.extend(power.to_le_bytes());
+        header.extend(power.to_le_bytes());
+        let n = 1usize << power;
+        let srs = Srs::<Bn254>::from_trapdoor_with_generators(tau, 2 * n - 1, g1, g2);
+        let tau_g1 = srs.g1_powers.iter().flat_map(point).collect();
+        let tau_g2 = std::iter::successors(Some(g2), |p| Some((*p * tau).into_affine()))
+            .take(n)
+            .flat_map(|p| point(&p))
+            .collect();
+        binfile(
+            b"ptau",
+            &[
+                (PTAU_HEADER, header),
+                (PTAU_TAU_G1, tau_g1),
+                (PTAU_TAU_G2, tau_g2),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_ptau_custom_generators() {
+        let rng = &mut ark_std::test_rng();
+        let tau = Fr::rand(rng);
+        let g1 = (G1Affine::generator() * Fr::rand(rng)).into_affine();
+        let g2 = (G2Affine::generator() * Fr::rand(rng)).into_affine();
+        let bytes = ptau_bytes(6, tau, g1, g2);
+
+        let ptau = Ptau::<Bn254>::from_bytes(&bytes, Srs::<Bn254>::size_for(3)).unwrap();
+        assert_eq!((ptau.power, ptau.ceremony_power), (6, 6));
+        ptau.check(rng).unwrap();
+        let srs = ptau.srs();
+        assert_eq!(
+            srs,
+            Srs::from_trapdoor_with_generators(tau, Srs::<Bn254>::size_for(3), g1, g2)
+        );
+        ptau.check_srs(&srs).unwrap();
+
+        let pk = fflonk_setup(&multiplier2(), &srs).unwrap();
+        assert_eq!((pk.vk.g1, pk.vk.g2), (g1, g2));
+        ptau.check_vk(&pk.vk).unwrap();
+        // the JSON of snarkjs doesn't have the generators, the ceremony does
+        let vk = ptau.verification_key(SnarkJSVK::from(&pk.vk)).unwrap();
+        assert_eq!(vk, pk.vk);
+
+        // c = a·b on the signals [1, c, a, b]
+        let (a, b) = (Fr::from(3u64), Fr::from(11u64));
+        let witness = multiplier2()
+            .witness(&[Fr::from(1u64), a * b, a, b])
+            .unwrap();
+        let proof =
+            fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &(a * b), rng)
+                .unwrap();
+        assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+            &vk,
+            &proof,
+            &(a * b),
+            false
+        ));
+        // the standard generators, as `From<SnarkJSVK>` assumes
+        let standard = VerificationKey::from(SnarkJSVK::from(&pk.vk));
+        assert!(!fflonk_verifier::<Keccak256TranscriptHash>(
+            &standard,
+            &proof,
+            &(a * b),
+            false
+        ));
+        assert!(ptau.check_vk(&standard).is_err());
+
+        // X_2 of another ceremony
+        let other = VerificationKey {
+            x2: (g2 * Fr::rand(rng)).into_affine(),
+            ..vk.clone()
+        };
+        assert!(ptau.check_vk(&other).is_err());
+        assert!(ptau.verification_key(SnarkJSVK::from(&other)).is_err());
+        let other = Srs {
+            x2: other.x2,
+            ..srs
+        };
+        assert!(ptau.check_srs(&other).is_err());
+    }
+
+    #[test]
+    fn test_ptau_invalid_files() {
+        let rng = &mut ark_std::test_rng();
+        let bytes = ptau_bytes(
+            3,
+            Fr::rand(rng),
+            G1Affine::generator(),
+            G2Affine::generator(),
+        );
+        let ptau = Ptau::<Bn254>::from_bytes(&bytes, 15).unwrap();
+        ptau.check(rng).unwrap();
+        // more powers than the file has, truncated, and not a `.ptau`
+        assert!(Ptau::<Bn254>::from_bytes(&bytes, 16).is_err());
+        assert!(Ptau::<Bn254>::from_bytes(&bytes[..bytes.len() - 1], 15).is_err());
+        assert!(Ptau::<Bn254>::from_bytes(&bytes[1..], 15).is_err());
+
+        // a power of another trapdoor
+        let mut wrong = ptau.clone();
+        wrong.tau_g1[7] = (G1Affine::generator() * Fr::rand(rng)).into_affine();
+        assert!(wrong.check(rng).is_err());
+        let mut wrong = ptau;
+        wrong.tau_g2[1] = (G2Affine::generator() * Fr::rand(rng)).into_affine();
+        assert!(wrong.check(rng).is_err());
+    }
+}
@@ -0,0 +1,469 @@
+use crate::r1cs::{FqVar, FrVar};
+use ark_bn254::{Fq, Fr, G1Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{Field, Zero};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{Namespace, SynthesisError};
+use std::borrow::Borrow;
+
+// The window of `G1Var::msm`.
+const WINDOW_BITS: usize = 4;
+
+/// A BN254 G1 point in affine coordinates over the non-native base field Fq,
+/// the point at infinity being flagged and (0, 0).
+///
+/// As G1 has a cofactor of 1, the points on the curve are the ones of the prime order subgroup.
+#[derive(Debug, Clone)]
+pub struct G1Var {
+    pub x: FqVar,
+    pub y: FqVar,
+    pub infinity: Boolean<Fr>,
+}
+
+impl G1Var {
+    pub fn zero() -> Self {
+        Self {
+            x: FqVar::zero(),
+            y: FqVar::zero(),
+            infinity: Boolean::TRUE,
+        }
+    }
+
+    pub fn constant(p: G1Affine) -> Self {
+        let (x, y) = p.xy().map(|(x, y)| (*x, *y)).unwrap_or_default();
+        Self {
+            x: FqVar::constant(x),
+            y: FqVar::constant(y),
+            infinity: Boolean::constant(p.is_zero()),
+        }
+    }
+
+    pub fn value(&self) -> Result<G1Affine, SynthesisError> {
+        if self.infinity.value()? {
+            return Ok(G1Affine::zero());
+        }
+        Ok(G1Affine::new_unchecked(self.x.value()?, self.y.value()?))
+    }
+
+    pub fn negate(&self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: self.x.clone(),
+            y: self.y.negate()?,
+            infinity: self.infinity.clone(),
+        })
+    }
+
+    pub fn select(cond: &Boolean<Fr>, a: &Self, b: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: cond.select(&a.x, &b.x)?,
+            y: cond.select(&a.y, &b.y)?,
+            infinity: cond.select(&a.infinity, &b.infinity)?,
+        })
+    }
+
+    // The complete addition: P + Q with either being the point at infinity, P = Q or P = -Q.
+    // The slope is a hint of the prover, checked against the chord when x_P ≠ x_Q
+    // and against the tangent when P = Q.
+    pub fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let x_eq = self.x.is_eq(&other.x)?;
+        let y_eq = self.y.is_eq(&other.y)?;
+        let finite = self.infinity.or(&other.infinity)?.not();
+        let lambda = self.slope_hint(other)?;
+
+        // (x_Q - x_P)·λ = y_Q - y_P
+        let chord = x_eq.not().and(&finite)?;
+        ((&other.x - &self.x) * &lambda)
+            .conditional_enforce_equal(&(&other.y - &self.y), &chord)?;
+        // 2·y_P·λ = 3·x_P^2
+        let tangent = x_eq.and(&y_eq)?.and(&finite)?;
+        (self.y.double()? * &lambda)
+            .conditional_enforce_equal(&(self.x.square()? * Fq::from(3u64)), &tangent)?;
+
+        // P + (-P) = 0
+        let cancel = x_eq.and(&y_eq.not())?;
+        let sum = Self::select(&cancel, &Self::zero(), &self.with_slope(other, &lambda)?)?;
+        self.or_other(other, &sum)
+    }
+
+    // 2·P, with the slope of the tangent as a hint. As G1 has a prime order, y_P ≠ 0
+    // unless P is the point at infinity.
+    pub fn double(&self) -> Result<Self, SynthesisError> {
+        let lambda = self.slope_hint(self)?;
+
+        // 2·y_P·λ = 3·x_P^2, which is 0 = 0 for the point at infinity
+        (self.y.double()? * &lambda).enforce_equal(&(self.x.square()? * Fq::from(3u64)))?;
+
+        Self::select(&self.infinity, self, &self.with_slope(self, &lambda)?)
+    }
+
+    // P + Q for P ≠ ±Q, with the slope of the chord as a hint, either being possibly the point
+    // at infinity. It isn't satisfiable for P = ±Q, which the honest callers rule out.
+    pub fn add_unchecked(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let finite = self.infinity.or(&other.infinity)?.not();
+        let lambda = self.slope_hint(other)?;
+
+        // x_P ≠ x_Q and (x_Q - x_P)·λ = y_Q - y_P
+        self.x.conditional_enforce_not_equal(&other.x, &finite)?;
+        ((&other.x - &self.x) * &lambda)
+            .conditional_enforce_equal(&(&other.y - &self.y), &finite)?;
+
+        self.or_other(other, &self.with_slope(other, &lambda)?)
+    }
+
+    // s·P by double-and-add, with the bits of s < r in little-endian order.
+    //
+    // The accumulator is 2k·P before the addition of P, with 2k < r: it's never ±P.
+    pub fn scalar_mul_le(&self, bits: &[Boolean<Fr>]) -> Result<Self, SynthesisError> {
+        let mut acc = Self::zero();
+        for bit in bits.iter().rev() {
+            acc = acc.double()?;
+            let sum = acc.add_unchecked(self)?;
+            acc = Self::select(bit, &sum, &acc)?;
+        }
+        Ok(acc)
+    }
+
+    // The slope λ of P and Q, allocated as a witness unless both are constants.
+    fn slope_hint(&self, other: &Self) -> Result<FqVar, SynthesisError> {
+        let lambda = || Ok(slope(self.value()?, other.value()?));
+        let cs = self
+            .x
+            .cs()
+            .or(other.x.cs())
+            .or(self.infinity.cs())
+            .or(other.infinity.cs());
+        if cs.is_none() {
+            Ok(FqVar::constant(lambda()?))
+        } else {
+            FqVar::new_witness(cs, lambda)
+        }
+    }
+
+    // (λ^2 - x_P - x_Q, λ·(x_P - x_R) - y_P), allocated as witnesses unless all are constants:
+    // the coordinates stay in normal form however many operations they go through.
+    fn with_slope(&self, other: &Self, lambda: &FqVar) -> Result<Self, SynthesisError> {
+        let x = || Ok(lambda.value()?.square() - self.x.value()? - other.x.value()?);
+        let y = |x: Fq| Ok(lambda.value()? * (self.x.value()? - x) - self.y.value()?);
+        let cs = self.x.cs().or(other.x.cs()).or(lambda.cs());
+        if cs.is_none() {
+            let x = x()?;
+            return Ok(Self {
+                x: FqVar::constant(x),
+                y: FqVar::constant(y(x)?),
+                infinity: Boolean::FALSE,
+            });
+        }
+        let x_value = x();
+        let x = FqVar::new_witness(cs.clone(), || x_value)?;
+        let y = FqVar::new_witness(cs, || y(x_value?))?;
+        // λ^2 = x_R + x_P + x_Q and λ·(x_P - x_R) = y_R + y_P
+        lambda.square()?.enforce_equal(&(&x + &self.x + &other.x))?;
+        (lambda * (&self.x - &x)).enforce_equal(&(&y + &self.y))?;
+        Ok(Self {
+            x,
+            y,
+            infinity: Boolean::FALSE,
+        })
+    }
+
+    // Q if P is the point at infinity, P if Q is, or else the sum.
+    fn or_other(&self, other: &Self, sum: &Self) -> Result<Self, SynthesisError> {
+        let sum = Self::select(&other.infinity, self, sum)?;
+        Self::select(&self.infinity, other, &sum)
+    }
+
+    // s·P, with the canonical bits of s.
+    pub fn scalar_mul(&self, s: &FrVar) -> Result<Self, SynthesisError> {
+        Self::msm(&[self.clone()], &[s.clone()])
+    }
+
+    // ∑ s_i·P_i, by a windowed double-and-add sharing the doublings (Straus): for every window
+    // of WINDOW_BITS bits, from the most significant one, the accumulator is doubled
+    // WINDOW_BITS times, then the multiples w_i·P_i of the window are added from tables.
+    //
+    // The additions being unchecked, it's unsatisfiable, rather than unsound, if the accumulator
+    // meets ±w_i·P_i, which is negligible unless the points depend on the scalars.
+    pub fn msm(points: &[Self], scalars: &[FrVar]) -> Result<Self, SynthesisError> {
+        let bits = scalars
+            .iter()
+            .map(|s| s.to_bits_le())
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::msm_le(points, &bits)
+    }
+
+    // ∑ s_i·P_i, with the bits of every s_i < r in little-endian order.
+    //
+    // Equal points with equal windows meet in the accumulator: with P_i = P_j and w_i = w_j in
+    // the most significant window, the accumulator is w_i·P_i when w_j·P_j is added. The points
+    // of A1 being the ones of the proof, a prover sending C1 = C2 or W1 = W2 can thus make the
+    // circuit unsatisfiable, which the verifier of the circuit sees as an invalid proof, not as
+    // an accepted one.
+    pub fn msm_le(points: &[Self], bits: &[Vec<Boolean<Fr>>]) -> Result<Self, SynthesisError> {
+        let tables = points
+            .iter()
+            .map(Self::window_table)
+            .collect::<Result<Vec<_>, _>>()?;
+        let windows = bits
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .div_ceil(WINDOW_BITS);
+
+        let mut acc = Self::zero();
+        for window in (0..windows).rev() {
+            for _ in 0..WINDOW_BITS {
+                acc = acc.double()?;
+            }
+            for (table, bits) in tables.iter().zip(bits) {
+                let window_bits = (0..WINDOW_BITS)
+                    .map(|i| {
+                        bits.get(window * WINDOW_BITS + i)
+                            .cloned()
+                            .unwrap_or(Boolean::FALSE)
+                    })
+                    .collect::<Vec<_>>();
+                acc = acc.add_unchecked(&Self::lookup(table, &window_bits)?)?;
+            }
+        }
+        Ok(acc)
+    }
+
+    // [0, P, 2·P, ..., (2^WINDOW_BITS - 1)·P]
+    fn window_table(&self) -> Result<Vec<Self>, SynthesisError> {
+        let mut table = vec![Self::zero(), self.clone(), self.double()?];
+        while table.len() < 1 << WINDOW_BITS {
+            table.push(table.last().unwrap().add_unchecked(self)?);
+        }
+        Ok(table)
+    }
+
+    // table[bits], with the bits in little-endian order.
+    fn lookup(table: &[Self], bits: &[Boolean<Fr>]) -> Result<Self, SynthesisError> {
+        let mut table = table.to_vec();
+        for bit in bits {
+            table = table
+                .chunks(2)
+                .map(|pair| Self::select(bit, &pair[1], &pair[0]))
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+        Ok(table.remove(0))
+    }
+
+    pub fn enforce_equal(&self, other: &Self) -> Result<(), SynthesisError> {
+        self.x.enforce_equal(&other.x)?;
+        self.y.enforce_equal(&other.y)?;
+        self.infinity.enforce_equal(&other.infinity)
+    }
+
+    // x and y as native field elements, each split into its high and low 128 bits:
+    //      [x_hi, x_lo, y_hi, y_lo]
+    // which is how `PoseidonTranscriptHash` absorbs the 64 bytes of `point_to_bytes_be`.
+    pub fn to_limbs(&self) -> Result<Vec<FrVar>, SynthesisError> {
+        let mut limbs = fq_to_limbs(&self.x)?;
+        limbs.extend(fq_to_limbs(&self.y)?);
+        Ok(limbs)
+    }
+}
+
+impl AllocVar<G1Affine, Fr> for G1Var {
+    fn new_variable<T: Borrow<G1Affine>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let p = f().map(|p| *p.borrow());
+        let xy = p.map(|p| p.xy().map(|(x, y)| (*x, *y)).unwrap_or_default());
+
+        let infinity = Boolean::new_variable(cs.clone(), || Ok(p?.is_zero()), mode)?;
+        let x = FqVar::new_variable(cs.clone(), || Ok(xy?.0), mode)?;
+        let y = FqVar::new_variable(cs, || Ok(xy?.1), mode)?;
+
+        // y^2 = x^3 + 3, and (0, 0) for the point at infinity
+        let finite = infinity.not();
+        y.square()?
+            .conditional_enforce_equal(&(x.square()? * &x + Fq::from(3u64)), &finite)?;
+        x.conditional_enforce_equal(&FqVar::zero(), &infinity)?;
+        y.conditional_enforce_equal(&FqVar::zero(), &infinity)?;
+
+        Ok(Self { x, y, infinity })
+    }
+}
+
+// The slope of the chord through P and Q, or of the tangent at P = Q, or 0 if there's none.
+fn slope(p: G1Affine, q: G1Affine) -> Fq {
+    match (p.xy(), q.xy()) {
+        (Some((x1, y1)), Some((x2, y2))) if x1 != x2 => (*y2 - y1) * (*x2 - x1).inverse().unwrap(),
+        (Some((x1, y1)), Some((_, y2))) if y1 == y2 && !y1.is_zero() => {
+            x1.square() * Fq::from(3u64) * y1.double().inverse().unwrap()
+        }
+        _ => Fq::zero(),
+    }
+}
+
+// The canonical bits of f, big-endian 128-bit limbs: [f_hi, f_lo].
+fn fq_to_limbs(f: &FqVar) -> Result<Vec<FrVar>, SynthesisError> {
+    bits_to_limbs(&f.to_bits_le()?)
+}
+
+pub(crate) fn bits_to_limbs(bits: &[Boolean<Fr>]) -> Result<Vec<FrVar>, SynthesisError> {
+    let (lo, hi) = bits.split_at(128);
+    Ok(vec![
+        Boolean::le_bits_to_fp_var(hi)?,
+        Boolean::le_bits_to_fp_var(lo)?,
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bn254::G1Projective;
+    use ark_ec::CurveGroup;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_g1_add() {
+        let rng = &mut ark_std::test_rng();
+        let (p, q) = (G1Affine::rand(rng), G1Affine::rand(rng));
+        let zero = G1Affine::zero();
+
+        let cases = [
+            (p, q, (p + q).into_affine()),
+            (p, p, (p + p).into_affine()),
+            (p, -p, zero),
+            (p, zero, p),
+            (zero, q, q),
+            (zero, zero, zero),
+        ];
+        for (a, b, expected) in cases {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a = G1Var::new_witness(cs.clone(), || Ok(a)).unwrap();
+            let b = G1Var::new_witness(cs.clone(), || Ok(b)).unwrap();
+            let sum = a.add(&b).unwrap();
+            assert_eq!(sum.value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_g1_double_and_add_unchecked() {
+        let rng = &mut ark_std::test_rng();
+        let (p, q) = (G1Affine::rand(rng), G1Affine::rand(rng));
+        let zero = G1Affine::zero();
+
+        for p in [p, zero] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let a = G1Var::new_witness(cs.clone(), || Ok(p)).unwrap();
+            assert_eq!(a.double().unwrap().value().unwrap(), (p + p).into_affine());
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        for (a, b) in [(p, q), (p, zero), (zero, q), (zero, zero)] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let sum = G1Var::new_witness(cs.clone(), || Ok(a))
+                .unwrap()
+                .add_unchecked(&G1Var::new_witness(cs.clone(), || Ok(b)).unwrap())
+                .unwrap();
+            assert_eq!(sum.value().unwrap(), (a + b).into_affine());
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // P + (-P) isn't satisfiable
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let a = G1Var::new_witness(cs.clone(), || Ok(p)).unwrap();
+        a.add_unchecked(&a.negate().unwrap()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_g1_rejects_off_curve() {
+        let rng = &mut ark_std::test_rng();
+        let (p, q) = (G1Affine::rand(rng), G1Affine::rand(rng));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let off_curve = G1Affine::new_unchecked(*p.x().unwrap(), *q.y().unwrap());
+        G1Var::new_witness(cs.clone(), || Ok(off_curve)).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_g1_scalar_mul() {
+        let rng = &mut ark_std::test_rng();
+        let p = G1Affine::rand(rng);
+        let s = 0b1011_0110u64;
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let a = G1Var::new_witness(cs.clone(), || Ok(p)).unwrap();
+        let bits = (0..8)
+            .map(|i| Boolean::new_witness(cs.clone(), || Ok((s >> i) & 1 == 1)).unwrap())
+            .collect::<Vec<_>>();
+        let product = a.scalar_mul_le(&bits).unwrap();
+        assert_eq!(
+            product.value().unwrap(),
+            (G1Projective::from(p) * Fr::from(s)).into_affine()
+        );
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_g1_msm() {
+        let rng = &mut ark_std::test_rng();
+        let points = [G1Affine::rand(rng), G1Affine::zero(), G1Affine::rand(rng)];
+        let scalars = [0b1011_0110u64, 77, 3];
+
+        // short scalars, to keep the test fast
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let point_vars = points
+            .iter()
+            .map(|p| G1Var::new_witness(cs.clone(), || Ok(*p)).unwrap())
+            .collect::<Vec<_>>();
+        let bits = scalars
+            .iter()
+            .map(|s| {
+                (0..8)
+                    .map(|i| Boolean::new_witness(cs.clone(), || Ok((s >> i) & 1 == 1)).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let msm = G1Var::msm_le(&point_vars, &bits).unwrap();
+        let expected = points
+            .iter()
+            .zip(scalars)
+            .map(|(p, s)| *p * Fr::from(s))
+            .sum::<G1Projective>()
+            .into_affine();
+        assert_eq!(msm.value().unwrap(), expected);
+        assert!(cs.is_satisfied().unwrap());
+
+        // equal points with equal scalars aren't satisfiable
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let p = G1Var::new_witness(cs.clone(), || Ok(points[0])).unwrap();
+        let bits = (0..8)
+            .map(|i| Boolean::new_witness(cs.clone(), || Ok((scalars[0] >> i) & 1 == 1)).unwrap())
+            .collect::<Vec<_>>();
+        G1Var::msm_le(&[p.clone(), p], &[bits.clone(), bits]).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_g1_to_limbs() {
+        let rng = &mut ark_std::test_rng();
+        let p = G1Affine::rand(rng);
+
+        let limbs = G1Var::constant(p)
+            .to_limbs()
+            .unwrap()
+            .iter()
+            .map(|limb| limb.value().unwrap())
+            .collect::<Vec<_>>();
+        let bytes = crate::curve::point_to_bytes_be(&p);
+        assert_eq!(
+            limbs,
+            crate::transcript::poseidon::bytes_to_limbs::<Fr>(&bytes)[1..]
+        );
+    }
+}
@@ -0,0 +1,973 @@
+//! The fflonk verifier as constraints over the scalar field of BN254, eg: to aggregate many
+//! proofs of the same circuit in an outer Groth16 over BN254.
+//!
+//! It mirrors `verifier::fflonk_verifier` with the algebraic `PoseidonTranscriptHash`:
+//!     - the challenges, the roots and zh(xi), see `ChallengesVar`
+//!     - the batch inversion hinted by the prover with `proof.evaluations.inv`, see `InversionVar`
+//!     - L_1(xi), PI(xi), R0, R1, R2 and the scalars of F, E and J
+//!     - A1 = F - E - J + y·W2, over the non-native G1, see `G1Var`
+//!
+//! The pairing e(A1, [1]_2)·e(W2, -X_2) = 1 is deferred: (A1, W2) is the public output of the
+//! circuit, see `PairingAccumulator`, for the outer verifier to check natively. The accumulators
+//! of many proofs fold into a single one, so that a single pairing checks them all.
+use crate::challenge::Challenges;
+use crate::curve::point_to_bytes_be;
+use crate::pairing::{check_pairing_with_w2, randomize_pairs};
+use crate::proof::{Evaluations, Proof};
+use crate::transcript::poseidon::{bytes_to_limbs, PoseidonTranscriptHash};
+use crate::transcript::TranscriptHash;
+use crate::verifier::compute_a1_with_challenges;
+use crate::vk::VerificationKey;
+use ark_bn254::{Fq, Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{One, Zero};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::nonnative::NonNativeFieldVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, Namespace, SynthesisError};
+use std::borrow::Borrow;
+
+pub mod g1;
+pub mod transcript;
+
+use g1::G1Var;
+use transcript::TranscriptVar;
+
+pub type FrVar = FpVar<Fr>;
+pub type FqVar = NonNativeFieldVar<Fq, Fr>;
+// (quotient1, quotient2, e_scalar, numerator)
+pub type FEJScalarsVar = (FrVar, FrVar, FrVar, FrVar);
+
+#[derive(Debug, Clone)]
+pub struct EvaluationsVar {
+    pub ql: FrVar,
+    pub qr: FrVar,
+    pub qm: FrVar,
+    pub qo: FrVar,
+    pub qc: FrVar,
+    pub s1: FrVar,
+    pub s2: FrVar,
+    pub s3: FrVar,
+    pub a: FrVar,
+    pub b: FrVar,
+    pub c: FrVar,
+    pub z: FrVar,
+    pub zw: FrVar,
+    pub t1w: FrVar,
+    pub t2w: FrVar,
+    pub inv: FrVar,
+}
+
+impl AllocVar<Evaluations, Fr> for EvaluationsVar {
+    fn new_variable<T: Borrow<Evaluations>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let evaluations = f().map(|e| e.borrow().clone());
+        let new = |eval: fn(&Evaluations) -> Fr| {
+            FrVar::new_variable(
+                cs.clone(),
+                || evaluations.as_ref().map(eval).map_err(|e| *e),
+                mode,
+            )
+        };
+
+        Ok(Self {
+            ql: new(|e| e.ql)?,
+            qr: new(|e| e.qr)?,
+            qm: new(|e| e.qm)?,
+            qo: new(|e| e.qo)?,
+            qc: new(|e| e.qc)?,
+            s1: new(|e| e.s1)?,
+            s2: new(|e| e.s2)?,
+            s3: new(|e| e.s3)?,
+            a: new(|e| e.a)?,
+            b: new(|e| e.b)?,
+            c: new(|e| e.c)?,
+            z: new(|e| e.z)?,
+            zw: new(|e| e.zw)?,
+            t1w: new(|e| e.t1w)?,
+            t2w: new(|e| e.t2w)?,
+            inv: new(|e| e.inv)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProofVar {
+    pub c1: G1Var,
+    pub c2: G1Var,
+    pub w1: G1Var,
+    pub w2: G1Var,
+    pub evaluations: EvaluationsVar,
+}
+
+impl AllocVar<Proof, Fr> for ProofVar {
+    fn new_variable<T: Borrow<Proof>>(
+        cs: impl Into<Namespace<Fr>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        let proof = f().map(|p| p.borrow().clone());
+        let point = |p: fn(&Proof) -> G1Projective| {
+            G1Var::new_variable(
+                cs.clone(),
+                || {
+                    proof
+                        .as_ref()
+                        .map(|proof| p(proof).into_affine())
+                        .map_err(|e| *e)
+                },
+                mode,
+            )
+        };
+
+        Ok(Self {
+            c1: point(|p| p.polynomials.c1)?,
+            c2: point(|p| p.polynomials.c2)?,
+            w1: point(|p| p.polynomials.w1)?,
+            w2: point(|p| p.polynomials.w2)?,
+            evaluations: EvaluationsVar::new_variable(
+                cs.clone(),
+                || {
+                    proof
+                        .as_ref()
+                        .map(|p| p.evaluations.clone())
+                        .map_err(|e| *e)
+                },
+                mode,
+            )?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RootsVar {
+    pub h0w8: Vec<FrVar>,
+    pub h1w4: Vec<FrVar>,
+    pub h2w3: Vec<FrVar>,
+    pub h3w3: Vec<FrVar>,
+}
+
+impl RootsVar {
+    // See `Roots::compute`.
+    pub fn compute(vk: &VerificationKey, xi_seed: &FrVar) -> Result<Self, SynthesisError> {
+        let omegas = &vk.omega;
+        let xi_seed_2 = xi_seed.square()?;
+        let xi_seed_3 = xi_seed * &xi_seed_2;
+        let xi_seed_6 = xi_seed_3.square()?;
+        let xi_seed_8 = &xi_seed_6 * &xi_seed_2;
+        let h3w3_0 = &xi_seed_8 * omegas.wr;
+
+        let h0w8 = [
+            Fr::one(),
+            omegas.w8_1,
+            omegas.w8_2,
+            omegas.w8_3,
+            omegas.w8_4,
+            omegas.w8_5,
+            omegas.w8_6,
+            omegas.w8_7,
+        ];
+        let h1w4 = [Fr::one(), omegas.w4, omegas.w4_2, omegas.w4_3];
+        let w3 = [Fr::one(), omegas.w3, omegas.w3_2];
+
+        Ok(Self {
+            h0w8: h0w8.iter().map(|w| &xi_seed_3 * *w).collect(),
+            h1w4: h1w4.iter().map(|w| &xi_seed_6 * *w).collect(),
+            h2w3: w3.iter().map(|w| &xi_seed_8 * *w).collect(),
+            h3w3: w3.iter().map(|w| &h3w3_0 * *w).collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChallengesVar {
+    pub alpha: FrVar,
+    pub beta: FrVar,
+    pub gamma: FrVar,
+    pub y: FrVar,
+    pub xi: FrVar,
+    pub zh: FrVar,
+    pub roots: RootsVar,
+}
+
+impl ChallengesVar {
+    // See `Challenges::compute::<PoseidonTranscriptHash>`.
+    pub fn compute(
+        cs: ConstraintSystemRef<Fr>,
+        vk: &VerificationKey,
+        proof: &ProofVar,
+        pub_input: &FrVar,
+    ) -> Result<Self, SynthesisError> {
+        let fr = TranscriptVar::fr_to_limbs;
+
+        // beta = H(C0 || pub_input || C1)
+        let limbs = [
+            G1Var::constant(vk.c0).to_limbs()?,
+            fr(pub_input)?,
+            proof.c1.to_limbs()?,
+        ]
+        .concat();
+        let beta = TranscriptVar::hash(cs.clone(), 160, &limbs)?;
+
+        // gamma = H(beta)
+        let gamma = TranscriptVar::hash(cs.clone(), 32, &fr(&beta)?)?;
+
+        // xi_seed = H(gamma || C2)
+        let limbs = [fr(&gamma)?, proof.c2.to_limbs()?].concat();
+        let xi_seed = TranscriptVar::hash(cs.clone(), 96, &limbs)?;
+
+        // alpha = H(xi_seed || evaluations)
+        let e = &proof.evaluations;
+        let mut limbs = fr(&xi_seed)?;
+        for eval in [
+            &e.ql, &e.qr, &e.qm, &e.qo, &e.qc, &e.s1, &e.s2, &e.s3, &e.a, &e.b, &e.c, &e.z, &e.zw,
+            &e.t1w, &e.t2w,
+        ] {
+            limbs.extend(fr(eval)?);
+        }
+        let alpha = TranscriptVar::hash(cs.clone(), 512, &limbs)?;
+
+        // y = H(alpha || W1)
+        let limbs = [fr(&alpha)?, proof.w1.to_limbs()?].concat();
+        let y = TranscriptVar::hash(cs, 96, &limbs)?;
+
+        // xi = xi_seed^24 and zh = xi^n - 1, with n = 2^power
+        let xi = xi_seed.pow_by_constant([24u64])?;
+        let mut xin = xi.clone();
+        for _ in 0..vk.power {
+            xin = xin.square()?;
+        }
+
+        Ok(Self {
+            alpha,
+            beta,
+            gamma,
+            y,
+            xi,
+            zh: xin - Fr::one(),
+            roots: RootsVar::compute(vk, &xi_seed)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InversionVar {
+    pub eval_l1: FrVar,
+    pub li_s0_inv: Vec<FrVar>,
+    pub li_s1_inv: Vec<FrVar>,
+    pub li_s2_inv: Vec<FrVar>,
+    pub den_h1: FrVar,
+    pub den_h2: FrVar,
+    pub zh_inv: FrVar,
+}
+
+impl InversionVar {
+    // See `Inversion::build`: the denominators are the same, and so is the accumulator, which is
+    // checked against the inverse `proof.evaluations.inv` sent by the prover. Then the inverses
+    // follow from the Montgomery batch inversion, without any other hint.
+    pub fn build(
+        vk: &VerificationKey,
+        proof: &ProofVar,
+        challenges: &ChallengesVar,
+    ) -> Result<Self, SynthesisError> {
+        let roots = &challenges.roots;
+        let (y, xi) = (&challenges.y, &challenges.xi);
+        let product = |roots: &[FrVar]| {
+            roots
+                .iter()
+                .fold(FrVar::one(), |acc, root| acc * (y - root))
+        };
+
+        let den_h1_base = product(&roots.h1w4);
+        let den_h2_base = product(&[roots.h2w3.clone(), roots.h3w3.clone()].concat());
+
+        // root0^6 * 8
+        let den1 = roots.h0w8[0].pow_by_constant([6u64])? * Fr::from(8u64);
+        let li_s0 = (0..8)
+            .map(|i| &den1 * &roots.h0w8[(i * 7) % 8] * (y - &roots.h0w8[i]))
+            .collect::<Vec<_>>();
+
+        let den1 = roots.h1w4[0].square()? * Fr::from(4u64);
+        let li_s1 = (0..4)
+            .map(|i| &den1 * &roots.h1w4[(i * 3) % 4] * (y - &roots.h1w4[i]))
+            .collect::<Vec<_>>();
+
+        let xiw = xi * vk.omega.w;
+        let den1 = &roots.h2w3[0] * (xi - &xiw) * Fr::from(3u64);
+        let den2 = &roots.h3w3[0] * (&xiw - xi) * Fr::from(3u64);
+        let li_s2 = (0..3)
+            .map(|i| &den1 * &roots.h2w3[(i * 2) % 3] * (y - &roots.h2w3[i]))
+            .chain((0..3).map(|i| &den2 * &roots.h3w3[(i * 2) % 3] * (y - &roots.h3w3[i])))
+            .collect::<Vec<_>>();
+
+        let eval_l1_base = (xi - Fr::one()) * vk.n;
+
+        // [zh, den_h1_base, den_h2_base, li_s0.., li_s1.., li_s2.., eval_l1_base]
+        let denominators = [
+            vec![challenges.zh.clone(), den_h1_base, den_h2_base],
+            li_s0,
+            li_s1,
+            li_s2,
+            vec![eval_l1_base],
+        ]
+        .concat();
+        let mut accumulator = Vec::with_capacity(denominators.len());
+        let mut acc = FrVar::one();
+        for denominator in &denominators {
+            acc *= denominator;
+            accumulator.push(acc.clone());
+        }
+
+        // zh*den_h1*den_h2 * MUL(li_s0[i]) * MUL(li_s1[i]) * MUL(li_s2[i]) * eval_l1 * proof.inv = 1
+        (acc * &proof.evaluations.inv).enforce_equal(&FrVar::one())?;
+
+        let mut inverses = vec![FrVar::zero(); denominators.len()];
+        let mut acc = proof.evaluations.inv.clone();
+        for i in (1..denominators.len()).rev() {
+            inverses[i] = &acc * &accumulator[i - 1];
+            acc *= &denominators[i];
+        }
+        inverses[0] = acc;
+
+        Ok(Self {
+            zh_inv: inverses[0].clone(),
+            den_h1: inverses[1].clone(),
+            den_h2: inverses[2].clone(),
+            li_s0_inv: inverses[3..11].to_vec(),
+            li_s1_inv: inverses[11..15].to_vec(),
+            li_s2_inv: inverses[15..21].to_vec(),
+            eval_l1: inverses[21].clone(),
+        })
+    }
+}
+
+// See `utils::polynomial_eval`.
+fn polynomial_eval_var(
+    base: &FrVar,
+    coefficients: &[FrVar],
+    roots: &[FrVar],
+    inv: &[FrVar],
+    acc: FrVar,
+) -> FrVar {
+    let mut acc = acc;
+    for (root, inv) in roots.iter().zip(inv) {
+        let mut h = FrVar::one();
+        let mut value = FrVar::zero();
+        for c in coefficients {
+            value += c * &h;
+            h *= root;
+        }
+        acc += value * base * inv;
+    }
+    acc
+}
+
+// See `compute_r::compute_r`.
+pub fn compute_r_var(
+    vk: &VerificationKey,
+    proof: &ProofVar,
+    challenges: &ChallengesVar,
+    inversion: &InversionVar,
+    L_1: &FrVar,
+    pi: &FrVar,
+) -> Result<(FrVar, FrVar, FrVar), SynthesisError> {
+    let e = &proof.evaluations;
+    let (y, xi, beta, gamma) = (
+        &challenges.y,
+        &challenges.xi,
+        &challenges.beta,
+        &challenges.gamma,
+    );
+    let roots = &challenges.roots;
+    let zh_inv = &inversion.zh_inv;
+
+    // r0(y)
+    let num = y.pow_by_constant([8u64])? - xi;
+    let coefficients = [&e.ql, &e.qr, &e.qo, &e.qm, &e.qc, &e.s1, &e.s2, &e.s3].map(Clone::clone);
+    let R0 = polynomial_eval_var(
+        &num,
+        &coefficients,
+        &roots.h0w8,
+        &inversion.li_s0_inv,
+        FrVar::zero(),
+    );
+
+    // r1(y), with T0(xi) = (ql·a + qr·b + qo·c + qm·a·b + qc + PI(xi)) / Z_H(xi)
+    let num = y.pow_by_constant([4u64])? - xi;
+    let t0 =
+        (&e.ql * &e.a + &e.qr * &e.b + &e.qo * &e.c + &e.qm * &e.a * &e.b + &e.qc + pi) * zh_inv;
+    let coefficients = [e.a.clone(), e.b.clone(), e.c.clone(), t0];
+    let R1 = polynomial_eval_var(
+        &num,
+        &coefficients,
+        &roots.h1w4,
+        &inversion.li_s1_inv,
+        FrVar::zero(),
+    );
+
+    // r2(y), with base = y^6 - y^3*xi*(1+w) + xi^2*w
+    let w = vk.omega.w;
+    let base = y.pow_by_constant([6u64])? - y.pow_by_constant([3u64])? * xi * (Fr::one() + w)
+        + xi.square()? * w;
+    let t1 = (&e.z - Fr::one()) * L_1 * zh_inv;
+    let beta_xi = beta * xi;
+    let t2 = ((&e.a + &beta_xi + gamma)
+        * (&e.b + &beta_xi * vk.k1 + gamma)
+        * (&e.c + &beta_xi * vk.k2 + gamma)
+        * &e.z
+        - (&e.a + beta * &e.s1 + gamma)
+            * (&e.b + beta * &e.s2 + gamma)
+            * (&e.c + beta * &e.s3 + gamma)
+            * &e.zw)
+        * zh_inv;
+    let gamma_r2 = polynomial_eval_var(
+        &base,
+        &[e.z.clone(), t1, t2],
+        &roots.h2w3,
+        &inversion.li_s2_inv[0..3],
+        FrVar::zero(),
+    );
+    let R2 = polynomial_eval_var(
+        &base,
+        &[e.zw.clone(), e.t1w.clone(), e.t2w.clone()],
+        &roots.h3w3,
+        &inversion.li_s2_inv[3..],
+        gamma_r2,
+    );
+
+    Ok((R0, R1, R2))
+}
+
+// The scalars of F, E and J: (quotient1, quotient2, e_scalar, numerator), see `FEJ::compute_scalars`.
+pub fn compute_fej_scalars_var(
+    challenges: &ChallengesVar,
+    inversion: &InversionVar,
+    R0: &FrVar,
+    R1: &FrVar,
+    R2: &FrVar,
+) -> FEJScalarsVar {
+    let numerator = challenges
+        .roots
+        .h0w8
+        .iter()
+        .fold(FrVar::one(), |acc, root| acc * (&challenges.y - root));
+    let quotient1 = &challenges.alpha * &numerator * &inversion.den_h1;
+    let quotient2 = &challenges.alpha * &challenges.alpha * &numerator * &inversion.den_h2;
+    let e_scalar = R0 + &quotient1 * R1 + &quotient2 * R2;
+
+    (quotient1, quotient2, e_scalar, numerator)
+}
+
+/// The challenges and the scalars of F, E and J of the verifier, see `compute_fej_scalars_var`:
+/// all of it but the curve arithmetic.
+pub fn fflonk_scalars_gadget(
+    cs: ConstraintSystemRef<Fr>,
+    vk: &VerificationKey,
+    proof: &ProofVar,
+    pub_input: &FrVar,
+) -> Result<(ChallengesVar, FEJScalarsVar), SynthesisError> {
+    // 1. compute challenge
+    let challenges = ChallengesVar::compute(cs, vk, proof, pub_input)?;
+
+    // 2. compute inversion
+    let inversion = InversionVar::build(vk, proof, &challenges)?;
+
+    // 3. compute lagrange of L_1
+    let L_1 = &challenges.zh * &inversion.eval_l1;
+
+    // 4. Compute public input polynomial evaluation PI(xi) = -public_input·L_1(xi)
+    let pi = (pub_input * &L_1).negate()?;
+
+    // 5. Computes r0(y), r1(y) and r2(y)
+    let (R0, R1, R2) = compute_r_var(vk, proof, &challenges, &inversion, &L_1, &pi)?;
+
+    // 6. compute the scalars of F, E and J
+    let scalars = compute_fej_scalars_var(&challenges, &inversion, &R0, &R1, &R2);
+    Ok((challenges, scalars))
+}
+
+// The points and the scalars of A1 - C0, see `fflonk_verifier_gadget`:
+//      quotient1·C1 + quotient2·C2 - e_scalar·[1]_1 - numerator·W1 + y·W2
+fn a1_msm_inputs(
+    vk: &VerificationKey,
+    proof: &ProofVar,
+    challenges: &ChallengesVar,
+    (quotient1, quotient2, e_scalar, numerator): FEJScalarsVar,
+) -> Result<(Vec<G1Var>, Vec<FrVar>), SynthesisError> {
+    Ok((
+        vec![
+            proof.c1.clone(),
+            proof.c2.clone(),
+            G1Var::constant(vk.g1),
+            proof.w1.clone(),
+            proof.w2.clone(),
+        ],
+        vec![
+            quotient1,
+            quotient2,
+            e_scalar.negate()?,
+            numerator.negate()?,
+            challenges.y.clone(),
+        ],
+    ))
+}
+
+/// The verifier up to the pairing, which is deferred to the returned accumulator (A1, W2).
+pub fn fflonk_verifier_gadget(
+    cs: ConstraintSystemRef<Fr>,
+    vk: &VerificationKey,
+    proof: &ProofVar,
+    pub_input: &FrVar,
+) -> Result<PairingAccumulatorVar, SynthesisError> {
+    let (challenges, scalars) = fflonk_scalars_gadget(cs, vk, proof, pub_input)?;
+
+    // 7-8. A1 = C0 + quotient1·C1 + quotient2·C2 - e_scalar·[1]_1 - numerator·W1 + y·W2,
+    // the multiplications sharing their doublings
+    let (points, scalars) = a1_msm_inputs(vk, proof, &challenges, scalars)?;
+    let a1 = G1Var::constant(vk.c0).add(&G1Var::msm(&points, &scalars)?)?;
+
+    Ok(PairingAccumulatorVar {
+        a1,
+        w2: proof.w2.clone(),
+    })
+}
+
+/// The deferred pairing check e(A1, vk.g2)·e(W2, -vk.X2) = 1 of proofs of the same circuit.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PairingAccumulator {
+    pub a1: G1Affine,
+    pub w2: G1Affine,
+}
+
+impl PairingAccumulator {
+    /// The accumulator of a single proof, or `None` if the inverse sent by the prover is wrong.
+    pub fn compute<T: TranscriptHash>(
+        vk: &VerificationKey,
+        proof: &Proof,
+        pub_input: &Fr,
+    ) -> Option<Self> {
+        let challenges = Challenges::compute::<T>(vk, proof, pub_input);
+        let a1 = compute_a1_with_challenges(vk, proof, pub_input, &challenges)?;
+
+        Some(Self {
+            a1,
+            w2: proof.polynomials.w2.into_affine(),
+        })
+    }
+
+    /// Fold the accumulators into (∑ rho^i·A1_i, ∑ rho^i·W2_i), see `pairing::randomize_pairs`
+    /// with the `PoseidonTranscriptHash`, which passes iff all of them do (but with a negligible
+    /// probability).
+    pub fn fold(accumulators: &[Self]) -> Self {
+        let pairs = accumulators
+            .iter()
+            .map(|acc| (acc.a1, acc.w2))
+            .collect::<Vec<_>>();
+        let (a1, w2) = randomize_pairs::<PoseidonTranscriptHash>(&pairs)
+            .into_iter()
+            .fold(
+                (G1Projective::zero(), G1Projective::zero()),
+                |(a1, w2), (a1_i, w2_i)| (a1 + a1_i, w2 + w2_i),
+            );
+
+        Self {
+            a1: a1.into_affine(),
+            w2: w2.into_affine(),
+        }
+    }
+
+    pub fn check(&self, vk: &VerificationKey) -> bool {
+        check_pairing_with_w2(vk, &self.a1, &self.w2)
+    }
+
+    // The public inputs of `PairingAccumulatorVar::enforce_public`:
+    //      [a1.x_hi, a1.x_lo, a1.y_hi, a1.y_lo, a1.infinity, w2.x_hi, ..., w2.infinity]
+    pub fn public_inputs(&self) -> Vec<Fr> {
+        [self.a1, self.w2]
+            .iter()
+            .flat_map(|p| {
+                let mut limbs = bytes_to_limbs::<Fr>(&point_to_bytes_be(p))[1..].to_vec();
+                limbs.push(Fr::from(p.is_zero()));
+                limbs
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PairingAccumulatorVar {
+    pub a1: G1Var,
+    pub w2: G1Var,
+}
+
+impl PairingAccumulatorVar {
+    // See `PairingAccumulator::fold`.
+    pub fn fold(
+        cs: ConstraintSystemRef<Fr>,
+        accumulators: &[Self],
+    ) -> Result<Self, SynthesisError> {
+        let (first, rest) = match accumulators {
+            [] => {
+                return Ok(Self {
+                    a1: G1Var::zero(),
+                    w2: G1Var::zero(),
+                })
+            }
+            [accumulator] => return Ok(accumulator.clone()),
+            [first, rest @ ..] => (first, rest),
+        };
+
+        let mut limbs = vec![];
+        for accumulator in accumulators {
+            limbs.extend(accumulator.a1.to_limbs()?);
+            limbs.extend(accumulator.w2.to_limbs()?);
+        }
+        let rho = TranscriptVar::hash(cs, 128 * accumulators.len(), &limbs)?;
+
+        // ρ^i·A1_i and ρ^i·W2_i for i > 0, the first accumulator being added as it is
+        let mut scalars = vec![rho.clone()];
+        while scalars.len() < rest.len() {
+            scalars.push(scalars.last().unwrap() * &rho);
+        }
+        let a1s = rest.iter().map(|acc| acc.a1.clone()).collect::<Vec<_>>();
+        let w2s = rest.iter().map(|acc| acc.w2.clone()).collect::<Vec<_>>();
+        let a1 = first.a1.add(&G1Var::msm(&a1s, &scalars)?)?;
+        let w2 = first.w2.add(&G1Var::msm(&w2s, &scalars)?)?;
+        Ok(Self { a1, w2 })
+    }
+
+    // Expose the accumulator as public inputs, see `PairingAccumulator::public_inputs`.
+    pub fn enforce_public(&self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        for p in [&self.a1, &self.w2] {
+            for limb in p.to_limbs()? {
+                FrVar::new_input(cs.clone(), || limb.value())?.enforce_equal(&limb)?;
+            }
+            Boolean::new_input(cs.clone(), || p.infinity.value())?.enforce_equal(&p.infinity)?;
+        }
+        Ok(())
+    }
+}
+
+/// The aggregation of proofs of the same circuit, for an outer SNARK over the scalar field of
+/// BN254, eg: Groth16.
+///
+/// Its public inputs are the ones of the proofs, followed by the ones of their folded
+/// `PairingAccumulator`, see `public_inputs`: the outer verifier checks the accumulator too.
+#[derive(Debug, Clone)]
+pub struct FflonkVerifierCircuit {
+    pub vk: VerificationKey,
+    pub proofs: Vec<(Proof, Fr)>,
+}
+
+impl FflonkVerifierCircuit {
+    /// The public inputs of the circuit, and the folded accumulator, or `None` if a proof
+    /// has a wrong inverse.
+    pub fn public_inputs(&self) -> Option<(Vec<Fr>, PairingAccumulator)> {
+        let accumulators = self
+            .proofs
+            .iter()
+            .map(|(proof, pub_input)| {
+                PairingAccumulator::compute::<PoseidonTranscriptHash>(&self.vk, proof, pub_input)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let accumulator = PairingAccumulator::fold(&accumulators);
+
+        let mut inputs = self
+            .proofs
+            .iter()
+            .map(|(_, pub_input)| *pub_input)
+            .collect::<Vec<_>>();
+        inputs.extend(accumulator.public_inputs());
+        Some((inputs, accumulator))
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for FflonkVerifierCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let pub_inputs = self
+            .proofs
+            .iter()
+            .map(|(_, pub_input)| FrVar::new_input(cs.clone(), || Ok(*pub_input)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut accumulators = vec![];
+        for ((proof, _), pub_input) in self.proofs.iter().zip(&pub_inputs) {
+            let proof = ProofVar::new_witness(cs.clone(), || Ok(proof))?;
+            accumulators.push(fflonk_verifier_gadget(
+                cs.clone(),
+                &self.vk,
+                &proof,
+                pub_input,
+            )?);
+        }
+
+        PairingAccumulatorVar::fold(cs.clone(), &accumulators)?.enforce_public(cs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compute_fej::FEJ;
+    use crate::compute_r::compute_r;
+    use crate::inversion::Inversion;
+    use crate::simulator::Simulator;
+    use crate::utils::{compute_pi, LangrangePolynomialEvaluation};
+    use crate::verifier::fflonk_verifier;
+    use ark_ff::{PrimeField, UniformRand};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::rngs::StdRng;
+    use ark_std::rand::SeedableRng;
+
+    // A simulated proof under the Poseidon transcript, see `Simulator`.
+    fn simulate(simulator: &Simulator, rng: &mut StdRng, pub_input: &Fr) -> Proof {
+        simulator
+            .simulate::<PoseidonTranscriptHash, _>(pub_input, rng)
+            .unwrap()
+    }
+
+    // The low bits of the scalars of A1 going through `G1Var::msm_le`, see `reduced_verifier_circuit`.
+    const REDUCED_MSM_BITS: usize = 8;
+
+    // The constraints of `FflonkVerifierCircuit` for a single proof, but for a reduced MSM of A1:
+    // only the REDUCED_MSM_BITS low bits of its scalars go through `G1Var::msm_le`, their high
+    // part being a witness computed out of the circuit, from the values of the scalars. The MSM
+    // alone is millions of constraints, see `test_fflonk_verifier_circuit` for the whole circuit.
+    fn reduced_verifier_circuit(
+        cs: ConstraintSystemRef<Fr>,
+        vk: &VerificationKey,
+        proof: &Proof,
+        pub_input: &Fr,
+    ) -> Result<(), SynthesisError> {
+        let pub_input = FrVar::new_input(cs.clone(), || Ok(*pub_input))?;
+        let proof = ProofVar::new_witness(cs.clone(), || Ok(proof))?;
+        let (challenges, scalars) = fflonk_scalars_gadget(cs.clone(), vk, &proof, &pub_input)?;
+        let (points, scalars) = a1_msm_inputs(vk, &proof, &challenges, scalars)?;
+
+        let low_bits = scalars
+            .iter()
+            .map(|s| Ok(s.to_bits_le()?[..REDUCED_MSM_BITS].to_vec()))
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+        let high = G1Var::new_witness(cs.clone(), || {
+            points
+                .iter()
+                .zip(&scalars)
+                .try_fold(G1Projective::zero(), |acc, (p, s)| {
+                    let s = s.value()?;
+                    let low = s.into_bigint().0[0] % (1 << REDUCED_MSM_BITS);
+                    Ok(acc + p.value()? * (s - Fr::from(low)))
+                })
+                .map(|high| high.into_affine())
+        })?;
+        let a1 = G1Var::constant(vk.c0)
+            .add(&G1Var::msm_le(&points, &low_bits)?)?
+            .add(&high)?;
+
+        let accumulator = PairingAccumulatorVar { a1, w2: proof.w2 };
+        PairingAccumulatorVar::fold(cs.clone(), &[accumulator])?.enforce_public(cs)
+    }
+
+    #[test]
+    fn test_fflonk_scalars_gadget() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        let simulator = Simulator::new(3, Fr::rand(rng), rng).unwrap();
+        let vk = simulator.vk.clone();
+        let pub_input = Fr::from(33u64);
+        let proof = simulate(&simulator, rng, &pub_input);
+        assert!(fflonk_verifier::<PoseidonTranscriptHash>(
+            &vk, &proof, &pub_input, false
+        ));
+
+        // the native values
+        let challenges = Challenges::compute::<PoseidonTranscriptHash>(&vk, &proof, &pub_input);
+        let inversion = Inversion::build(&vk, &proof, &challenges);
+        let l1 = LangrangePolynomialEvaluation::compute_L1_polynomial_evaluation(
+            &challenges.zh,
+            &inversion.eval_l1,
+        );
+        let pi = compute_pi(&[pub_input], &[l1]);
+        let (r0, r1, r2) = compute_r(&vk, &proof, &challenges, &inversion, &l1, &pi);
+        let scalars = FEJ::compute_scalars(&challenges, &inversion, r0, r1, r2);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let proof_var = ProofVar::new_witness(cs.clone(), || Ok(&proof)).unwrap();
+        let pub_input_var = FrVar::new_input(cs.clone(), || Ok(pub_input)).unwrap();
+        let (challenges_var, (q1, q2, e, numerator)) =
+            fflonk_scalars_gadget(cs.clone(), &vk, &proof_var, &pub_input_var).unwrap();
+
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(challenges_var.beta.value().unwrap(), challenges.beta);
+        assert_eq!(challenges_var.y.value().unwrap(), challenges.y);
+        assert_eq!(challenges_var.zh.value().unwrap(), challenges.zh);
+        assert_eq!(
+            (
+                q1.value().unwrap(),
+                q2.value().unwrap(),
+                e.value().unwrap(),
+                numerator.value().unwrap()
+            ),
+            scalars
+        );
+
+        // a wrong inverse
+        let mut wrong = proof.clone();
+        wrong.evaluations.inv += Fr::one();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let proof_var = ProofVar::new_witness(cs.clone(), || Ok(&wrong)).unwrap();
+        let pub_input_var = FrVar::new_input(cs.clone(), || Ok(pub_input)).unwrap();
+        let _ = fflonk_scalars_gadget(cs.clone(), &vk, &proof_var, &pub_input_var).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_pairing_accumulator() {
+        let rng = &mut StdRng::seed_from_u64(1);
+        let simulator = Simulator::new(3, Fr::rand(rng), rng).unwrap();
+        let vk = simulator.vk.clone();
+        let proofs = (0..3u64)
+            .map(|i| {
+                let pub_input = Fr::from(i);
+                (simulate(&simulator, rng, &pub_input), pub_input)
+            })
+            .collect::<Vec<_>>();
+
+        let accumulators = proofs
+            .iter()
+            .map(|(proof, pub_input)| {
+                PairingAccumulator::compute::<PoseidonTranscriptHash>(&vk, proof, pub_input)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(accumulators.iter().all(|acc| acc.check(&vk)));
+        assert!(PairingAccumulator::fold(&accumulators).check(&vk));
+        assert_eq!(
+            PairingAccumulator::fold(&accumulators[..1]),
+            accumulators[0]
+        );
+
+        // a failing proof fails the folded accumulator
+        let mut accumulators = accumulators;
+        accumulators[1].a1 = (accumulators[1].a1 + G1Affine::generator()).into_affine();
+        assert!(!PairingAccumulator::fold(&accumulators).check(&vk));
+
+        let circuit = FflonkVerifierCircuit { vk, proofs };
+        let (inputs, accumulator) = circuit.public_inputs().unwrap();
+        assert_eq!(inputs.len(), 3 + 10);
+        assert_eq!(&inputs[3..], accumulator.public_inputs());
+    }
+
+    // The gadgets over constants, which compute the accumulators without any constraint:
+    // a cheap check of their values, see `test_fflonk_verifier_circuit` for the constraints.
+    #[test]
+    fn test_fflonk_verifier_gadget_values() {
+        let rng = &mut StdRng::seed_from_u64(3);
+        let simulator = Simulator::new(3, Fr::rand(rng), rng).unwrap();
+        let vk = simulator.vk.clone();
+        let proofs = (0..2u64)
+            .map(|i| {
+                let pub_input = Fr::from(i + 7);
+                (simulate(&simulator, rng, &pub_input), pub_input)
+            })
+            .collect::<Vec<_>>();
+
+        let mut accumulators = vec![];
+        let mut accumulator_vars = vec![];
+        for (proof, pub_input) in &proofs {
+            let proof_var = ProofVar::new_constant(ConstraintSystemRef::None, proof).unwrap();
+            let accumulator_var = fflonk_verifier_gadget(
+                ConstraintSystemRef::None,
+                &vk,
+                &proof_var,
+                &FrVar::constant(*pub_input),
+            )
+            .unwrap();
+            let accumulator =
+                PairingAccumulator::compute::<PoseidonTranscriptHash>(&vk, proof, pub_input)
+                    .unwrap();
+            assert_eq!(accumulator_var.a1.value().unwrap(), accumulator.a1);
+            assert_eq!(accumulator_var.w2.value().unwrap(), accumulator.w2);
+            accumulators.push(accumulator);
+            accumulator_vars.push(accumulator_var);
+        }
+
+        let folded =
+            PairingAccumulatorVar::fold(ConstraintSystemRef::None, &accumulator_vars).unwrap();
+        assert_eq!(
+            PairingAccumulator {
+                a1: folded.a1.value().unwrap(),
+                w2: folded.w2.value().unwrap(),
+            },
+            PairingAccumulator::fold(&accumulators)
+        );
+    }
+
+    #[test]
+    fn test_fflonk_verifier_circuit_reduced() {
+        let rng = &mut StdRng::seed_from_u64(4);
+        let simulator = Simulator::new(3, Fr::rand(rng), rng).unwrap();
+        let vk = &simulator.vk;
+        let pub_input = Fr::from(33u64);
+        let proof = simulate(&simulator, rng, &pub_input);
+
+        let circuit = FflonkVerifierCircuit {
+            vk: vk.clone(),
+            proofs: vec![(proof.clone(), pub_input)],
+        };
+        let (inputs, accumulator) = circuit.public_inputs().unwrap();
+        assert!(accumulator.check(vk));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        reduced_verifier_circuit(cs.clone(), vk, &proof, &pub_input).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        // the public inputs, A1 among them, are the ones of the whole circuit
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+
+        // another public input, or a wrong inverse, fail the scalars of the proof
+        let mut wrong = proof.clone();
+        wrong.evaluations.inv += Fr::one();
+        for (proof, pub_input) in [(&proof, pub_input + Fr::one()), (&wrong, pub_input)] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            reduced_verifier_circuit(cs.clone(), vk, proof, &pub_input).unwrap();
+            assert!(!cs.is_satisfied().unwrap());
+        }
+
+        // A1 is bound to the proof: another x_lo of A1 isn't satisfiable
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        reduced_verifier_circuit(cs.clone(), vk, &proof, &pub_input).unwrap();
+        cs.borrow_mut().unwrap().instance_assignment[3] += Fr::one();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    // The non-native scalar multiplications make it millions of constraints, and more than
+    // 6 GB of memory, see `test_fflonk_verifier_circuit_reduced` for the rest of its constraints:
+    // cargo test --release --features r1cs -- --ignored test_fflonk_verifier_circuit
+    #[test]
+    #[ignore]
+    fn test_fflonk_verifier_circuit() {
+        let rng = &mut StdRng::seed_from_u64(2);
+        let simulator = Simulator::new(3, Fr::rand(rng), rng).unwrap();
+        let vk = simulator.vk.clone();
+        let pub_input = Fr::from(33u64);
+        let proof = simulate(&simulator, rng, &pub_input);
+
+        let circuit = FflonkVerifierCircuit {
+            vk: vk.clone(),
+            proofs: vec![(proof, pub_input)],
+        };
+        let (inputs, accumulator) = circuit.public_inputs().unwrap();
+        assert!(accumulator.check(&vk));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        // the first instance variable is the constant one
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], inputs);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::r1cs::g1::bits_to_limbs;
+use crate::r1cs::FrVar;
+use crate::transcript::poseidon::poseidon_config;
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+/// The in-circuit `PoseidonTranscriptHash`.
+pub struct TranscriptVar;
+
+impl TranscriptVar {
+    // H(bytes) of `PoseidonTranscriptHash`, the `len` bytes being given as their 128-bit limbs,
+    // see `transcript::poseidon::bytes_to_limbs`.
+    pub fn hash(
+        cs: ConstraintSystemRef<Fr>,
+        len: usize,
+        limbs: &[FrVar],
+    ) -> Result<FrVar, SynthesisError> {
+        let mut sponge = PoseidonSpongeVar::new(cs, &poseidon_config::<Fr>());
+        let mut input = vec![FrVar::constant(Fr::from(len as u64))];
+        input.extend_from_slice(limbs);
+        sponge.absorb(&input)?;
+        Ok(sponge.squeeze_field_elements(1)?.remove(0))
+    }
+
+    // The limbs of the 32 big-endian bytes of `curve::field_to_bytes_be`: [f_hi, f_lo].
+    pub fn fr_to_limbs(f: &FrVar) -> Result<Vec<FrVar>, SynthesisError> {
+        bits_to_limbs(&f.to_bits_le()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::field_to_bytes_be;
+    use crate::transcript::poseidon::PoseidonTranscriptHash;
+    use crate::transcript::TranscriptHash;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_transcript_var() {
+        let rng = &mut ark_std::test_rng();
+        let (a, b) = (Fr::rand(rng), Fr::rand(rng));
+        let expected: Fr = PoseidonTranscriptHash::hash_to_fr(
+            [field_to_bytes_be(&a), field_to_bytes_be(&b)].concat(),
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let a = FrVar::new_witness(cs.clone(), || Ok(a)).unwrap();
+        let b = FrVar::new_witness(cs.clone(), || Ok(b)).unwrap();
+        let limbs = [
+            TranscriptVar::fr_to_limbs(&a).unwrap(),
+            TranscriptVar::fr_to_limbs(&b).unwrap(),
+        ]
+        .concat();
+        let hash = TranscriptVar::hash(cs.clone(), 64, &limbs).unwrap();
+        assert_eq!(hash.value().unwrap(), expected);
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -1,97 +1,183 @@
 pub mod fr {
-    use ark_bn254::Fr;
-    use std::str::FromStr;
+    use ark_ff::PrimeField;
 
-    pub fn serialize<S>(fr: &Fr, s: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S, F: PrimeField>(fr: &F, s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        s.serialize_str(&fr.to_string())
+        // `Display` of arkworks trims the leading zeros, so zero would be an empty string.
+        s.serialize_str(&fr.into_bigint().to_string())
     }
 
-    pub fn deserialize<'de, D>(data: D) -> Result<Fr, D::Error>
+    pub fn deserialize<'de, D, F: PrimeField>(data: D) -> Result<F, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        <&str as serde::Deserialize>::deserialize(data).map(|s| Fr::from_str(s).unwrap())
+        <&str as serde::Deserialize>::deserialize(data).and_then(|s| {
+            F::from_str(s).map_err(|_e| serde::de::Error::custom("Invalid field element"))
+        })
     }
 }
 
 pub mod fq {
-    use ark_bn254::Fq;
-    use std::str::FromStr;
+    use ark_ff::PrimeField;
+
+    pub fn serialize<S, F: PrimeField>(fr: &F, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_str(&fr.into_bigint().to_string())
+    }
+
+    pub fn deserialize<'de, D, F: PrimeField>(data: D) -> Result<F, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        super::fr::deserialize(data)
+    }
+}
+
+// A list of field elements, each as `fr`.
+pub mod frs {
+    use ark_ff::PrimeField;
+    use serde::ser::SerializeSeq;
 
-    pub fn serialize<S>(fr: &Fq, s: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S, F: PrimeField>(frs: &[F], s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let a = fr.to_string();
-        println!("serde_fr: {:?}", a);
-        s.serialize_str(&a)
+        let mut seq = s.serialize_seq(Some(frs.len()))?;
+        for fr in frs {
+            seq.serialize_element(&fr.into_bigint().to_string())?;
+        }
+        seq.end()
     }
 
-    pub fn deserialize<'de, D>(data: D) -> Result<Fq, D::Error>
+    pub fn deserialize<'de, D, F: PrimeField>(data: D) -> Result<Vec<F>, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        <&str as serde::Deserialize>::deserialize(data).map(|s| Fq::from_str(s).unwrap())
+        <Vec<String> as serde::Deserialize>::deserialize(data)?
+            .iter()
+            .map(|s| F::from_str(s).map_err(|_e| serde::de::Error::custom("Invalid field element")))
+            .collect()
+    }
+}
+
+// A fixed-size array of field elements, as `frs`.
+pub mod fr_array {
+    use ark_ff::PrimeField;
+
+    pub fn serialize<S, F: PrimeField, const N: usize>(
+        frs: &[F; N],
+        s: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        super::frs::serialize(frs, s)
+    }
+
+    pub fn deserialize<'de, D, F: PrimeField, const N: usize>(data: D) -> Result<[F; N], D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        super::frs::deserialize::<D, F>(data)?
+            .try_into()
+            .map_err(|frs: Vec<F>| {
+                serde::de::Error::invalid_length(frs.len(), &N.to_string().as_str())
+            })
     }
 }
 
 mod fq2 {
-    use ark_bn254::{Fq, Fq2};
+    use ark_ff::{Field, PrimeField};
     use serde::{Deserialize, Serialize};
 
+    // The quadratic extension field element as [c0, c1].
     #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
-    pub(crate) struct Fq2Serde(
-        #[serde(with = "super::fq")] pub(crate) Fq,
-        #[serde(with = "super::fq")] pub(crate) Fq,
+    #[serde(bound = "F: PrimeField")]
+    pub(crate) struct Fq2Serde<F: PrimeField>(
+        #[serde(with = "super::fq")] pub(crate) F,
+        #[serde(with = "super::fq")] pub(crate) F,
     );
 
-    pub fn serialize<S>(fq2: &Fq2, s: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S, F: Field>(fq2: &F, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut elems = fq2.to_base_prime_field_elements();
+        match (elems.next(), elems.next(), elems.next()) {
+            (Some(c0), Some(c1), None) => Fq2Serde(c0, c1).serialize(s),
+            _ => Err(serde::ser::Error::custom("Not a quadratic extension field")),
+        }
+    }
+
+    pub fn deserialize<'de, D, F: Field>(data: D) -> Result<F, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let fq2 = Fq2Serde::<F::BasePrimeField>::deserialize(data)?;
+        F::from_base_prime_field_elems(&[fq2.0, fq2.1])
+            .ok_or_else(|| serde::de::Error::custom("Not a quadratic extension field"))
+    }
+}
+
+// An extension field element as the list of its base prime field coefficients, eg: 12 for Fq12.
+pub mod fqk {
+    use ark_ff::{Field, PrimeField};
+    use serde::ser::SerializeSeq;
+
+    pub fn serialize<S, F: Field>(fqk: &F, s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        Fq2Serde(fq2.c0, fq2.c1).serialize(s)
+        let mut seq = s.serialize_seq(Some(F::extension_degree() as usize))?;
+        for c in fqk.to_base_prime_field_elements() {
+            seq.serialize_element(&c.into_bigint().to_string())?;
+        }
+        seq.end()
     }
 
-    pub fn deserialize<'de, D>(data: D) -> Result<Fq2, D::Error>
+    pub fn deserialize<'de, D, F: Field>(data: D) -> Result<F, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let fq2 = Fq2Serde::deserialize(data)?;
-        Ok(Fq2::new(fq2.0, fq2.1))
+        let elems = super::frs::deserialize::<D, F::BasePrimeField>(data)?;
+        F::from_base_prime_field_elems(&elems)
+            .ok_or_else(|| serde::de::Error::custom("Invalid extension field element"))
     }
 }
 
 pub mod g2 {
-    use ark_bn254::{Fq2, G2Projective};
-
-    use ark_serialize::Valid;
+    use crate::curve::SWPoint;
+    use ark_ff::Field;
 
     use serde::{Deserialize, Serialize};
 
     #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
-    pub(crate) struct G2Serde(
-        #[serde(with = "super::fq2")] pub(crate) Fq2,
-        #[serde(with = "super::fq2")] pub(crate) Fq2,
-        #[serde(with = "super::fq2")] pub(crate) Fq2,
+    #[serde(bound = "F: Field")]
+    pub(crate) struct G2Serde<F: Field>(
+        #[serde(with = "super::fq2")] pub(crate) F,
+        #[serde(with = "super::fq2")] pub(crate) F,
+        #[serde(with = "super::fq2")] pub(crate) F,
     );
     // serde G2Affine from G2Projective.
-    pub fn serialize<S>(g2: &G2Projective, s: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S, G: SWPoint>(g2: &G, s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        G2Serde(g2.x, g2.y, g2.z).serialize(s)
+        let [x, y, z] = g2.coordinates();
+        G2Serde(x, y, z).serialize(s)
     }
 
     // deserde G2Affine into G2Projective.
-    pub fn deserialize<'de, D>(data: D) -> Result<G2Projective, D::Error>
+    pub fn deserialize<'de, D, G: SWPoint>(data: D) -> Result<G, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
         let g2 = G2Serde::deserialize(data)?;
-        let candidate = G2Projective::new(g2.0, g2.1, g2.2);
+        let candidate = G::from_coordinates(g2.0, g2.1, g2.2);
         candidate
             .check()
             .map_err(|_e| serde::de::Error::custom("Invalid G2Projective point"))?;
@@ -100,32 +186,37 @@ pub mod g2 {
 }
 
 pub mod g1 {
-    use ark_bn254::{Fq, G1Projective};
-
-    use ark_serialize::Valid;
+    use crate::curve::SWPoint;
+    use ark_ff::PrimeField;
 
     use serde::{Deserialize, Serialize};
 
     #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
-    pub(crate) struct G1Serde(
-        #[serde(with = "super::fq")] pub(crate) Fq,
-        #[serde(with = "super::fq")] pub(crate) Fq,
-        #[serde(with = "super::fq")] pub(crate) Fq,
+    #[serde(bound = "F: PrimeField")]
+    pub(crate) struct G1Serde<F: PrimeField>(
+        #[serde(with = "super::fq")] pub(crate) F,
+        #[serde(with = "super::fq")] pub(crate) F,
+        #[serde(with = "super::fq")] pub(crate) F,
     );
 
-    pub fn serialize<S>(g1: &G1Projective, s: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S, G>(g1: &G, s: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
+        G: SWPoint,
+        G::BaseField: PrimeField,
     {
-        G1Serde(g1.x, g1.y, g1.z).serialize(s)
+        let [x, y, z] = g1.coordinates();
+        G1Serde(x, y, z).serialize(s)
     }
 
-    pub fn deserialize<'de, D>(data: D) -> Result<G1Projective, D::Error>
+    pub fn deserialize<'de, D, G>(data: D) -> Result<G, D::Error>
     where
         D: serde::Deserializer<'de>,
+        G: SWPoint,
+        G::BaseField: PrimeField,
     {
         let g1 = G1Serde::deserialize(data)?;
-        let candidate = G1Projective::new(g1.0, g1.1, g1.2);
+        let candidate = G::from_coordinates(g1.0, g1.1, g1.2);
         candidate
             .check()
             .map_err(|_e| serde::de::Error::custom("Invalid G1Projective point"))?;
@@ -133,6 +224,33 @@ pub mod g1 {
     }
 }
 
+// A G1 point in affine coordinates, as `g1` of its projective form.
+pub mod g1_affine {
+    use crate::curve::SWPoint;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::PrimeField;
+
+    pub fn serialize<S, G>(g1: &G, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+        G: AffineRepr,
+        G::Group: SWPoint,
+        <G::Group as SWPoint>::BaseField: PrimeField,
+    {
+        super::g1::serialize(&g1.into_group(), s)
+    }
+
+    pub fn deserialize<'de, D, G>(data: D) -> Result<G, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        G: AffineRepr,
+        G::Group: SWPoint,
+        <G::Group as SWPoint>::BaseField: PrimeField,
+    {
+        super::g1::deserialize::<D, G::Group>(data).map(|g1| g1.into_affine())
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -264,7 +382,7 @@ mod test {
 
         let json = serde_json::to_string(&expect_fq2).unwrap();
         println!("fq2: {:?}", json);
-        let actual_fq2: super::fq2::Fq2Serde = serde_json::from_str(&json).unwrap();
+        let actual_fq2: super::fq2::Fq2Serde<Fq> = serde_json::from_str(&json).unwrap();
 
         assert_eq!(expect_fq2, actual_fq2);
 
@@ -272,7 +390,7 @@ mod test {
 
         let json = serde_json::to_string(&expect_g1).unwrap();
         println!("g1: {:?}", json);
-        let actual_g1: super::g1::G1Serde = serde_json::from_str(&json).unwrap();
+        let actual_g1: super::g1::G1Serde<Fq> = serde_json::from_str(&json).unwrap();
 
         assert_eq!(expect_g1, actual_g1);
 
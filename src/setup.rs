@@ -0,0 +1,288 @@
+use crate::prover::{PlonkCircuit, PlonkWitness, ProvingKey, Srs};
+use crate::vk::{SnarkJSVK, VerificationKey};
+use anyhow::ensure;
+use ark_ec::pairing::Pairing;
+use ark_ff::{FftField, Field};
+
+// The minimum power of the domain of snarkjs, so that T0, T1 and T2 fit in C1 and C2.
+const MIN_POWER: u8 = 3;
+
+/// A constraint of a PLONK circuit of snarkjs, on the signals `[sl, sr, so]`:
+///      ql·sl + qr·sr + qo·so + qm·sl·sr + qc = 0
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PlonkConstraint<F: Field> {
+    pub signals: [usize; 3],
+    pub qm: F,
+    pub ql: F,
+    pub qr: F,
+    pub qo: F,
+    pub qc: F,
+}
+
+impl<F: Field> PlonkConstraint<F> {
+    // The row of snarkjs reading the public signal `signal`: sl - PI = 0.
+    pub fn public_input(signal: usize) -> Self {
+        Self {
+            signals: [signal, 0, 0],
+            qm: F::zero(),
+            ql: F::one(),
+            qr: F::zero(),
+            qo: F::zero(),
+            qc: F::zero(),
+        }
+    }
+
+    // The padding row of snarkjs, on the signal 0.
+    pub fn padding() -> Self {
+        Self {
+            signals: [0; 3],
+            qm: F::zero(),
+            ql: F::zero(),
+            qr: F::zero(),
+            qo: F::zero(),
+            qc: F::zero(),
+        }
+    }
+}
+
+/// A circuit as `snarkjs fflonk setup` lays it out: the signal 0 is the constant 1, the signals
+/// 1..=n_public are the public ones, and every constraint is a row after the rows of the
+/// public signals.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SnarkJSCircuit<F: Field> {
+    pub n_vars: usize,
+    pub n_public: usize,
+    pub constraints: Vec<PlonkConstraint<F>>,
+}
+
+impl<F: FftField> SnarkJSCircuit<F> {
+    // The rows of the circuit: the public signals, then the constraints.
+    pub fn rows(&self) -> Vec<PlonkConstraint<F>> {
+        (1..=self.n_public)
+            .map(PlonkConstraint::public_input)
+            .chain(self.constraints.iter().cloned())
+            .collect()
+    }
+
+    // The power of the domain of snarkjs, which leaves at least 2 rows after the constraints:
+    //      max(3, log2(rows + 1) + 1)
+    pub fn power(&self) -> u8 {
+        let rows = self.n_public + self.constraints.len();
+        MIN_POWER.max((usize::BITS - (rows + 1).leading_zeros()) as u8)
+    }
+
+    /// The PLONK circuit with the selectors and the permutation of snarkjs.
+    ///
+    /// The rows after the constraints are on the signal 0, except the last 2 whose wires are
+    /// left alone: every signal is a cycle through its wires in the order of the rows, from a
+    /// to c.
+    pub fn plonk_circuit(&self) -> anyhow::Result<PlonkCircuit<F>> {
+        let power = self.power();
+        let n = 1 << power;
+        let (k1, k2) = compute_k1_k2::<F>(power);
+
+        let mut rows = self.rows();
+        rows.resize(n - 2, PlonkConstraint::padding());
+        let selector = |select: fn(&PlonkConstraint<F>) -> F| {
+            let mut selector: Vec<F> = rows.iter().map(select).collect();
+            selector.resize(n, F::zero());
+            selector
+        };
+
+        // permutation[wire] is the wire of the same signal before it, and the first wire of a
+        // signal is sent to the last one.
+        let mut permutation: Vec<usize> = (0..3 * n).collect();
+        let mut first_wire = vec![None; self.n_vars];
+        let mut last_wire = vec![0; self.n_vars];
+        for (row, constraint) in rows.iter().enumerate() {
+            for (column, &signal) in constraint.signals.iter().enumerate() {
+                ensure!(
+                    signal < self.n_vars,
+                    "The signal {signal} of the row {row} isn't one of the {} signals",
+                    self.n_vars
+                );
+                let wire = column * n + row;
+                match first_wire[signal] {
+                    None => first_wire[signal] = Some(wire),
+                    Some(_) => permutation[wire] = last_wire[signal],
+                }
+                last_wire[signal] = wire;
+            }
+        }
+        for (signal, first) in first_wire.iter().enumerate() {
+            match first {
+                Some(first) => permutation[*first] = last_wire[signal],
+                None => anyhow::bail!("The signal {signal} isn't used"),
+            }
+        }
+
+        Ok(PlonkCircuit {
+            power,
+            k1,
+            k2,
+            ql: selector(|row| row.ql),
+            qr: selector(|row| row.qr),
+            qo: selector(|row| row.qo),
+            qm: selector(|row| row.qm),
+            qc: selector(|row| row.qc),
+            permutation,
+        })
+    }
+
+    /// The wires of the rows for the values of the signals, with `signals[0] = 1`.
+    pub fn witness(&self, signals: &[F]) -> anyhow::Result<PlonkWitness<F>> {
+        ensure!(
+            signals.len() == self.n_vars,
+            "The witness has {} signals instead of {}",
+            signals.len(),
+            self.n_vars
+        );
+        let n = 1 << self.power();
+        let mut rows = self.rows();
+        rows.resize(n, PlonkConstraint::padding());
+        let wire = |column: usize| {
+            rows.iter()
+                .map(|row| signals[row.signals[column]])
+                .collect()
+        };
+
+        Ok(PlonkWitness {
+            a: wire(0),
+            b: wire(1),
+            c: wire(2),
+        })
+    }
+}
+
+// The k1 and k2 of snarkjs: the smallest ones from 2 such that the domain, k1·domain and
+// k2·domain are disjoint cosets. They're 2 and 3 on BN254.
+pub fn compute_k1_k2<F: FftField>(power: u8) -> (F, F) {
+    let ks = compute_ks::<F>(power, 3);
+    (ks[1], ks[2])
+}
+
+// The coset shifts of `wires` wires, as snarkjs picks k1 and k2: 1, then the smallest ones from 2
+// such that the cosets of the domain are disjoint, eg: [1, k1, k2, k3] of `Arithmetization::four_wire`.
+pub fn compute_ks<F: FftField>(power: u8, wires: usize) -> Vec<F> {
+    let n = 1u64 << power;
+    // k is in the coset of k' if (k / k')^n = 1
+    let is_included = |k: F, cosets: &[F]| {
+        cosets
+            .iter()
+            .any(|coset| (k * coset.inverse().unwrap()).pow([n]).is_one())
+    };
+
+    let mut ks = vec![F::one()];
+    let mut k = F::one();
+    while ks.len() < wires {
+        k += F::one();
+        if !is_included(k, &ks) {
+            ks.push(k);
+        }
+    }
+    ks.truncate(wires);
+    ks
+}
+
+/// `snarkjs fflonk setup` of the `circuit` over the powers of tau `srs`: the proving key, whose
+/// `vk` and `SnarkJSVK::from(&pk.vk)` are the verification key of snarkjs.
+///
+/// The SRS needs the 8·2^power powers of C0, or `Srs::size_for(power)` to prove with the key.
+pub fn fflonk_setup<E: Pairing>(
+    circuit: &SnarkJSCircuit<E::ScalarField>,
+    srs: &Srs<E>,
+) -> anyhow::Result<ProvingKey<E>> {
+    ProvingKey::new(&circuit.plonk_circuit()?, srs)
+}
+
+/// `fflonk_setup` with both verification keys only.
+pub fn fflonk_setup_vk<E: Pairing>(
+    circuit: &SnarkJSCircuit<E::ScalarField>,
+    srs: &Srs<E>,
+) -> anyhow::Result<(VerificationKey<E>, SnarkJSVK<E>)> {
+    let vk = fflonk_setup(circuit, srs)?.vk;
+    let snarkjs_vk = SnarkJSVK::from(&vk);
+    Ok((vk, snarkjs_vk))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::multiplier2;
+    use crate::prover::fflonk_prover;
+    use crate::transcript::Keccak256TranscriptHash;
+    use crate::verifier::fflonk_verifier;
+    use ark_bn254::{Bn254, Fr};
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_plonk_circuit() {
+        let circuit = multiplier2::<Fr>();
+        assert_eq!(circuit.power(), 3);
+        let plonk = circuit.plonk_circuit().unwrap();
+        plonk.validate().unwrap();
+        assert_eq!((plonk.k1, plonk.k2), (Fr::from(2u64), Fr::from(3u64)));
+
+        // the signal 0: b_0, c_0, the padding rows 2..6 of every wire, and b_1 is the signal 3
+        let n = 8;
+        assert_eq!(plonk.permutation[0], 2 * n + 1);
+        assert_eq!(plonk.permutation[2 * n + 1], 0);
+        assert_eq!(plonk.permutation[n + 1], n + 1);
+        assert_eq!(plonk.permutation[n + 6], n + 6);
+        assert_eq!(plonk.permutation[2 * n + 7], 2 * n + 7);
+        assert_eq!(plonk.permutation[n], 2 * n + 5);
+        assert_eq!(plonk.permutation[2 * n], n);
+
+        // 1 + 13 rows and the last 2 fit in 2^4 rows, but not 1 + 14
+        let constraints = |rows: usize| SnarkJSCircuit {
+            constraints: vec![circuit.constraints[0].clone(); rows],
+            ..circuit.clone()
+        };
+        assert_eq!(constraints(13).power(), 4);
+        assert_eq!(constraints(14).power(), 5);
+
+        let unused = SnarkJSCircuit {
+            n_vars: 5,
+            ..circuit.clone()
+        };
+        assert!(unused.plonk_circuit().is_err());
+    }
+
+    #[test]
+    fn test_fflonk_setup() {
+        let rng = &mut ark_std::test_rng();
+        let circuit = multiplier2::<Fr>();
+        let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), Srs::<Bn254>::size_for(3));
+        let pk = fflonk_setup(&circuit, &srs).unwrap();
+        let (vk, snarkjs_vk) = fflonk_setup_vk(&circuit, &srs).unwrap();
+        assert_eq!(VerificationKey::from(snarkjs_vk), vk);
+
+        let signals = [1u64, 33, 3, 11].map(Fr::from);
+        let witness = circuit.witness(&signals).unwrap();
+        let proof = fflonk_prover::<_, Keccak256TranscriptHash, _>(
+            &pk,
+            &srs,
+            &witness,
+            &Fr::from(33u64),
+            rng,
+        )
+        .unwrap();
+        assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+            &vk,
+            &proof,
+            &Fr::from(33u64),
+            false
+        ));
+
+        let signals = [1u64, 34, 3, 11].map(Fr::from);
+        let witness = circuit.witness(&signals).unwrap();
+        assert!(fflonk_prover::<_, Keccak256TranscriptHash, _>(
+            &pk,
+            &srs,
+            &witness,
+            &Fr::from(34u64),
+            rng
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use crate::challenge::Challenges;
+use crate::inversion::Inversion;
+use crate::opening::{compute_opening_fej, OpeningChallenges, OpeningProof, OpeningSetConfig};
+use crate::pairing::DesignatedVerifierBackend;
+use crate::proof::{Evaluations, Polynomials, Proof};
+use crate::setup::compute_k1_k2;
+use crate::transcript::TranscriptHash;
+use crate::verifier::compute_a1_with_challenges;
+use crate::vk::{Omega, VerificationKey};
+use anyhow::anyhow;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{Field, One, Zero};
+use ark_std::rand::RngCore;
+use ark_std::UniformRand;
+
+/// The near-valid proofs of `Simulator::forge`, each failing a single step of the verifier.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Forgery {
+    /// The transcript and the inverse check out, but W2 is off: only the pairing check fails.
+    WrongPairing,
+    /// W2 passes the pairing check, but the inverse `inv` is off: only the inverse check fails.
+    WrongInverse,
+}
+
+impl Forgery {
+    pub const ALL: [Forgery; 2] = [Forgery::WrongPairing, Forgery::WrongInverse];
+}
+
+/// A proof simulator for the tests, who knows the trapdoor x of X_2 = x·[1]_2: it doesn't need
+/// a circuit nor a witness, any commitments and evaluations pass the verifier once the inverse
+/// is set and W2 is solved from the pairing check:
+///      A1 = F - E - J + y·W2 = x·W2  ⇔  W2 = (F - E - J) / (x - y)
+///
+/// Never use it beyond the tests: the proofs it simulates prove nothing. It's only built for the
+/// tests, or with the feature `test-utils`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulator<E: Pairing = Bn254> {
+    pub vk: VerificationKey<E>,
+    pub trapdoor: E::ScalarField,
+}
+
+impl<E: Pairing> Simulator<E> {
+    /// The verification key of a 2^power domain with X_2 = trapdoor·[1]_2 and a random C0.
+    ///
+    /// Fails if the domain is larger than the 2-adicity of the scalar field.
+    pub fn new<R: RngCore>(
+        power: u8,
+        trapdoor: E::ScalarField,
+        rng: &mut R,
+    ) -> anyhow::Result<Self> {
+        let omega = Omega::new(power)?;
+        let (k1, k2) = compute_k1_k2(power);
+        let vk = VerificationKey {
+            power,
+            n: E::ScalarField::from(1u64 << power),
+            k1,
+            k2,
+            x2: (E::G2Affine::generator() * trapdoor).into_affine(),
+            c0: random_g1::<E, _>(rng).into_affine(),
+            g1: E::G1Affine::generator(),
+            g2: E::G2Affine::generator(),
+            omega,
+        };
+        Ok(Self { vk, trapdoor })
+    }
+
+    /// Simulate the proofs of an existing verification key, whose trapdoor is known.
+    pub fn from_vk(vk: VerificationKey<E>, trapdoor: E::ScalarField) -> anyhow::Result<Self> {
+        DesignatedVerifierBackend::new(&vk, trapdoor)?;
+        Ok(Self { vk, trapdoor })
+    }
+
+    /// Random commitments and evaluations, with the inverse and W2 left for `complete`.
+    pub fn random_proof<R: RngCore>(&self, rng: &mut R) -> Proof<E> {
+        let mut rand_fr = || E::ScalarField::rand(rng);
+        let evaluations = Evaluations {
+            ql: rand_fr(),
+            qr: rand_fr(),
+            qm: rand_fr(),
+            qo: rand_fr(),
+            qc: rand_fr(),
+            s1: rand_fr(),
+            s2: rand_fr(),
+            s3: rand_fr(),
+            a: rand_fr(),
+            b: rand_fr(),
+            c: rand_fr(),
+            z: rand_fr(),
+            zw: rand_fr(),
+            t1w: rand_fr(),
+            t2w: rand_fr(),
+            inv: E::ScalarField::zero(),
+        };
+        Proof {
+            polynomials: Polynomials {
+                c1: random_g1::<E, _>(rng),
+                c2: random_g1::<E, _>(rng),
+                w1: random_g1::<E, _>(rng),
+                w2: E::G1::zero(),
+            },
+            evaluations,
+        }
+    }
+
+    /// A proof of random commitments and evaluations which passes the verifier for `pub_input`.
+    pub fn simulate<T: TranscriptHash, R: RngCore>(
+        &self,
+        pub_input: &E::ScalarField,
+        rng: &mut R,
+    ) -> anyhow::Result<Proof<E>> {
+        self.complete::<T>(self.random_proof(rng), pub_input)
+    }
+
+    /// Set the inverse and W2 of the `proof`, so that its commitments and evaluations, whatever
+    /// they are, pass the verifier for `pub_input`.
+    pub fn complete<T: TranscriptHash>(
+        &self,
+        proof: Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> anyhow::Result<Proof<E>> {
+        let challenges = Challenges::compute::<T>(&self.vk, &proof, pub_input);
+        self.complete_with_challenges(proof, pub_input, &challenges)
+    }
+
+    /// Same as `complete`, with the challenges of another profile or context: neither the
+    /// inverse nor W2 are absorbed by the transcript, so they don't change the challenges.
+    pub fn complete_with_challenges(
+        &self,
+        mut proof: Proof<E>,
+        pub_input: &E::ScalarField,
+        challenges: &Challenges<E>,
+    ) -> anyhow::Result<Proof<E>> {
+        let (den_h1_base, den_h2_base, li_s0, li_s1, li_s2, eval_l1_base) =
+            Inversion::compute_denominators(&self.vk, challenges);
+        let accumulator = Inversion::<E>::accumulator(
+            &den_h1_base,
+            &den_h2_base,
+            &challenges.zh,
+            &li_s0,
+            &li_s1,
+            &li_s2,
+            &eval_l1_base,
+        );
+        proof.evaluations.inv = accumulator
+            .last()
+            .unwrap()
+            .inverse()
+            .ok_or_else(|| anyhow!("A denominator of the verifier is zero"))?;
+
+        // with W2 = 0, A1 is F - E - J
+        proof.polynomials.w2 = E::G1::zero();
+        let a1 = compute_a1_with_challenges(&self.vk, &proof, pub_input, challenges)
+            .ok_or_else(|| anyhow!("The inverse doesn't check out"))?;
+        let x_minus_y = (self.trapdoor - challenges.y)
+            .inverse()
+            .ok_or_else(|| anyhow!("The challenge y is the trapdoor"))?;
+        // normalize, so that W2 is written as [x, y, 1]
+        proof.polynomials.w2 = (a1 * x_minus_y).into_affine().into_group();
+        Ok(proof)
+    }
+
+    /// Set W2 of the `opening`, so that its commitments and evaluations, whatever they are, pass
+    /// `opening::verify_openings` for the `config` and the `seed`.
+    pub fn open<T: TranscriptHash>(
+        &self,
+        config: &OpeningSetConfig,
+        opening: &mut OpeningProof<E>,
+        seed: &E::ScalarField,
+    ) -> anyhow::Result<()> {
+        let challenges = OpeningChallenges::compute::<T>(&self.vk, opening, seed);
+        let fej = compute_opening_fej(config, &self.vk, opening, &challenges)?
+            .ok_or_else(|| anyhow!("A denominator of the verifier is zero"))?;
+        let x_minus_y = (self.trapdoor - challenges.y)
+            .inverse()
+            .ok_or_else(|| anyhow!("The challenge y is the trapdoor"))?;
+        opening.w2 = ((fej.F.into_group() - fej.E - fej.J) * x_minus_y).into_affine();
+        Ok(())
+    }
+
+    /// A simulated proof for `pub_input` turned into the given `forgery`.
+    pub fn forge<T: TranscriptHash, R: RngCore>(
+        &self,
+        forgery: Forgery,
+        pub_input: &E::ScalarField,
+        rng: &mut R,
+    ) -> anyhow::Result<Proof<E>> {
+        let mut proof = self.simulate::<T, _>(pub_input, rng)?;
+        match forgery {
+            Forgery::WrongPairing => proof.polynomials.w2 += self.vk.g1,
+            Forgery::WrongInverse => proof.evaluations.inv += E::ScalarField::one(),
+        }
+        Ok(proof)
+    }
+}
+
+fn random_g1<E: Pairing, R: RngCore>(rng: &mut R) -> E::G1 {
+    // normalize, so that the point is written as [x, y, 1]
+    (E::G1Affine::generator() * E::ScalarField::rand(rng))
+        .into_affine()
+        .into_group()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pairing::ArkworksBackend;
+    use crate::transcript::{FiatShamirProfile, Keccak256TranscriptHash};
+    use crate::verifier::{
+        fflonk_designated_verifier, fflonk_verifier, fflonk_verifier_with_backend,
+        fflonk_verifier_with_profile,
+    };
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Fr;
+
+    fn test_simulator_for<E: Pairing>() {
+        let rng = &mut ark_std::test_rng();
+        let trapdoor = E::ScalarField::rand(rng);
+        let simulator = Simulator::<E>::new(3, trapdoor, rng).unwrap();
+        assert_eq!(
+            (simulator.vk.k1, simulator.vk.k2),
+            (E::ScalarField::from(2u64), E::ScalarField::from(3u64))
+        );
+
+        for _ in 0..4 {
+            let pub_input = E::ScalarField::rand(rng);
+            let proof = simulator
+                .simulate::<Keccak256TranscriptHash, _>(&pub_input, rng)
+                .unwrap();
+            assert!(
+                fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+                    &ArkworksBackend,
+                    &simulator.vk,
+                    &proof,
+                    &pub_input
+                )
+            );
+            assert!(fflonk_designated_verifier::<_, Keccak256TranscriptHash>(
+                trapdoor,
+                &simulator.vk,
+                &proof,
+                &pub_input
+            )
+            .unwrap());
+            assert!(
+                !fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+                    &ArkworksBackend,
+                    &simulator.vk,
+                    &proof,
+                    &(pub_input + E::ScalarField::one())
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulator() {
+        test_simulator_for::<Bn254>();
+        test_simulator_for::<Bls12_381>();
+    }
+
+    #[test]
+    fn test_simulator_forgeries() {
+        let rng = &mut ark_std::test_rng();
+        let simulator = Simulator::<Bn254>::new(4, Fr::rand(rng), rng).unwrap();
+        let pub_input = Fr::from(33u64);
+
+        for forgery in Forgery::ALL {
+            let proof = simulator
+                .forge::<Keccak256TranscriptHash, _>(forgery, &pub_input, rng)
+                .unwrap();
+            assert!(!fflonk_verifier::<Keccak256TranscriptHash>(
+                &simulator.vk,
+                &proof,
+                &pub_input,
+                false
+            ));
+            let challenges =
+                Challenges::compute::<Keccak256TranscriptHash>(&simulator.vk, &proof, &pub_input);
+            let inverse = Inversion::check_inverse(&simulator.vk, &proof, &challenges);
+            assert_eq!(inverse, forgery == Forgery::WrongPairing);
+
+            // undone, the forgery passes
+            let mut fixed = proof;
+            match forgery {
+                Forgery::WrongPairing => fixed.polynomials.w2 -= simulator.vk.g1,
+                Forgery::WrongInverse => fixed.evaluations.inv -= Fr::one(),
+            }
+            assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+                &simulator.vk,
+                &fixed,
+                &pub_input,
+                false
+            ));
+        }
+    }
+
+    #[test]
+    fn test_simulator_given_proof() {
+        let rng = &mut ark_std::test_rng();
+        let trapdoor = Fr::rand(rng);
+        let vk = VerificationKey {
+            x2: (ark_bn254::G2Affine::generator() * trapdoor).into_affine(),
+            ..Default::default()
+        };
+        assert!(Simulator::from_vk(vk.clone(), trapdoor + Fr::one()).is_err());
+        let simulator = Simulator::from_vk(vk, trapdoor).unwrap();
+
+        // chosen evaluations, under the strong profile
+        let pub_input = Fr::rand(rng);
+        let mut proof = simulator.random_proof(rng);
+        proof.evaluations.a = Fr::zero();
+        proof.evaluations.z = Fr::one();
+        let profile = FiatShamirProfile::Strong;
+        let challenges = Challenges::compute_with_profile::<Keccak256TranscriptHash>(
+            profile,
+            &simulator.vk,
+            &proof,
+            &pub_input,
+        );
+        let proof = simulator
+            .complete_with_challenges(proof, &pub_input, &challenges)
+            .unwrap();
+        assert_eq!(
+            (proof.evaluations.a, proof.evaluations.z),
+            (Fr::zero(), Fr::one())
+        );
+        assert!(fflonk_verifier_with_profile::<Keccak256TranscriptHash>(
+            profile,
+            &simulator.vk,
+            &proof,
+            &pub_input,
+            false
+        ));
+        assert!(!fflonk_verifier::<Keccak256TranscriptHash>(
+            &simulator.vk,
+            &proof,
+            &pub_input,
+            false
+        ));
+    }
+}
@@ -0,0 +1,410 @@
+use crate::challenge::Challenges;
+use crate::compute_fej::FEJ;
+use crate::compute_r::compute_r;
+use crate::curve::{field_to_bytes_be, point_to_bytes_be, SWPoint};
+use crate::inversion::Inversion;
+use crate::pairing::PairingBackend;
+use crate::proof::Proof;
+use crate::transcript::{TranscriptHash, STATE_INPUTS_DOMAIN};
+use crate::utils::{compute_a1, compute_pi, LangrangePolynomialEvaluation};
+use crate::vk::VerificationKey;
+use anyhow::ensure;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// `fflonk_verifier` as a state machine, for the environments with a per-step compute limit
+/// (eg: chunked on-chain execution or BitVM scripts): every step is run by `step`, and the state
+/// in between can be serialized, so that the verification is split across processes and resumed.
+///
+/// Every variant is named after the step it runs next and holds what the later steps need:
+///      Challenges → Inversion → PublicInput → R → FEJ → A1 → Pairing → Done
+///
+/// The state doesn't hold the verification key, the proof and the public input: the caller
+/// passes them to every step. Every state after `Challenges` holds the `inputs` digest of them
+/// and of the transcript hash, see `VerifierState::inputs_digest`, and `step` rejects the state
+/// for other inputs: a state can't be resumed with another proof than the one it was computed for.
+/// The digest doesn't authenticate the state itself: a state from an untrusted party must be
+/// checked, eg: by `dispute::find_dispute`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "step", bound = "E::G1: SWPoint<BaseField = E::BaseField>")]
+pub enum VerifierState<E: Pairing = Bn254> {
+    // 1. compute the challenges, with the transcript hash of `step`
+    Challenges,
+    // 2. compute the inverses, checking the one sent by the prover
+    Inversion {
+        #[serde(with = "crate::serde::fr")]
+        inputs: E::ScalarField,
+        challenges: Challenges<E>,
+    },
+    // 3-4. compute L1(xi) and PI(xi)
+    PublicInput {
+        #[serde(with = "crate::serde::fr")]
+        inputs: E::ScalarField,
+        challenges: Challenges<E>,
+        inversion: Inversion<E>,
+    },
+    // 5. compute r0(y), r1(y) and r2(y)
+    R {
+        #[serde(with = "crate::serde::fr")]
+        inputs: E::ScalarField,
+        challenges: Challenges<E>,
+        inversion: Inversion<E>,
+        #[serde(with = "crate::serde::fr")]
+        l1: E::ScalarField,
+        #[serde(with = "crate::serde::fr")]
+        pi: E::ScalarField,
+    },
+    // 6. compute [F]_1, [E]_1 and [J]_1
+    FEJ {
+        #[serde(with = "crate::serde::fr")]
+        inputs: E::ScalarField,
+        challenges: Challenges<E>,
+        inversion: Inversion<E>,
+        #[serde(with = "crate::serde::fr_array")]
+        r: [E::ScalarField; 3],
+    },
+    // 7. compute A1 = F - E - J + y·W2
+    A1 {
+        #[serde(with = "crate::serde::fr")]
+        inputs: E::ScalarField,
+        challenges: Challenges<E>,
+        fej: FEJ<E>,
+    },
+    // 8. check e(A1, [1]_2)·e(W2, -[x]_2) = 1
+    Pairing {
+        #[serde(with = "crate::serde::fr")]
+        inputs: E::ScalarField,
+        #[serde(with = "crate::serde::g1_affine")]
+        a1: E::G1Affine,
+    },
+    Done {
+        #[serde(with = "crate::serde::fr")]
+        inputs: E::ScalarField,
+        verified: bool,
+    },
+}
+
+impl<E: Pairing> Default for VerifierState<E> {
+    fn default() -> Self {
+        Self::Challenges
+    }
+}
+
+impl<E: Pairing> VerifierState<E>
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+{
+    pub fn load<P: AsRef<Path>>(state_path: P) -> anyhow::Result<Self> {
+        let mut file = File::open(state_path)?;
+        let mut state_json = String::new();
+        file.read_to_string(&mut state_json)?;
+        Ok(serde_json::from_str(&state_json)?)
+    }
+}
+
+impl<E: Pairing> VerifierState<E> {
+    /// The verification result, once the last step has run.
+    pub fn result(&self) -> Option<bool> {
+        match self {
+            Self::Done { verified, .. } => Some(*verified),
+            _ => None,
+        }
+    }
+
+    /// The digest of the inputs of the state, `None` for the initial one.
+    pub fn inputs(&self) -> Option<E::ScalarField> {
+        match self {
+            Self::Challenges => None,
+            Self::Inversion { inputs, .. }
+            | Self::PublicInput { inputs, .. }
+            | Self::R { inputs, .. }
+            | Self::FEJ { inputs, .. }
+            | Self::A1 { inputs, .. }
+            | Self::Pairing { inputs, .. }
+            | Self::Done { inputs, .. } => Some(*inputs),
+        }
+    }
+
+    /// The digest of the inputs of the verification, held by every state after the first one:
+    ///      H(STATE_INPUTS_DOMAIN || vk.digest() || C1 || C2 || W1 || W2 || evaluations || pub_input)
+    /// with the transcript hash `T` as H, so that it binds the transcript as well.
+    pub fn inputs_digest<T: TranscriptHash>(
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> E::ScalarField {
+        let (polynomials, evaluations) = (&proof.polynomials, &proof.evaluations);
+        let points = [
+            polynomials.c1,
+            polynomials.c2,
+            polynomials.w1,
+            polynomials.w2,
+        ];
+        let scalars = [
+            vk.digest::<T>(),
+            evaluations.ql,
+            evaluations.qr,
+            evaluations.qm,
+            evaluations.qo,
+            evaluations.qc,
+            evaluations.s1,
+            evaluations.s2,
+            evaluations.s3,
+            evaluations.a,
+            evaluations.b,
+            evaluations.c,
+            evaluations.z,
+            evaluations.zw,
+            evaluations.t1w,
+            evaluations.t2w,
+            evaluations.inv,
+            *pub_input,
+        ];
+        let mut concatenated = STATE_INPUTS_DOMAIN.to_vec();
+        concatenated.extend(field_to_bytes_be(&scalars[0]));
+        for point in E::G1::normalize_batch(&points) {
+            concatenated.extend(point_to_bytes_be(&point));
+        }
+        for scalar in &scalars[1..] {
+            concatenated.extend(field_to_bytes_be(scalar));
+        }
+        T::hash_to_fr(concatenated)
+    }
+
+    /// Run the next step. An inconsistent key or a wrong inverse sent by the prover ends the
+    /// verification at the inversion step, and `Done` is left as it is.
+    ///
+    /// Only the challenges depend on the transcript hash `T`, and only the pairing check on
+    /// the `backend`, see `fflonk_verifier_with_backend`.
+    ///
+    /// Fails if the state was computed for other inputs, see `VerifierState::inputs_digest`.
+    pub fn step<T: TranscriptHash, B: PairingBackend<E>>(
+        self,
+        backend: &B,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> anyhow::Result<Self> {
+        let inputs = Self::inputs_digest::<T>(vk, proof, pub_input);
+        ensure!(
+            self.inputs()
+                .map_or(true, |state_inputs| state_inputs == inputs),
+            "The state isn't the one of the verification key, proof, public input and transcript"
+        );
+
+        Ok(match self {
+            Self::Challenges => Self::Inversion {
+                inputs,
+                challenges: Challenges::compute::<T>(vk, proof, pub_input),
+            },
+            Self::Inversion { challenges, .. } => match vk
+                .is_consistent()
+                .then(|| Inversion::try_build(vk, proof, &challenges))
+                .flatten()
+            {
+                Some(inversion) => Self::PublicInput {
+                    inputs,
+                    challenges,
+                    inversion,
+                },
+                // the key is inconsistent, or the provided inverse in the proof is wrong
+                None => Self::Done {
+                    inputs,
+                    verified: false,
+                },
+            },
+            Self::PublicInput {
+                challenges,
+                inversion,
+                ..
+            } => {
+                let l1 = LangrangePolynomialEvaluation::compute_L1_polynomial_evaluation(
+                    &challenges.zh,
+                    &inversion.eval_l1,
+                );
+                Self::R {
+                    inputs,
+                    challenges,
+                    inversion,
+                    l1,
+                    pi: compute_pi(&[*pub_input], &[l1]),
+                }
+            }
+            Self::R {
+                challenges,
+                inversion,
+                l1,
+                pi,
+                ..
+            } => {
+                let (r0, r1, r2) = compute_r(vk, proof, &challenges, &inversion, &l1, &pi);
+                Self::FEJ {
+                    inputs,
+                    challenges,
+                    inversion,
+                    r: [r0, r1, r2],
+                }
+            }
+            Self::FEJ {
+                challenges,
+                inversion,
+                r: [r0, r1, r2],
+                ..
+            } => Self::A1 {
+                inputs,
+                challenges,
+                fej: FEJ::compute(vk, proof, &challenges, &inversion, r0, r1, r2),
+            },
+            Self::A1 {
+                challenges, fej, ..
+            } => Self::Pairing {
+                inputs,
+                a1: compute_a1(proof, &fej, &challenges),
+            },
+            Self::Pairing { a1, .. } => Self::Done {
+                inputs,
+                verified: backend.check_pairing(vk, &a1, &proof.polynomials.w2.into_affine()),
+            },
+            done @ Self::Done { .. } => done,
+        })
+    }
+
+    /// Run the remaining steps at once.
+    pub fn run<T: TranscriptHash, B: PairingBackend<E>>(
+        mut self,
+        backend: &B,
+        vk: &VerificationKey<E>,
+        proof: &Proof<E>,
+        pub_input: &E::ScalarField,
+    ) -> anyhow::Result<bool> {
+        loop {
+            if let Some(verified) = self.result() {
+                // the inputs of the final state are checked as well
+                self.step::<T, B>(backend, vk, proof, pub_input)?;
+                return Ok(verified);
+            }
+            self = self.step::<T, B>(backend, vk, proof, pub_input)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+    use crate::pairing::{ArkworksBackend, OnProvingPairingsBackend};
+    use crate::transcript::{Blake3TranscriptHash, Keccak256TranscriptHash};
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::One;
+    use std::str::FromStr;
+
+    // Every step in a "new process": the state goes through JSON in between.
+    fn run_resumed(vk: &VerificationKey, proof: &Proof, pub_input: &Fr) -> (bool, usize) {
+        let mut state = VerifierState::default();
+        let mut steps = 0;
+        while state.result().is_none() {
+            let json = serde_json::to_string(&state).unwrap();
+            let resumed: VerifierState = serde_json::from_str(&json).unwrap();
+            assert_eq!(resumed, state);
+
+            state = resumed
+                .step::<Keccak256TranscriptHash, _>(&ArkworksBackend, vk, proof, pub_input)
+                .unwrap();
+            steps += 1;
+        }
+        (state.result().unwrap(), steps)
+    }
+
+    #[test]
+    fn test_verifier_state() {
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let vk = VerificationKey::default();
+
+        assert_eq!(run_resumed(&vk, &proof, &pub_input), (true, 7));
+        assert!(VerifierState::default()
+            .run::<Keccak256TranscriptHash, _>(&OnProvingPairingsBackend, &vk, &proof, &pub_input)
+            .unwrap());
+
+        // W2 isn't absorbed by the transcript: a wrong one only fails the pairing check
+        let mut wrong = proof.clone();
+        wrong.polynomials.w2 += G1Affine::generator();
+        assert_eq!(run_resumed(&vk, &wrong, &pub_input), (false, 7));
+
+        // a wrong inverse stops at the inversion
+        let mut wrong = proof.clone();
+        wrong.evaluations.inv += Fr::one();
+        assert_eq!(run_resumed(&vk, &wrong, &pub_input), (false, 2));
+
+        let inputs =
+            VerifierState::inputs_digest::<Keccak256TranscriptHash>(&vk, &proof, &pub_input);
+        let done = VerifierState::<Bn254>::Done {
+            inputs,
+            verified: true,
+        };
+        assert_eq!(
+            done.clone()
+                .step::<Keccak256TranscriptHash, _>(&ArkworksBackend, &vk, &proof, &pub_input)
+                .unwrap(),
+            done
+        );
+    }
+
+    #[test]
+    fn test_verifier_state_inputs() {
+        let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+        let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+        let vk = VerificationKey::default();
+
+        // a state computed for the proof, up to the pairing check
+        let mut state = VerifierState::default();
+        for _ in 0..6 {
+            state = state
+                .step::<Keccak256TranscriptHash, _>(&ArkworksBackend, &vk, &proof, &pub_input)
+                .unwrap();
+        }
+        assert!(matches!(state, VerifierState::Pairing { .. }));
+
+        // it can't be resumed with another proof, public input, key or transcript
+        let mut wrong_proof = proof.clone();
+        wrong_proof.polynomials.w2 += G1Affine::generator();
+        let mut wrong_vk = vk.clone();
+        wrong_vk.k1 += Fr::one();
+        assert!(state
+            .clone()
+            .step::<Keccak256TranscriptHash, _>(&ArkworksBackend, &vk, &wrong_proof, &pub_input)
+            .is_err());
+        assert!(state
+            .clone()
+            .step::<Keccak256TranscriptHash, _>(
+                &ArkworksBackend,
+                &vk,
+                &proof,
+                &(pub_input + Fr::one())
+            )
+            .is_err());
+        assert!(state
+            .clone()
+            .step::<Keccak256TranscriptHash, _>(&ArkworksBackend, &wrong_vk, &proof, &pub_input)
+            .is_err());
+        assert!(state
+            .clone()
+            .run::<Blake3TranscriptHash, _>(&ArkworksBackend, &vk, &proof, &pub_input)
+            .is_err());
+
+        // nor can its result
+        let done = state
+            .step::<Keccak256TranscriptHash, _>(&ArkworksBackend, &vk, &proof, &pub_input)
+            .unwrap();
+        assert_eq!(done.result(), Some(true));
+        assert!(done
+            .run::<Keccak256TranscriptHash, _>(&ArkworksBackend, &vk, &wrong_proof, &pub_input)
+            .is_err());
+    }
+}
@@ -1,11 +1,21 @@
 use std::str::FromStr;
 
+use crate::challenge::Challenges;
 use crate::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+use crate::pairing::{
+    randomize_pairs, verify_batch_pairing_artifact, verify_pairing_artifact, ArkworksBackend,
+    DesignatedVerifierBackend, EvmPrecompileBackend, OnProvingPairingsBackend, PairingArtifact,
+};
 use crate::proof::Proof;
-use crate::transcript::Keccak256TranscriptHash;
-use crate::verifier::fflonk_verifier;
+use crate::transcript::{Blake3TranscriptHash, FiatShamirProfile, Keccak256TranscriptHash};
+use crate::verifier::{
+    fflonk_batch_verifier_with_pairing_artifact, fflonk_verifier, fflonk_verifier_with_backend,
+    fflonk_verifier_with_challenges, fflonk_verifier_with_context,
+    fflonk_verifier_with_pairing_artifact,
+};
 use crate::vk::{SnarkJSVK, VerificationKey};
-use ark_bn254::Fr;
+use ark_bn254::{Bn254, Fr, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
 
 #[test]
 fn test_fflonk_verifier() {
@@ -24,6 +34,74 @@ fn test_fflonk_verifier() {
     );
 }
 
+#[test]
+fn test_fflonk_verifier_with_context() {
+    let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+    let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+    let vk = VerificationKey::default();
+
+    let verify = |context: &[u8]| {
+        fflonk_verifier_with_context::<Keccak256TranscriptHash>(
+            FiatShamirProfile::SnarkJS,
+            context,
+            &vk,
+            &proof,
+            &pub_input,
+            false,
+        )
+    };
+
+    // The mock proof is produced without context, which is the empty one.
+    assert!(verify(b""));
+    // So the same proof fails under any other context.
+    assert!(!verify(b"chain-id:1"));
+    assert!(!verify(b"chain-id:2"));
+    assert!(!verify(&[0u8; 20]));
+}
+
+#[test]
+fn test_fflonk_verifier_with_challenges() {
+    let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+    let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+    let vk = VerificationKey::default();
+
+    // replay the challenge values, as if they were taken from a Solidity trace.
+    let expect = Challenges::compute::<Keccak256TranscriptHash>(&vk, &proof, &pub_input);
+    let xi_seed = Challenges::<Bn254>::compute_xiseed::<Keccak256TranscriptHash>(
+        &expect.gamma,
+        proof.polynomials.c2.into_affine(),
+    );
+    let challenges = Challenges::from_values(
+        &vk,
+        expect.beta,
+        expect.gamma,
+        xi_seed,
+        expect.alpha,
+        expect.y,
+    );
+    assert_eq!(challenges, expect);
+    assert!(fflonk_verifier_with_challenges(
+        &vk,
+        &proof,
+        &pub_input,
+        &challenges,
+        false
+    ));
+
+    // alpha isn't involved in the inverse, so it's the pairing (algebra) that fails.
+    let mut wrong = challenges;
+    wrong.alpha += Fr::from(1);
+    assert!(!fflonk_verifier_with_challenges(
+        &vk, &proof, &pub_input, &wrong, false
+    ));
+
+    // the challenges of another transcript fails the inverse check.
+    let blake3 = Challenges::compute::<Blake3TranscriptHash>(&vk, &proof, &pub_input);
+    assert!(!fflonk_verifier_with_challenges(
+        &vk, &proof, &pub_input, &blake3, false
+    ));
+}
+
 #[test]
 fn test_verify_snarkjs_fflonk_proof() {
     let proof: Proof = serde_json::from_str(r#"
@@ -115,5 +193,134 @@ fn test_verify_snarkjs_fflonk_proof() {
     )
     .unwrap();
 
-    fflonk_verifier::<Keccak256TranscriptHash>(&vk, &proof, &pubs, false);
+    assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+        &vk, &proof, &pubs, false
+    ));
+}
+
+#[test]
+fn test_fflonk_verifier_with_pairing_artifact() {
+    let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+    let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+    let vk = VerificationKey::default();
+
+    let (a1, artifact) =
+        fflonk_verifier_with_pairing_artifact::<Keccak256TranscriptHash>(&vk, &proof, &pub_input)
+            .expect("Proof verification failed!");
+
+    // the standalone check only needs A1, W2, the vk and the artifact.
+    let w2 = proof.polynomials.w2.into_affine();
+    let json = serde_json::to_string(&artifact).unwrap();
+    let artifact: PairingArtifact = serde_json::from_str(&json).unwrap();
+    assert!(verify_pairing_artifact(&vk, &a1, &w2, &artifact));
+
+    assert!(
+        fflonk_verifier_with_pairing_artifact::<Keccak256TranscriptHash>(
+            &vk,
+            &proof,
+            &(pub_input + Fr::from(1u64))
+        )
+        .is_none()
+    );
+
+    // W2 isn't part of the transcript: only the pairing fails, before any witness is proved
+    let mut wrong_w2 = proof.clone();
+    wrong_w2.polynomials.w2 += G1Affine::generator();
+    assert!(
+        fflonk_verifier_with_pairing_artifact::<Keccak256TranscriptHash>(
+            &vk, &wrong_w2, &pub_input
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn test_fflonk_batch_verifier_with_pairing_artifact() {
+    let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+    let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+    let vk = VerificationKey::default();
+
+    let proofs = vec![(proof.clone(), pub_input); 3];
+    let (a1s, artifact) =
+        fflonk_batch_verifier_with_pairing_artifact::<Keccak256TranscriptHash>(&vk, &proofs)
+            .expect("Proof verification failed!");
+
+    // the standalone check only needs the A1s, the W2s, the vk and the artifact.
+    let w2 = proof.polynomials.w2.into_affine();
+    let pairs = a1s.iter().map(|a1| (*a1, w2)).collect::<Vec<_>>();
+    let randomized = randomize_pairs::<Keccak256TranscriptHash>(&pairs);
+    assert!(verify_batch_pairing_artifact(
+        &(&vk).into(),
+        &randomized,
+        &artifact
+    ));
+
+    let mut proofs = proofs;
+    proofs[1].1 += Fr::from(1u64);
+    assert!(
+        fflonk_batch_verifier_with_pairing_artifact::<Keccak256TranscriptHash>(&vk, &proofs)
+            .is_none()
+    );
+}
+
+#[test]
+fn test_fflonk_verifier_with_backend() {
+    let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+    let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+    let vk = VerificationKey::default();
+
+    let verify = |backend: &dyn Fn(&Fr) -> bool| {
+        backend(&pub_input) && !backend(&(pub_input + Fr::from(1u64)))
+    };
+    assert!(verify(&|pubs| fflonk_verifier_with_backend::<
+        _,
+        Keccak256TranscriptHash,
+        _,
+    >(&ArkworksBackend, &vk, &proof, pubs)));
+    assert!(verify(&|pubs| fflonk_verifier_with_backend::<
+        _,
+        Keccak256TranscriptHash,
+        _,
+    >(
+        &OnProvingPairingsBackend,
+        &vk,
+        &proof,
+        pubs
+    )));
+    assert!(verify(&|pubs| fflonk_verifier_with_backend::<
+        _,
+        Keccak256TranscriptHash,
+        _,
+    >(
+        &EvmPrecompileBackend, &vk, &proof, pubs
+    )));
+
+    // the trapdoor of the snarkjs setup isn't known: a wrong one has no designated verifier, and
+    // the designated verifier of another key rejects.
+    assert!(DesignatedVerifierBackend::new(&vk, Fr::from(1u64)).is_err());
+    let mut other_vk = vk.clone();
+    other_vk.x2 = (vk.g2 * Fr::from(1u64)).into_affine();
+    let backend = DesignatedVerifierBackend::new(&other_vk, Fr::from(1u64)).unwrap();
+    assert!(
+        !fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+            &backend, &vk, &proof, &pub_input
+        )
+    );
+}
+
+#[test]
+fn test_proof_try_construct() {
+    let proof = Proof::<Bn254>::try_construct(&MOCK_PROOF_DATA).unwrap();
+    assert_eq!(proof, Proof::construct(MOCK_PROOF_DATA.to_vec()));
+
+    // a missing value, and not a decimal number
+    assert!(Proof::<Bn254>::try_construct(&MOCK_PROOF_DATA[..23]).is_err());
+    for (i, value) in [(0, ""), (7, "-1"), (23, "0x1")] {
+        let mut values = MOCK_PROOF_DATA;
+        values[i] = value;
+        assert!(
+            Proof::<Bn254>::try_construct(&values).is_err(),
+            "{i}: {value}"
+        );
+    }
 }
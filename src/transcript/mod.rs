@@ -1,36 +1,256 @@
-use ark_bn254::Fr;
+#[cfg(feature = "r1cs")]
+pub mod poseidon;
+
 use ark_ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use tiny_keccak::{Hasher, Keccak};
 
 pub trait TranscriptHash {
-    fn hash_to_fr(bytes: Vec<u8>) -> Fr;
+    // Hash the bytes into the scalar field F, by reducing the big-endian digest modulo its order.
+    fn hash_to_fr<F: PrimeField>(bytes: Vec<u8>) -> F;
 }
 
 pub struct Keccak256TranscriptHash;
 
 impl TranscriptHash for Keccak256TranscriptHash {
-    fn hash_to_fr(bytes: Vec<u8>) -> Fr {
-        println!("keccak_hash ");
+    fn hash_to_fr<F: PrimeField>(bytes: Vec<u8>) -> F {
         let mut hasher = Keccak::v256();
         hasher.update(&bytes);
 
         let mut out = [0u8; 32];
         hasher.finalize(&mut out);
 
-        Fr::from_be_bytes_mod_order(&out)
+        F::from_be_bytes_mod_order(&out)
     }
 }
 
 pub struct Blake3TranscriptHash;
 
 impl TranscriptHash for Blake3TranscriptHash {
-    fn hash_to_fr(bytes: Vec<u8>) -> Fr {
-        println!("blake3_hash ");
-
+    fn hash_to_fr<F: PrimeField>(bytes: Vec<u8>) -> F {
         let mut hasher = blake3::Hasher::new();
         hasher.update(&bytes);
         let out = hasher.finalize();
-        let res = Fr::from_be_bytes_mod_order(out.as_bytes());
+        let res = F::from_be_bytes_mod_order(out.as_bytes());
         res
     }
 }
+
+/// The transcript hash selected at runtime, eg: from proof metadata or a config file.
+///
+/// Parses from (and displays as) `keccak256` / `blake3`, case-insensitively.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptKind {
+    /// The stock snarkjs transcript, see `Keccak256TranscriptHash`.
+    #[default]
+    #[serde(alias = "keccak")]
+    Keccak256,
+    /// The transcript of the blake3-patched snarkjs, see `Blake3TranscriptHash`.
+    Blake3,
+}
+
+impl TranscriptKind {
+    /// All the supported transcripts.
+    pub const ALL: [TranscriptKind; 2] = [TranscriptKind::Keccak256, TranscriptKind::Blake3];
+
+    pub fn hash_to_fr<F: PrimeField>(&self, bytes: Vec<u8>) -> F {
+        match self {
+            TranscriptKind::Keccak256 => Keccak256TranscriptHash::hash_to_fr(bytes),
+            TranscriptKind::Blake3 => Blake3TranscriptHash::hash_to_fr(bytes),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TranscriptKind::Keccak256 => "keccak256",
+            TranscriptKind::Blake3 => "blake3",
+        }
+    }
+}
+
+impl fmt::Display for TranscriptKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for TranscriptKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "keccak256" | "keccak" => Ok(TranscriptKind::Keccak256),
+            "blake3" => Ok(TranscriptKind::Blake3),
+            other => Err(anyhow::anyhow!("Unknown transcript hash: {other}")),
+        }
+    }
+}
+
+/// Domain separator of the beta preimage under `FiatShamirProfile::Strong`.
+pub const STRONG_FS_DOMAIN: &[u8] = b"fflonk-strong-fs-v1";
+/// Domain separator of `VerificationKey::digest`.
+pub const VK_DIGEST_DOMAIN: &[u8] = b"fflonk-vk-digest-v1";
+/// Domain separator of `VerifierState::inputs_digest`.
+pub const STATE_INPUTS_DOMAIN: &[u8] = b"fflonk-state-inputs-v1";
+
+/// Domain separator of the caller-supplied context absorbed before beta, see `bind_context`.
+pub const CONTEXT_DOMAIN: &[u8] = b"fflonk-context-v1";
+
+/// Bind the caller-supplied `context` (eg: chain id, contract address or session nonce)
+/// to the beta `preimage`, so that the proof only verifies under the same context:
+///     CONTEXT_DOMAIN || len(context) as u64 big-endian || context || preimage
+///
+/// The empty context is a no-op, which keeps the transcript compatible with snarkjs.
+/// It can't collide with a non-empty one: the snarkjs preimage starts with `C0.x < p`,
+/// whose first byte is below `CONTEXT_DOMAIN`'s, and the strong one with `STRONG_FS_DOMAIN`.
+pub fn bind_context(context: &[u8], preimage: Vec<u8>) -> Vec<u8> {
+    if context.is_empty() {
+        return preimage;
+    }
+
+    let mut concatenated = CONTEXT_DOMAIN.to_vec();
+    concatenated.extend((context.len() as u64).to_be_bytes());
+    concatenated.extend_from_slice(context);
+    concatenated.extend(preimage);
+    concatenated
+}
+
+/// Which data the first challenge `beta` absorbs.
+///
+/// Parses from (and displays as) `snarkjs` / `strong`, case-insensitively.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FiatShamirProfile {
+    /// The snarkjs-compatible transcript: beta = H(C0 || pub_input || C1).
+    ///
+    /// Note that it doesn't absorb `n`, `k1`, `k2`, the omegas nor `X_2`.
+    #[default]
+    SnarkJS,
+    /// beta = H(STRONG_FS_DOMAIN || vk.digest() || len(pub_inputs) || pub_inputs || C1),
+    /// which binds the whole verification key. For circuits controlled end-to-end only,
+    /// as the proofs of snarkjs won't pass it.
+    Strong,
+}
+
+impl FiatShamirProfile {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FiatShamirProfile::SnarkJS => "snarkjs",
+            FiatShamirProfile::Strong => "strong",
+        }
+    }
+}
+
+impl fmt::Display for FiatShamirProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for FiatShamirProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "snarkjs" => Ok(FiatShamirProfile::SnarkJS),
+            "strong" => Ok(FiatShamirProfile::Strong),
+            other => Err(anyhow::anyhow!("Unknown Fiat-Shamir profile: {other}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::BigInteger;
+
+    #[test]
+    fn test_transcript_kind_from_str() {
+        assert_eq!(
+            TranscriptKind::from_str("keccak256").unwrap(),
+            TranscriptKind::Keccak256
+        );
+        assert_eq!(
+            TranscriptKind::from_str("Keccak").unwrap(),
+            TranscriptKind::Keccak256
+        );
+        assert_eq!(
+            TranscriptKind::from_str(" BLAKE3 ").unwrap(),
+            TranscriptKind::Blake3
+        );
+        assert!(TranscriptKind::from_str("sha256").is_err());
+
+        for kind in TranscriptKind::ALL {
+            assert_eq!(TranscriptKind::from_str(&kind.to_string()).unwrap(), kind);
+        }
+    }
+
+    #[test]
+    fn test_transcript_kind_serde() {
+        let kinds: Vec<TranscriptKind> =
+            serde_json::from_str(r#"["keccak256", "keccak", "blake3"]"#).unwrap();
+        assert_eq!(
+            kinds,
+            vec![
+                TranscriptKind::Keccak256,
+                TranscriptKind::Keccak256,
+                TranscriptKind::Blake3
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&TranscriptKind::Blake3).unwrap(),
+            r#""blake3""#
+        );
+    }
+
+    #[test]
+    fn test_transcript_kind_hash_to_fr() {
+        let bytes = Fr::from(42u64).into_bigint().to_bytes_be();
+        assert_eq!(
+            TranscriptKind::Keccak256.hash_to_fr::<Fr>(bytes.clone()),
+            Keccak256TranscriptHash::hash_to_fr(bytes.clone())
+        );
+        assert_eq!(
+            TranscriptKind::Blake3.hash_to_fr::<Fr>(bytes.clone()),
+            Blake3TranscriptHash::hash_to_fr(bytes)
+        );
+    }
+
+    #[test]
+    fn test_fiat_shamir_profile_from_str() {
+        assert_eq!(
+            FiatShamirProfile::from_str("SnarkJS").unwrap(),
+            FiatShamirProfile::SnarkJS
+        );
+        assert_eq!(
+            FiatShamirProfile::from_str("strong").unwrap(),
+            FiatShamirProfile::Strong
+        );
+        assert!(FiatShamirProfile::from_str("weak").is_err());
+        assert_eq!(FiatShamirProfile::default(), FiatShamirProfile::SnarkJS);
+        assert_eq!(
+            serde_json::to_string(&FiatShamirProfile::Strong).unwrap(),
+            r#""strong""#
+        );
+    }
+
+    #[test]
+    fn test_bind_context() {
+        let preimage = vec![1u8, 2, 3];
+        assert_eq!(bind_context(&[], preimage.clone()), preimage);
+
+        let bound = bind_context(b"chain-id:1", preimage.clone());
+        assert!(bound.starts_with(CONTEXT_DOMAIN));
+        assert!(bound.ends_with(&preimage));
+        assert_ne!(bound, bind_context(b"chain-id:2", preimage.clone()));
+        // length-prefixed: moving bytes between context and preimage changes the binding.
+        assert_ne!(
+            bind_context(b"ab", b"c".to_vec()),
+            bind_context(b"a", b"bc".to_vec())
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::transcript::TranscriptHash;
+use ark_crypto_primitives::sponge::poseidon::{
+    find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge,
+};
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
+use ark_ff::{BigInteger, PrimeField};
+
+// The parameters of Poseidon over the 254-bit scalar field of BN254, width 3.
+pub const POSEIDON_RATE: usize = 2;
+pub const POSEIDON_FULL_ROUNDS: usize = 8;
+pub const POSEIDON_PARTIAL_ROUNDS: usize = 57;
+pub const POSEIDON_ALPHA: u64 = 5;
+
+// The transcript absorbs the bytes in big-endian chunks of that size, which fit in any field
+// of more than 128 bits.
+pub const POSEIDON_LIMB_BYTES: usize = 16;
+
+/// The Poseidon parameters of `PoseidonTranscriptHash`, the round constants and the MDS matrix
+/// being generated by the Grain LFSR.
+pub fn poseidon_config<F: PrimeField>() -> PoseidonConfig<F> {
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        POSEIDON_RATE,
+        POSEIDON_FULL_ROUNDS as u64,
+        POSEIDON_PARTIAL_ROUNDS as u64,
+        0,
+    );
+    PoseidonConfig::new(
+        POSEIDON_FULL_ROUNDS,
+        POSEIDON_PARTIAL_ROUNDS,
+        POSEIDON_ALPHA,
+        mds,
+        ark,
+        POSEIDON_RATE,
+        1,
+    )
+}
+
+// The field elements absorbed for the bytes:
+//      len(bytes) || bytes[0..16] || bytes[16..32] || ...
+// each chunk being read as a big-endian integer, the last one right-padded with zeros.
+pub fn bytes_to_limbs<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    std::iter::once(F::from(bytes.len() as u64))
+        .chain(bytes.chunks(POSEIDON_LIMB_BYTES).map(|chunk| {
+            let mut limb = [0u8; POSEIDON_LIMB_BYTES];
+            limb[..chunk.len()].copy_from_slice(chunk);
+            F::from_be_bytes_mod_order(&limb)
+        }))
+        .collect()
+}
+
+// The limbs as sponge inputs, as `Absorb` is only implemented for the concrete fields.
+struct Limbs<F: PrimeField>(Vec<F>);
+
+impl<F: PrimeField> Absorb for Limbs<F> {
+    fn to_sponge_bytes(&self, dest: &mut Vec<u8>) {
+        for limb in &self.0 {
+            dest.extend(limb.into_bigint().to_bytes_le());
+        }
+    }
+
+    fn to_sponge_field_elements<CF: PrimeField>(&self, dest: &mut Vec<CF>) {
+        for limb in &self.0 {
+            dest.push(CF::from_le_bytes_mod_order(
+                &limb.into_bigint().to_bytes_le(),
+            ));
+        }
+    }
+}
+
+/// An algebraic transcript, cheap to express as constraints, see `r1cs::TranscriptVar`.
+///
+/// The proofs of snarkjs don't use it: it's for the circuits proved with it end-to-end,
+/// eg: the inner proofs of a recursive verifier.
+pub struct PoseidonTranscriptHash;
+
+impl TranscriptHash for PoseidonTranscriptHash {
+    fn hash_to_fr<F: PrimeField>(bytes: Vec<u8>) -> F {
+        let mut sponge = PoseidonSponge::new(&poseidon_config::<F>());
+        sponge.absorb(&Limbs(bytes_to_limbs::<F>(&bytes)));
+        sponge.squeeze_field_elements::<F>(1)[0]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::Field;
+
+    #[test]
+    fn test_bytes_to_limbs() {
+        let mut bytes = vec![0u8; 32];
+        bytes[15] = 1;
+        bytes[31] = 2;
+        bytes.push(3);
+        assert_eq!(
+            bytes_to_limbs::<Fr>(&bytes),
+            vec![
+                Fr::from(33u64),
+                Fr::from(1u64),
+                Fr::from(2u64),
+                Fr::from(3u64) * Fr::from(2u64).pow([120])
+            ]
+        );
+    }
+
+    #[test]
+    fn test_poseidon_transcript_hash() {
+        let a: Fr = PoseidonTranscriptHash::hash_to_fr(vec![1u8; 32]);
+        assert_eq!(a, PoseidonTranscriptHash::hash_to_fr(vec![1u8; 32]));
+        assert_ne!(a, PoseidonTranscriptHash::hash_to_fr(vec![1u8; 33]));
+        // the length is absorbed: trailing zeros change the hash
+        let b: Fr = PoseidonTranscriptHash::hash_to_fr(vec![1u8; 16]);
+        assert_ne!(
+            b,
+            PoseidonTranscriptHash::hash_to_fr([vec![1u8; 16], vec![0]].concat())
+        );
+    }
+}
@@ -1,24 +1,21 @@
 use crate::challenge::Challenges;
 use crate::compute_fej::FEJ;
 use crate::proof::Proof;
-use ark_bn254::{Fr, G1Affine};
-use ark_ec::CurveGroup;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::{Field, PrimeField};
-use num_traits::{One, Zero};
 use std::fs::File;
 use std::io::Read;
-use std::ops::Neg;
 use std::path::Path;
-use std::str::FromStr;
 
 // Compute zero polynomial evaluation
 //      Z_H(xi) = xi^n - 1
-pub fn compute_zero_poly_evals(xi: &Fr, n: &Fr) -> Fr {
+pub fn compute_zero_poly_evals<F: PrimeField>(xi: &F, n: &F) -> F {
     // 1. Compute xin = xi^n
     let xin = xi.pow(n.into_bigint());
 
     // 2. zh = xin - 1
-    xin - Fr::one()
+    xin - F::one()
 }
 
 // Compute Lagrange polynomial evaluation
@@ -33,11 +30,11 @@ impl LangrangePolynomialEvaluation {
     // eg:
     //  Li_1_base = n * (xi - 1), which omega=w0=1
     //  Li_2_base = n * (xi - w1), which omega = w1
-    pub fn compute_lagrange_base(xi: &Fr, n: &Fr, omega: &Fr) -> Fr {
-        (xi - omega) * n
+    pub fn compute_lagrange_base<F: Field>(xi: &F, n: &F, omega: &F) -> F {
+        (*xi - omega) * n
     }
-    pub fn compute_L1_base(xi: &Fr, n: &Fr) -> Fr {
-        Self::compute_lagrange_base(xi, n, &Fr::one())
+    pub fn compute_L1_base<F: Field>(xi: &F, n: &F) -> F {
+        Self::compute_lagrange_base(xi, n, &F::one())
     }
 
     // Compute Lagrange polynomial evaluation L_i(xi)
@@ -46,46 +43,50 @@ impl LangrangePolynomialEvaluation {
     // eg:
     //  Li_1 = zh * Li_1_base_inv, which omega=w0=1
     //  Li_2 = w1 * zh * Li_2_base_inv , which omega = w1
-    pub fn compute_lagrange_polynomial_evaluation(zh: &Fr, Li_inv: &Fr, omega: &Fr) -> Fr {
-        omega * zh * Li_inv
+    pub fn compute_lagrange_polynomial_evaluation<F: Field>(zh: &F, Li_inv: &F, omega: &F) -> F {
+        *omega * zh * Li_inv
     }
 
-    pub fn compute_L1_polynomial_evaluation(zh: &Fr, L1_base_inv: &Fr) -> Fr {
-        Self::compute_lagrange_polynomial_evaluation(zh, L1_base_inv, &Fr::one())
+    pub fn compute_L1_polynomial_evaluation<F: Field>(zh: &F, L1_base_inv: &F) -> F {
+        Self::compute_lagrange_polynomial_evaluation(zh, L1_base_inv, &F::one())
     }
 }
 
 // Compute public input polynomial evaluation `PI(xi)`:
 // $PI(xi) = -\sum_i^l PublicInput_i·L_i(xi)$
-pub fn compute_pi(pub_inputs: &[Fr], eval_ls: &[Fr]) -> Fr {
+pub fn compute_pi<F: Field>(pub_inputs: &[F], eval_ls: &[F]) -> F {
     pub_inputs
         .iter()
         .zip(eval_ls.iter())
-        .map(|(pub_input_i, eval_li)| pub_input_i * eval_li)
-        .sum::<Fr>()
+        .map(|(pub_input_i, eval_li)| *pub_input_i * eval_li)
+        .sum::<F>()
         .neg()
 }
 
 // Compute P1 in the pairing.
 //      F = F - E - J + y·W2
-pub fn compute_a1(proof: &Proof, fej: &FEJ, challenges: &Challenges) -> G1Affine {
+pub fn compute_a1<E: Pairing>(
+    proof: &Proof<E>,
+    fej: &FEJ<E>,
+    challenges: &Challenges<E>,
+) -> E::G1Affine {
     let W2 = proof.polynomials.w2;
 
     // F = F - E - J + y·W2
-    (fej.F - fej.E - fej.J + W2 * challenges.y).into_affine()
+    (fej.F.into_group() - fej.E - fej.J + W2 * challenges.y).into_affine()
 }
 
-pub fn polynomial_eval(
-    base: Fr,
-    coefficients: &[Fr],
-    challenges: &[Fr],
-    inv: &[Fr],
-    acc: Option<Fr>,
-) -> Fr {
-    let mut acc = acc.unwrap_or(Fr::zero());
+pub fn polynomial_eval<F: Field>(
+    base: F,
+    coefficients: &[F],
+    challenges: &[F],
+    inv: &[F],
+    acc: Option<F>,
+) -> F {
+    let mut acc = acc.unwrap_or(F::zero());
     for (i, root) in challenges.iter().enumerate() {
-        let mut h = Fr::one();
-        let mut c1_value = Fr::zero();
+        let mut h = F::one();
+        let mut c1_value = F::zero();
         for c in coefficients {
             c1_value += (*c) * h;
             h *= *root
@@ -96,12 +97,12 @@ pub fn polynomial_eval(
 }
 
 // For now, only support single public inputs.
-pub fn load_public_input<P: AsRef<Path>>(pi_path: P) -> anyhow::Result<Fr> {
+pub fn load_public_input<F: PrimeField, P: AsRef<Path>>(pi_path: P) -> anyhow::Result<F> {
     let mut file = File::open(pi_path)?;
     let mut vk_json = String::new();
     file.read_to_string(&mut vk_json)?;
     let mut pub_inputs: Vec<String> = serde_json::from_str(&vk_json)?;
     assert_eq!(pub_inputs.len(), 1);
     let fr_str = pub_inputs.pop().unwrap();
-    Ok(Fr::from_str(&fr_str).unwrap())
+    F::from_str(&fr_str).map_err(|_| anyhow::anyhow!("Invalid public input: {fr_str}"))
 }
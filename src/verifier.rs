@@ -1,14 +1,24 @@
 use crate::challenge::Challenges;
 use crate::compute_fej::FEJ;
 use crate::compute_r::compute_r;
+use crate::gate::{Arithmetization, CustomGateProof, GateChallenges};
 use crate::inversion::Inversion;
-use crate::pairing::{check_pairing, prove_and_verify_pairing};
+use crate::lookup::{LookupArithmetization, LookupChallenges, LookupProof};
+use crate::opening::verify_openings;
+use crate::pairing::{
+    check_pairing_with_w2, prove_batch_pairing, prove_pairing, randomize_pairs,
+    verify_batch_pairing_artifact, verify_pairing_artifact, ArkworksBackend,
+    DesignatedVerifierBackend, OnProvingPairingsBackend, PairingArtifact, PairingBackend,
+    PreparedVerificationKey,
+};
 
 use crate::proof::Proof;
-use crate::transcript::TranscriptHash;
+use crate::transcript::{FiatShamirProfile, TranscriptHash, TranscriptKind};
 use crate::utils::{compute_a1, compute_pi, LangrangePolynomialEvaluation};
 use crate::vk::VerificationKey;
-use ark_bn254::Fr;
+use ark_bn254::{Fr, G1Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
 
 /// Use the given verification key `vk` to verify the `proof`` against the given `pubs` public inputs.
 /// Can fail if:
@@ -17,20 +27,199 @@ use ark_bn254::Fr;
 ///
 /// Params:
 ///  @is_recursive_verifier:
-///       if true, will leverage power of `prove and verify pairing`.
-///       if false, will use default pairing.
+///       if true, will leverage power of `prove and verify pairing`, ie: `OnProvingPairingsBackend`.
+///       if false, will use default pairing, ie: `ArkworksBackend`.
+///
+/// The proofs of the other curves go through `fflonk_verifier_with_backend`.
 pub fn fflonk_verifier<T: TranscriptHash>(
     vk: &VerificationKey,
     proof: &Proof,
     pub_input: &Fr,
     is_recursive_verifier: bool,
+) -> bool {
+    if is_recursive_verifier {
+        fflonk_verifier_with_backend::<_, T, _>(&OnProvingPairingsBackend, vk, proof, pub_input)
+    } else {
+        fflonk_verifier_with_backend::<_, T, _>(&ArkworksBackend, vk, proof, pub_input)
+    }
+}
+
+/// Same as `fflonk_verifier`, but the challenges are derived under the given Fiat-Shamir `profile`.
+///
+/// `FiatShamirProfile::SnarkJS` is what `fflonk_verifier` uses; `FiatShamirProfile::Strong`
+/// is opt-in, for the proofs whose prover also binds the whole verification key.
+pub fn fflonk_verifier_with_profile<T: TranscriptHash>(
+    profile: FiatShamirProfile,
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+    is_recursive_verifier: bool,
+) -> bool {
+    // 1. compute challenge
+    let challenges = Challenges::compute_with_profile::<T>(profile, vk, proof, pub_input);
+
+    verify_challenges(vk, proof, pub_input, &challenges, is_recursive_verifier)
+}
+
+/// Same as `fflonk_verifier_with_profile`, but the proof is bound to the caller-supplied `context`
+/// (eg: chain id, contract address or session nonce), which is absorbed before beta is squeezed.
+/// So the proof fails under any other context, see `transcript::bind_context`.
+///
+/// The empty context is the same as `fflonk_verifier_with_profile`.
+pub fn fflonk_verifier_with_context<T: TranscriptHash>(
+    profile: FiatShamirProfile,
+    context: &[u8],
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+    is_recursive_verifier: bool,
+) -> bool {
+    // 1. compute challenge
+    let challenges = Challenges::compute_with_context::<T>(profile, context, vk, proof, pub_input);
+
+    verify_challenges(vk, proof, pub_input, &challenges, is_recursive_verifier)
+}
+
+/// Same as `fflonk_verifier`, but the transcript hash is selected at runtime by `transcript`.
+///
+/// Only the challenge computation depends on the transcript, the rest of the pipeline is shared.
+pub fn fflonk_verifier_with_transcript(
+    transcript: TranscriptKind,
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+    is_recursive_verifier: bool,
+) -> bool {
+    // 1. compute challenge
+    let challenges = Challenges::compute_with_transcript(transcript, vk, proof, pub_input);
+
+    verify_challenges(vk, proof, pub_input, &challenges, is_recursive_verifier)
+}
+
+/// Same as `fflonk_verifier`, but the final pairing check is run by the given `backend`,
+/// eg: `ArkworksBackend`, `OnProvingPairingsBackend`, `DesignatedVerifierBackend` or `EvmPrecompileBackend`.
+pub fn fflonk_verifier_with_backend<E: Pairing, T: TranscriptHash, B: PairingBackend<E>>(
+    backend: &B,
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    pub_input: &E::ScalarField,
 ) -> bool {
     // 1. compute challenge
     let challenges = Challenges::compute::<T>(vk, proof, pub_input);
 
+    verify_challenges_with_backend(backend, vk, proof, pub_input, &challenges)
+}
+
+/// The designated-verifier mode: same as `fflonk_verifier`, for the verifier who generated the SRS
+/// and knows its `trapdoor` x. The pairing check is then the G1 equation A1 = x·W2, see `DesignatedVerifierBackend`.
+///
+/// Fails if the trapdoor isn't the one of `vk.x2`.
+pub fn fflonk_designated_verifier<E: Pairing, T: TranscriptHash>(
+    trapdoor: E::ScalarField,
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    pub_input: &E::ScalarField,
+) -> anyhow::Result<bool> {
+    let backend = DesignatedVerifierBackend::new(vk, trapdoor)?;
+    Ok(fflonk_verifier_with_backend::<E, T, _>(
+        &backend, vk, proof, pub_input,
+    ))
+}
+
+/// Verify a proof of unknown origin, whose transcript hash isn't known.
+///
+/// Tries every registered transcript in `TranscriptKind::ALL`, and picks the one under which the
+/// inverse `proof.evaluations.inv` sent by the prover checks out (no pairing needed for that).
+///
+/// Returns the transcript the proof was produced with and the verification result,
+/// or `None` if none of the transcripts matched.
+pub fn fflonk_verifier_detect_transcript(
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+    is_recursive_verifier: bool,
+) -> Option<(TranscriptKind, bool)> {
+    let (transcript, challenges) = detect_transcript(vk, proof, pub_input)?;
+
+    let res = verify_challenges(vk, proof, pub_input, &challenges, is_recursive_verifier);
+    Some((transcript, res))
+}
+
+/// Detect the transcript the proof was produced with, along with the challenges derived from it.
+pub fn detect_transcript<E: Pairing>(
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    pub_input: &E::ScalarField,
+) -> Option<(TranscriptKind, Challenges<E>)> {
+    TranscriptKind::ALL.into_iter().find_map(|transcript| {
+        let challenges = Challenges::compute_with_transcript(transcript, vk, proof, pub_input);
+        Inversion::check_inverse(vk, proof, &challenges).then_some((transcript, challenges))
+    })
+}
+
+/// Run the rest of the verifier with the caller-provided `challenges` instead of deriving them
+/// through a transcript, eg: the challenges replayed from a Solidity trace by `Challenges::from_values`.
+///
+/// If a proof passes with the expected challenges but fails `fflonk_verifier`, it's a transcript
+/// mismatch rather than an algebra one.
+pub fn fflonk_verifier_with_challenges(
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+    challenges: &Challenges,
+    is_recursive_verifier: bool,
+) -> bool {
+    verify_challenges(vk, proof, pub_input, challenges, is_recursive_verifier)
+}
+
+// The transcript-independent part of the verifier: step 2 to 8, with the backend of
+// `is_recursive_verifier`, see `fflonk_verifier`.
+fn verify_challenges(
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+    challenges: &Challenges,
+    is_recursive_verifier: bool,
+) -> bool {
+    if is_recursive_verifier {
+        verify_challenges_with_backend(&OnProvingPairingsBackend, vk, proof, pub_input, challenges)
+    } else {
+        verify_challenges_with_backend(&ArkworksBackend, vk, proof, pub_input, challenges)
+    }
+}
+
+// Same as `verify_challenges`, with the pairing check of the given `backend`.
+pub(crate) fn verify_challenges_with_backend<E: Pairing, B: PairingBackend<E>>(
+    backend: &B,
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    pub_input: &E::ScalarField,
+    challenges: &Challenges<E>,
+) -> bool {
+    let Some(a1) = compute_a1_with_challenges(vk, proof, pub_input, challenges) else {
+        // the key is inconsistent, or the provided inverse in the proof is wrong
+        return false;
+    };
+
+    // 8. Validate all evaluations
+    backend.check_pairing(vk, &a1, &proof.polynomials.w2.into_affine())
+}
+
+// Step 2 to 7: the point A1 of the pairing check, or `None` if the key is inconsistent (see
+// `VerificationKey::is_consistent`) or the provided inverse is wrong.
+pub(crate) fn compute_a1_with_challenges<E: Pairing>(
+    vk: &VerificationKey<E>,
+    proof: &Proof<E>,
+    pub_input: &E::ScalarField,
+    challenges: &Challenges<E>,
+) -> Option<E::G1Affine> {
+    if !vk.is_consistent() {
+        return None;
+    }
+
     // 2. compute inversion
     //     Compute public input polynomial evaluation PI(xi) = \sum_i^l -public_input_i·L_i(xi)
-    let inv_tuple = Inversion::build(vk, proof, &challenges);
+    let inv_tuple = Inversion::try_build(vk, proof, challenges)?;
 
     // 3. compute lagrange of L_1
     let L_1 = LangrangePolynomialEvaluation::compute_L1_polynomial_evaluation(
@@ -42,19 +231,106 @@ pub fn fflonk_verifier<T: TranscriptHash>(
     let pi = compute_pi(&[*pub_input], &[L_1]);
 
     // 5. Computes r1(y) and r2(y)
-    let (R0, R1, R2) = compute_r(vk, proof, &challenges, &inv_tuple, &L_1, &pi);
+    let (R0, R1, R2) = compute_r(vk, proof, challenges, &inv_tuple, &L_1, &pi);
 
     // 6. compute fej
     // Compute full batched polynomial commitment [F]_1, group-encoded batch evaluation [E]_1 and the full difference [J]_1
-    let fej = FEJ::compute(vk, proof, &challenges, &inv_tuple, R0, R1, R2);
+    let fej = FEJ::compute(vk, proof, challenges, &inv_tuple, R0, R1, R2);
 
     // 7. compute_a1
-    let a1 = compute_a1(proof, &fej, &challenges);
+    Some(compute_a1(proof, &fej, challenges))
+}
 
-    // 8. Validate all evaluations
-    if is_recursive_verifier {
-        prove_and_verify_pairing(vk, proof, &a1)
-    } else {
-        check_pairing(vk, proof, &a1)
+/// Same as `fflonk_verifier` in the recursive mode, but returns the on-proving-pairings witness
+/// along with A1, for a cheaper verifier to run `pairing::verify_pairing_artifact` on.
+///
+/// Returns `None` if the proof fails.
+pub fn fflonk_verifier_with_pairing_artifact<T: TranscriptHash>(
+    vk: &VerificationKey,
+    proof: &Proof,
+    pub_input: &Fr,
+) -> Option<(G1Affine, PairingArtifact)> {
+    // 1. compute challenge
+    let challenges = Challenges::compute::<T>(vk, proof, pub_input);
+    let a1 = compute_a1_with_challenges(vk, proof, pub_input, &challenges)?;
+
+    // 8. Validate all evaluations, then keep the witness: a failing pairing has none
+    let w2 = proof.polynomials.w2.into_affine();
+    if !check_pairing_with_w2(vk, &a1, &w2) {
+        return None;
+    }
+    let artifact = prove_pairing(vk, &a1, &w2);
+    verify_pairing_artifact(vk, &a1, &w2, &artifact).then_some((a1, artifact))
+}
+
+/// Same as `fflonk_verifier_with_pairing_artifact` for a batch of proofs, whose pairing checks
+/// are merged into a single on-proving-pairings witness of `pairing::randomize_pairs`.
+///
+/// Returns the A1 of every proof, or `None` if any of them fails.
+pub fn fflonk_batch_verifier_with_pairing_artifact<T: TranscriptHash>(
+    vk: &VerificationKey,
+    proofs: &[(Proof, Fr)],
+) -> Option<(Vec<G1Affine>, PairingArtifact)> {
+    let pairs = proofs
+        .iter()
+        .map(|(proof, pub_input)| {
+            let challenges = Challenges::compute::<T>(vk, proof, pub_input);
+            let a1 = compute_a1_with_challenges(vk, proof, pub_input, &challenges)?;
+            Some((a1, proof.polynomials.w2.into_affine()))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    // 8. Validate all evaluations at once, with the lines of the vk computed once
+    let pvk = PreparedVerificationKey::from(vk);
+    let randomized = randomize_pairs::<T>(&pairs);
+    let artifact = prove_batch_pairing(&pvk, &randomized).ok()?;
+    verify_batch_pairing_artifact(&pvk, &randomized, &artifact)
+        .then(|| (pairs.into_iter().map(|(a1, _)| a1).collect(), artifact))
+}
+
+/// Verify a `proof` of the custom gate `arithmetization`, eg: `Arithmetization::four_wire`.
+///
+/// The proof is opened through `opening::verify_openings`, with the layout of
+/// `Arithmetization::opening_set_config` and the challenges of `GateChallenges::compute`.
+/// Fails on an invalid arithmetization, while a proof with evaluations of another arity is `Ok(false)`.
+pub fn fflonk_custom_gate_verifier<E: Pairing, T: TranscriptHash>(
+    arithmetization: &Arithmetization<E>,
+    vk: &VerificationKey<E>,
+    proof: &CustomGateProof<E>,
+    pub_input: &E::ScalarField,
+) -> anyhow::Result<bool> {
+    let config = arithmetization.opening_set_config()?;
+    if arithmetization
+        .check_evaluations(&proof.evaluations)
+        .is_err()
+    {
+        return Ok(false);
     }
+    let challenges = GateChallenges::compute::<T>(arithmetization, vk, proof, pub_input)?;
+    let opening = arithmetization.opening_proof(vk, proof, &challenges, pub_input)?;
+
+    verify_openings::<E, T>(&config, vk, &opening, &challenges.gamma)
+}
+
+/// Verify a `proof` of the `arithmetization` with a lookup argument, see `lookup`.
+///
+/// Same as `fflonk_custom_gate_verifier`, with the transcript of `LookupChallenges::compute`.
+pub fn fflonk_lookup_verifier<E: Pairing, T: TranscriptHash>(
+    arithmetization: &LookupArithmetization<E>,
+    vk: &VerificationKey<E>,
+    proof: &LookupProof<E>,
+    pub_input: &E::ScalarField,
+) -> anyhow::Result<bool> {
+    let config = arithmetization.opening_set_config()?;
+    if arithmetization
+        .arithmetization
+        .check_evaluations(&proof.evaluations)
+        .is_err()
+    {
+        return Ok(false);
+    }
+    let challenges = LookupChallenges::compute::<T>(arithmetization, vk, proof, pub_input)?;
+    let opening = arithmetization.opening_proof(vk, proof, &challenges, pub_input)?;
+
+    verify_openings::<E, T>(&config, vk, &opening, &challenges.zeta)
 }
@@ -1,6 +1,10 @@
-use ark_bn254::{Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use crate::curve::{field_to_bytes_be, point_to_bytes_be, SWPoint};
+use crate::transcript::{TranscriptHash, VK_DIGEST_DOMAIN};
+use anyhow::anyhow;
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::pairing::Pairing;
 use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::Field;
+use ark_ff::{FftField, Field, PrimeField};
 use num_traits::One;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
@@ -9,37 +13,42 @@ use std::path::Path;
 use std::str::FromStr;
 
 #[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
-pub struct SnarkJSVK {
+#[serde(bound = "E::G1: SWPoint<BaseField = E::BaseField>, E::G2: SWPoint")]
+pub struct SnarkJSVK<E: Pairing = Bn254> {
     // Domain size
     pub power: u8,
 
     // Verification Key data
     #[serde(with = "crate::serde::fr")]
-    pub k1: Fr,
+    pub k1: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub k2: Fr,
+    pub k2: E::ScalarField,
 
     // OMEGAS
     #[serde(with = "crate::serde::fr")]
-    pub w: Fr,
+    pub w: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub w3: Fr,
+    pub w3: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub w4: Fr,
+    pub w4: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub w8: Fr,
+    pub w8: E::ScalarField,
     #[serde(with = "crate::serde::fr")]
-    pub wr: Fr,
+    pub wr: E::ScalarField,
 
     // Verifier preprocessed input
     // x·[1]_2
     #[serde(with = "crate::serde::g2", rename = "X_2")]
-    pub x2: G2Projective,
+    pub x2: E::G2,
     // C_0(x)·[1]_1
     #[serde(with = "crate::serde::g1", rename = "C0")]
-    pub c0: G1Projective,
+    pub c0: E::G1,
 }
-impl SnarkJSVK {
+impl<E: Pairing> SnarkJSVK<E>
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+    E::G2: SWPoint,
+{
     pub fn load<P: AsRef<Path>>(vk_path: P) -> anyhow::Result<Self> {
         let mut file = File::open(vk_path)?;
         let mut vk_json = String::new();
@@ -49,7 +58,7 @@ impl SnarkJSVK {
     }
 }
 
-impl Default for SnarkJSVK {
+impl Default for SnarkJSVK<Bn254> {
     fn default() -> Self {
         let k = 24;
         Self {
@@ -115,31 +124,70 @@ impl Default for SnarkJSVK {
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
-pub struct VerificationKey {
+pub struct VerificationKey<E: Pairing = Bn254> {
     // Domain size
     pub power: u8,
-    pub n: Fr,
+    pub n: E::ScalarField,
 
     // Verification Key data
-    pub k1: Fr,
-    pub k2: Fr,
+    pub k1: E::ScalarField,
+    pub k2: E::ScalarField,
 
     // Verifier preprocessed input
     // x·[1]_2
-    pub x2: G2Affine,
+    pub x2: E::G2Affine,
     // C_0(x)·[1]_1
-    pub c0: G1Affine,
-    // [1]_2
-    pub g2: G2Affine,
+    pub c0: E::G1Affine,
+    // [1]_1 and [1]_2 of the SRS, the generators of G1 and G2 unless the ceremony picked others
+    pub g1: E::G1Affine,
+    pub g2: E::G2Affine,
     // OMEGAS
-    pub omega: Omega,
+    pub omega: Omega<E>,
 }
-impl Default for VerificationKey {
+impl<E: Pairing> VerificationKey<E> {
+    // Digest of the whole verification key, absorbed by `FiatShamirProfile::Strong`:
+    //      H(VK_DIGEST_DOMAIN || n || k1 || k2 || w || wr || w3 || w4 || w8 || C0 || X_2 || [1]_2)
+    // with every field element as big-endian bytes (see `curve::field_to_bytes_be`), G1 points
+    // as x || y and G2 points as x.c0 || x.c1 || y.c0 || y.c1.
+    // [1]_1 is appended only if it isn't the generator, so that the digest of the keys of the
+    // standard SRSs stays the same.
+    pub fn digest<T: TranscriptHash>(&self) -> E::ScalarField {
+        let mut concatenated = vec![
+            VK_DIGEST_DOMAIN.to_vec(),
+            field_to_bytes_be(&self.n),
+            field_to_bytes_be(&self.k1),
+            field_to_bytes_be(&self.k2),
+            field_to_bytes_be(&self.omega.w),
+            field_to_bytes_be(&self.omega.wr),
+            field_to_bytes_be(&self.omega.w3),
+            field_to_bytes_be(&self.omega.w4),
+            field_to_bytes_be(&self.omega.w8_1),
+            point_to_bytes_be(&self.c0),
+            point_to_bytes_be(&self.x2),
+            point_to_bytes_be(&self.g2),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        if self.g1 != E::G1Affine::generator() {
+            concatenated.extend(point_to_bytes_be(&self.g1));
+        }
+        T::hash_to_fr(concatenated)
+    }
+
+    // n is the 2^power of the domain: the verifier reads n, and the R1CS verifier power, so a
+    // key where they differ is rejected rather than verified differently.
+    pub fn is_consistent(&self) -> bool {
+        self.n == E::ScalarField::from(2u64).pow([self.power as u64])
+    }
+}
+
+impl Default for VerificationKey<Bn254> {
     fn default() -> Self {
         let k = 24;
         Self {
             power: k,
-            n: Fr::from(1 << k),
+            n: Fr::from(1u64 << k),
             k1: Fr::from(2),
             k2: Fr::from(3),
             x2: {
@@ -172,62 +220,137 @@ impl Default for VerificationKey {
                 .unwrap();
                 G1Affine::new(x, y)
             },
+            g1: G1Affine::generator(),
             g2: G2Affine::generator(),
             omega: Omega::default(),
         }
     }
 }
 
-impl From<SnarkJSVK> for VerificationKey {
-    fn from(origin: SnarkJSVK) -> Self {
+impl<E: Pairing> From<SnarkJSVK<E>> for VerificationKey<E> {
+    fn from(origin: SnarkJSVK<E>) -> Self {
         let k = origin.power;
 
-        let omega = Omega {
-            w: origin.w,
-            wr: origin.wr,
-            w3: origin.w3,
-            w4: origin.w4,
-            w4_2: origin.w4,
-            w8_1: origin.w8,
-            ..Default::default()
-        };
-        let precompute_omega = Omega::precompute(&omega);
+        let precompute_omega =
+            Omega::from_roots(origin.w, origin.wr, origin.w3, origin.w4, origin.w8);
 
         VerificationKey {
             power: k,
-            n: Fr::from(1 << k),
+            // not a shift, the power of an untrusted JSON may be 64 or more
+            n: E::ScalarField::from(2u64).pow([k as u64]),
             k1: origin.k1,
             k2: origin.k2,
             x2: origin.x2.into_affine(),
             c0: origin.c0.into_affine(),
-            g2: G2Affine::generator(),
+            g1: E::G1Affine::generator(),
+            g2: E::G2Affine::generator(),
             omega: precompute_omega,
         }
     }
 }
 
+impl<E: Pairing> From<&VerificationKey<E>> for SnarkJSVK<E> {
+    fn from(vk: &VerificationKey<E>) -> Self {
+        SnarkJSVK {
+            power: vk.power,
+            k1: vk.k1,
+            k2: vk.k2,
+            w: vk.omega.w,
+            w3: vk.omega.w3,
+            w4: vk.omega.w4,
+            w8: vk.omega.w8_1,
+            wr: vk.omega.wr,
+            x2: vk.x2.into_group(),
+            c0: vk.c0.into_group(),
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
-pub struct Omega {
-    pub w: Fr,
-    pub wr: Fr,
-
-    pub w3: Fr,
-    pub w3_2: Fr,
-
-    pub w4: Fr,
-    pub w4_2: Fr,
-    pub w4_3: Fr,
-
-    pub w8_1: Fr,
-    pub w8_2: Fr,
-    pub w8_3: Fr,
-    pub w8_4: Fr,
-    pub w8_5: Fr,
-    pub w8_6: Fr,
-    pub w8_7: Fr,
+pub struct Omega<E: Pairing = Bn254> {
+    pub w: E::ScalarField,
+    pub wr: E::ScalarField,
+
+    pub w3: E::ScalarField,
+    pub w3_2: E::ScalarField,
+
+    pub w4: E::ScalarField,
+    pub w4_2: E::ScalarField,
+    pub w4_3: E::ScalarField,
+
+    pub w8_1: E::ScalarField,
+    pub w8_2: E::ScalarField,
+    pub w8_3: E::ScalarField,
+    pub w8_4: E::ScalarField,
+    pub w8_5: E::ScalarField,
+    pub w8_6: E::ScalarField,
+    pub w8_7: E::ScalarField,
 }
 
-impl Omega {
+impl<E: Pairing> Omega<E> {
+    // The omegas of the 2^power domain, which are the same as what snarkjs picks:
+    //      w: the 2^power-th root of unity, w4 and w8: the 4th and 8th roots in the same 2-adic tower,
+    //      w3 = g^(2(r-1)/3): the cube root of unity, where g is the multiplicative generator,
+    //      wr = w^(3^-1 mod 2^power): a cube root of w, so that h3w3 = h2w3·wr.
+    //
+    // Eg: on BN254, `Omega::new(24)` is `Omega::default()`.
+    // Fails if the domain is larger than the 2-adicity of the scalar field.
+    pub fn new(power: u8) -> anyhow::Result<Self> {
+        let w = (power < 64)
+            .then(|| E::ScalarField::get_root_of_unity(1u64 << power))
+            .flatten()
+            .ok_or_else(|| {
+                anyhow!("The domain 2^{power} is larger than the 2-adicity of the scalar field")
+            })?;
+        let w4 = E::ScalarField::get_root_of_unity(4).unwrap();
+        let w8 = E::ScalarField::get_root_of_unity(8).unwrap();
+
+        // (r-1)/3, as 3 | r-1 for the fflonk-friendly curves.
+        let modulus: num_bigint::BigUint = E::ScalarField::MODULUS.into();
+        let third = (modulus - 1u32) / 3u32;
+        let w3 = E::ScalarField::GENERATOR
+            .pow(third.to_u64_digits())
+            .square();
+
+        // 3^-1 mod 2^power: 3·(2^power+1)/3 = 1 for odd power, and 3·(2^(power+1)+1)/3 = 1 for even power.
+        let inv3 = if power % 2 == 1 {
+            ((1u128 << power) + 1) / 3
+        } else {
+            ((1u128 << (power + 1)) + 1) / 3
+        };
+        let wr = w.pow([inv3 as u64]);
+
+        Ok(Self::from_roots(w, wr, w3, w4, w8))
+    }
+
+    // Build the omegas from the roots of the verification key and precompute their powers.
+    pub fn from_roots(
+        w: E::ScalarField,
+        wr: E::ScalarField,
+        w3: E::ScalarField,
+        w4: E::ScalarField,
+        w8: E::ScalarField,
+    ) -> Self {
+        let one = E::ScalarField::one();
+        let omega = Omega {
+            w,
+            wr,
+            w3,
+            w3_2: one,
+            w4,
+            w4_2: one,
+            w4_3: one,
+            w8_1: w8,
+            w8_2: one,
+            w8_3: one,
+            w8_4: one,
+            w8_5: one,
+            w8_6: one,
+            w8_7: one,
+        };
+        Omega::precompute(&omega)
+    }
+
     pub fn precompute(origin: &Self) -> Self {
         let w3_2 = origin.w3.pow([2]);
 
@@ -260,7 +383,7 @@ impl Omega {
     }
 }
 
-impl Default for Omega {
+impl Default for Omega<Bn254> {
     fn default() -> Self {
         Self {
             w: Fr::from_str(
@@ -344,6 +467,28 @@ mod test {
         assert_eq!(expect, actaul);
     }
 
+    #[test]
+    fn test_omega_new() {
+        // snarkjs' constants of the BN254 domain 2^24.
+        assert_eq!(Omega::<Bn254>::new(24).unwrap(), Omega::default());
+
+        // the domains larger than the 2-adicity have no omegas, rather than a panic
+        assert!(Omega::<Bn254>::new(28).is_ok());
+        assert!(Omega::<Bn254>::new(29).is_err());
+        assert!(Omega::<Bn254>::new(64).is_err());
+        assert!(Omega::<Bn254>::new(u8::MAX).is_err());
+
+        let omega = Omega::<ark_bls12_381::Bls12_381>::new(24).unwrap();
+        assert_eq!(omega.w.pow([1u64 << 24]), ark_bls12_381::Fr::one());
+        assert_ne!(omega.w.pow([1u64 << 23]), ark_bls12_381::Fr::one());
+        assert_eq!(omega.wr.pow([3]), omega.w);
+        assert_eq!(omega.w3.pow([3]), ark_bls12_381::Fr::one());
+        assert_ne!(omega.w3, ark_bls12_381::Fr::one());
+        assert_eq!(omega.w4_2, -ark_bls12_381::Fr::one());
+        assert_eq!(omega.w8_4, -ark_bls12_381::Fr::one());
+        assert_eq!(omega.w8_2, omega.w4);
+    }
+
     #[test]
     fn test_convert_between_SnarkJSVK_and_VerificationKey() {
         let snarkjs_vk = SnarkJSVK::default();
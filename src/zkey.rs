@@ -0,0 +1,488 @@
+use crate::circom::{read_prime, read_sections, section, PlonkAddition, Reader};
+use crate::curve::SWPoint;
+use crate::prover::{PlonkWitness, ProvingKey, Srs};
+use crate::vk::{Omega, VerificationKey};
+use anyhow::ensure;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{FftField, One, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain};
+use std::path::Path;
+
+// The sections of a fflonk `.zkey` of snarkjs, see `src/fflonk_constants.js` of snarkjs.
+const ZKEY_HEADER: u32 = 1;
+const FFLONK_HEADER: u32 = 2;
+const FFLONK_ADDITIONS: u32 = 3;
+// then the B and C maps
+const FFLONK_A_MAP: u32 = 4;
+// then QR, QM, QO, QC, SIGMA1, SIGMA2 and SIGMA3
+const FFLONK_QL: u32 = 7;
+const FFLONK_PTAU: u32 = 16;
+const FFLONK_C0: u32 = 17;
+
+const FFLONK_PROTOCOL_ID: u32 = 10;
+
+/// A fflonk `.zkey` of snarkjs, as `snarkjs fflonk setup` writes it.
+///
+/// Unlike the JSON of `snarkjs zkey export verificationkey`, its field elements are in the
+/// Montgomery form of ffjavascript, and its points are their affine coordinates in the same form.
+#[derive(Debug, Clone)]
+pub struct FflonkZKey<E: Pairing = Bn254> {
+    pub n_vars: usize,
+    pub n_public: usize,
+    pub n_additions: usize,
+    pub n_constraints: usize,
+    pub vk: VerificationKey<E>,
+    // The sections for proving, if they're read
+    pub proving: Option<ZKeyProving<E>>,
+}
+
+/// The sections of a `.zkey` for proving: the preprocessed polynomials, the powers of tau, and
+/// how the wires of circom map to the rows.
+#[derive(Debug, Clone)]
+pub struct ZKeyProving<E: Pairing = Bn254> {
+    pub pk: ProvingKey<E>,
+    pub srs: Srs<E>,
+    pub additions: Vec<PlonkAddition<E::ScalarField>>,
+    // The signals of the wires a, b and c of every row
+    pub signals: [Vec<usize>; 3],
+}
+
+impl<E: Pairing> FflonkZKey<E>
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+    E::G2: SWPoint,
+{
+    /// Read the `.zkey`, and the sections for proving if `proving` is set.
+    pub fn load<P: AsRef<Path>>(zkey_path: P, proving: bool) -> anyhow::Result<Self> {
+        Self::from_bytes(&std::fs::read(zkey_path)?, proving)
+    }
+
+    pub fn from_bytes(bytes: &[u8], proving: bool) -> anyhow::Result<Self> {
+        let sections = read_sections(bytes, b"zkey")?;
+        let protocol = section(&sections, ZKEY_HEADER)?.u32()?;
+        ensure!(
+            protocol == FFLONK_PROTOCOL_ID,
+            "The protocol {protocol} of the zkey isn't fflonk"
+        );
+
+        // n8q || q || n8r || r || n_vars || n_public || domain_size || n_additions ||
+        //      n_constraints || k1 || k2 || w3 || w4 || w8 || wr || X_2 || C0
+        let mut header = section(&sections, FFLONK_HEADER)?;
+        read_prime::<E::BaseField>(&mut header)?;
+        read_prime::<E::ScalarField>(&mut header)?;
+        let n_vars = header.u32()? as usize;
+        let n_public = header.u32()? as usize;
+        // the verifier, like the contract of snarkjs it follows, takes a single public input
+        ensure!(
+            n_public <= 1,
+            "The zkey has {n_public} public inputs, but the verifier supports one at most"
+        );
+        let domain_size = header.u32()?;
+        ensure!(
+            domain_size.is_power_of_two(),
+            "The domain size {domain_size} isn't a power of 2"
+        );
+        let power = domain_size.trailing_zeros() as u8;
+        ensure!(
+            power as u32 <= E::ScalarField::TWO_ADICITY,
+            "The domain size {domain_size} is larger than the 2-adicity of the scalar field"
+        );
+        let n_additions = header.u32()? as usize;
+        let n_constraints = header.u32()? as usize;
+        let [k1, k2, w3, w4, w8, wr] = [(); 6].map(|_| header.montgomery::<E::ScalarField>());
+        let x2 = read_point::<E::G2>(&mut header)?.into_affine();
+        let c0 = read_point::<E::G1>(&mut header)?.into_affine();
+
+        let omega = Omega::<E>::new(power)?;
+        let vk = VerificationKey {
+            power,
+            n: E::ScalarField::from(domain_size as u64),
+            k1: k1?,
+            k2: k2?,
+            x2,
+            c0,
+            // the zkey keeps only X_2 of the G2 powers: snarkjs assumes the standard generators
+            g1: E::G1Affine::generator(),
+            g2: E::G2Affine::generator(),
+            omega: Omega::from_roots(omega.w, wr?, w3?, w4?, w8?),
+        };
+
+        let proving = if proving {
+            let n = domain_size as usize;
+            let mut additions = section(&sections, FFLONK_ADDITIONS)?;
+            let additions = (0..n_additions)
+                .map(|_| {
+                    let signals = [additions.u32()? as usize, additions.u32()? as usize];
+                    let coefs = [additions.montgomery()?, additions.montgomery()?];
+                    Ok(PlonkAddition { signals, coefs })
+                })
+                .collect::<anyhow::Result<_>>()?;
+            let signals = [0, 1, 2].map(|column| {
+                let mut map = section(&sections, FFLONK_A_MAP + column)?;
+                (0..n_constraints)
+                    .map(|_| Ok(map.u32()? as usize))
+                    .collect::<anyhow::Result<Vec<_>>>()
+            });
+
+            // every polynomial is followed by its evaluations on the domain of size 4n
+            let polynomial = |section_type: u32, len: usize| {
+                let mut data = section(&sections, section_type)?;
+                let coeffs = (0..len)
+                    .map(|_| data.montgomery())
+                    .collect::<anyhow::Result<_>>()?;
+                Ok::<_, anyhow::Error>(DensePolynomial::from_coefficients_vec(coeffs))
+            };
+            let [ql, qr, qm, qo, qc, s1, s2, s3] =
+                [0, 1, 2, 3, 4, 5, 6, 7].map(|i| polynomial(FFLONK_QL + i, n));
+            let (s1, s2, s3) = (s1?, s2?, s3?);
+            let domain = Radix2EvaluationDomain::<E::ScalarField>::new(n).unwrap();
+            let sigma = [&s1, &s2, &s3].map(|s| domain.fft(&s.coeffs));
+
+            let mut ptau = section(&sections, FFLONK_PTAU)?;
+            let mut g1_powers = vec![];
+            while !ptau.0.is_empty() {
+                g1_powers.push(read_point::<E::G1>(&mut ptau)?);
+            }
+
+            let [a, b, c] = signals;
+            Some(ZKeyProving {
+                pk: ProvingKey {
+                    vk: vk.clone(),
+                    ql: ql?,
+                    qr: qr?,
+                    qo: qo?,
+                    qm: qm?,
+                    qc: qc?,
+                    s1,
+                    s2,
+                    s3,
+                    sigma,
+                    c0: polynomial(FFLONK_C0, 8 * n)?,
+                },
+                srs: Srs {
+                    g1_powers: E::G1::normalize_batch(&g1_powers),
+                    x2,
+                    g2: E::G2Affine::generator(),
+                },
+                additions,
+                signals: [a?, b?, c?],
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            n_vars,
+            n_public,
+            n_additions,
+            n_constraints,
+            vk,
+            proving,
+        })
+    }
+}
+
+impl<E: Pairing> ZKeyProving<E> {
+    /// The wires of the rows for the values of the wires of circom, eg: of a `.wtns` file, see
+    /// `CircomCircuit::witness`.
+    pub fn witness(
+        &self,
+        wires: &[E::ScalarField],
+    ) -> anyhow::Result<PlonkWitness<E::ScalarField>> {
+        ensure!(
+            wires.first().is_some_and(|one| one.is_one()),
+            "The wire 0 isn't 1"
+        );
+        let mut signals = wires.to_vec();
+        for addition in &self.additions {
+            let [s1, s2] = addition.signals;
+            ensure!(
+                s1.max(s2) < signals.len(),
+                "The witness doesn't have the signals of the additions"
+            );
+            let [c1, c2] = addition.coefs;
+            signals.push(c1 * signals[s1] + c2 * signals[s2]);
+        }
+
+        let n = 1 << self.pk.vk.power;
+        let wire = |column: usize| {
+            let map = &self.signals[column];
+            ensure!(
+                map.iter().all(|signal| *signal < signals.len()),
+                "The witness doesn't have the signals of the rows"
+            );
+            let mut wire: Vec<_> = map.iter().map(|signal| signals[*signal]).collect();
+            // the padding rows are on the signal 0
+            wire.resize(n, signals[0]);
+            Ok(wire)
+        };
+
+        Ok(PlonkWitness {
+            a: wire(0)?,
+            b: wire(1)?,
+            c: wire(2)?,
+        })
+    }
+}
+
+// An affine point of ffjavascript: x || y, the point at infinity being all zeros.
+pub(crate) fn read_point<G: SWPoint>(reader: &mut Reader) -> anyhow::Result<G> {
+    let x = reader.montgomery::<G::BaseField>()?;
+    let y = reader.montgomery::<G::BaseField>()?;
+    let point = if x.is_zero() && y.is_zero() {
+        G::from_coordinates(
+            G::BaseField::one(),
+            G::BaseField::one(),
+            G::BaseField::zero(),
+        )
+    } else {
+        G::from_coordinates(x, y, G::BaseField::one())
+    };
+    point
+        .check()
+        .map_err(|_| anyhow::anyhow!("The point isn't on the curve"))?;
+    Ok(point)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::circom::{CircomCircuit, R1CS};
+    use crate::prover::fflonk_prover;
+    use crate::setup::fflonk_setup;
+    use crate::transcript::Keccak256TranscriptHash;
+    use crate::verifier::fflonk_verifier;
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, Field, PrimeField};
+    use ark_std::UniformRand;
+
+    pub(crate) fn montgomery<F: Field>(f: &F) -> Vec<u8> {
+        f.to_base_prime_field_elements()
+            .flat_map(|x| {
+                let r = F::BasePrimeField::from(2u64).pow([256]);
+                (x * r).into_bigint().to_bytes_le()
+            })
+            .collect()
+    }
+
+    pub(crate) fn point<A: AffineRepr>(p: &A) -> Vec<u8> {
+        match p.xy() {
+            Some((x, y)) => [montgomery(x), montgomery(y)].concat(),
+            None => montgomery(&A::BaseField::zero()).repeat(2),
+        }
+    }
+
+    pub(crate) fn prime<F: PrimeField>() -> Vec<u8> {
+        [32u32.to_le_bytes().to_vec(), F::MODULUS.to_bytes_le()].concat()
+    }
+
+    // The `.zkey` that `snarkjs fflonk setup` writes for the circuit.
+    fn zkey_bytes(circom: &CircomCircuit<Fr>, pk: &ProvingKey, srs: &Srs) -> Vec<u8> {
+        let vk = &pk.vk;
+        let n = 1usize << vk.power;
+        let rows = circom.circuit.rows();
+        let u32_bytes = |value: usize| (value as u32).to_le_bytes().to_vec();
+
+        let mut header = [prime::<ark_bn254::Fq>(), prime::<Fr>()].concat();
+        for value in [
+            circom.circuit.n_vars,
+            circom.circuit.n_public,
+            n,
+            circom.additions.len(),
+            rows.len(),
+        ] {
+            header.extend(u32_bytes(value));
+        }
+        let omega = &vk.omega;
+        for f in [vk.k1, vk.k2, omega.w3, omega.w4, omega.w8_1, omega.wr] {
+            header.extend(montgomery(&f));
+        }
+        header.extend(point(&vk.x2));
+        header.extend(point(&vk.c0));
+
+        let additions = circom
+            .additions
+            .iter()
+            .flat_map(|addition| {
+                let [s1, s2] = addition.signals;
+                let [c1, c2] = addition.coefs;
+                [
+                    u32_bytes(s1),
+                    u32_bytes(s2),
+                    montgomery(&c1),
+                    montgomery(&c2),
+                ]
+                .concat()
+            })
+            .collect();
+        let mut sections = vec![
+            (ZKEY_HEADER, FFLONK_PROTOCOL_ID.to_le_bytes().to_vec()),
+            (FFLONK_HEADER, header),
+            (FFLONK_ADDITIONS, additions),
+        ];
+        for column in 0..3 {
+            let map = rows.iter().flat_map(|row| u32_bytes(row.signals[column]));
+            sections.push((FFLONK_A_MAP + column as u32, map.collect()));
+        }
+
+        // the coefficients, then the evaluations on the domain of size 4n
+        let extended = Radix2EvaluationDomain::<Fr>::new(4 * n).unwrap();
+        let polynomials = [
+            &pk.ql, &pk.qr, &pk.qm, &pk.qo, &pk.qc, &pk.s1, &pk.s2, &pk.s3,
+        ];
+        for (i, p) in polynomials.into_iter().enumerate() {
+            let mut coeffs = p.coeffs.clone();
+            coeffs.resize(n, Fr::zero());
+            let evals = extended.fft(&coeffs);
+            let data = coeffs.iter().chain(&evals).flat_map(montgomery).collect();
+            sections.push((FFLONK_QL + i as u32, data));
+        }
+        // the Lagrange polynomials of the public inputs, which the reader skips
+        sections.push((15, vec![]));
+        sections.push((FFLONK_PTAU, srs.g1_powers.iter().flat_map(point).collect()));
+        let mut c0 = pk.c0.coeffs.clone();
+        c0.resize(8 * n, Fr::zero());
+        sections.push((FFLONK_C0, c0.iter().flat_map(montgomery).collect()));
+
+        binfile(b"zkey", &sections)
+    }
+
+    pub(crate) fn binfile(magic: &[u8; 4], sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((sections.len() as u32).to_le_bytes());
+        for (section_type, data) in sections {
+            bytes.extend(section_type.to_le_bytes());
+            bytes.extend((data.len() as u64).to_le_bytes());
+            bytes.extend(data);
+        }
+        bytes
+    }
+
+    // The constants of snarkjs and ffjavascript, rather than the ones of `zkey_bytes`: a real
+    // `.zkey` isn't tracked.
+    #[test]
+    fn test_zkey_constants() {
+        // `src/fflonk_constants.js` and the protocol id of `src/zkey_constants.js` of snarkjs
+        assert_eq!(
+            [
+                ZKEY_HEADER,
+                FFLONK_HEADER,
+                FFLONK_ADDITIONS,
+                FFLONK_A_MAP,
+                FFLONK_QL
+            ],
+            [1, 2, 3, 4, 7]
+        );
+        assert_eq!([FFLONK_PTAU, FFLONK_C0, FFLONK_PROTOCOL_ID], [16, 17, 10]);
+
+        // 1 as ffjavascript keeps it: R = 2^256 mod r, and 2^256 mod q
+        let le = |be: &str| {
+            let mut bytes = hex::decode(be).unwrap();
+            bytes.reverse();
+            bytes
+        };
+        let r_fr = le("0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb");
+        let r_fq = le("0e0a77c19a07df2f666ea36f7879462c0a78eb28f5c70b3dd35d438dc58f0d9d");
+        assert_eq!(montgomery(&Fr::one()), r_fr);
+        assert_eq!(Reader(&r_fr).montgomery::<Fr>().unwrap(), Fr::one());
+        assert_eq!(
+            Reader(&r_fq).montgomery::<ark_bn254::Fq>().unwrap(),
+            ark_bn254::Fq::one()
+        );
+
+        // [1]_1 = (1, 2): (R, 2R) mod q, and the point at infinity all zeros
+        let generator = [
+            r_fq,
+            le("1c14ef83340fbe5eccdd46def0f28c5814f1d651eb8e167ba6ba871b8b1e1b3a"),
+        ]
+        .concat();
+        let g1 = ark_bn254::G1Affine::generator();
+        assert_eq!(point(&g1), generator);
+        let read = read_point::<ark_bn254::G1Projective>(&mut Reader(&generator)).unwrap();
+        assert_eq!(read.into_affine(), g1);
+        let infinity = read_point::<ark_bn254::G1Projective>(&mut Reader(&[0u8; 64])).unwrap();
+        assert!(infinity.is_zero());
+    }
+
+    #[test]
+    fn test_fflonk_zkey() {
+        let rng = &mut ark_std::test_rng();
+        // (x + 2y + 3z)·y = out, on the wires [1, out, x, y, z]
+        let r1cs = R1CS {
+            n_wires: 5,
+            n_pub_out: 1,
+            n_pub_in: 0,
+            n_prv_in: 3,
+            constraints: vec![[
+                vec![(2, Fr::one()), (3, Fr::from(2u64)), (4, Fr::from(3u64))],
+                vec![(3, Fr::one())],
+                vec![(1, Fr::one())],
+            ]],
+        };
+        let circom = CircomCircuit::from_r1cs(&r1cs);
+        let srs = Srs::from_trapdoor(Fr::rand(rng), Srs::<Bn254>::size_for(3));
+        let pk = fflonk_setup(&circom.circuit, &srs).unwrap();
+        let bytes = zkey_bytes(&circom, &pk, &srs);
+
+        let zkey = FflonkZKey::<Bn254>::from_bytes(&bytes, false).unwrap();
+        assert_eq!(zkey.vk, pk.vk);
+        assert_eq!((zkey.n_vars, zkey.n_public), (7, 1));
+        assert_eq!((zkey.n_additions, zkey.n_constraints), (2, 4));
+        assert!(zkey.proving.is_none());
+
+        let proving = FflonkZKey::<Bn254>::from_bytes(&bytes, true)
+            .unwrap()
+            .proving
+            .unwrap();
+        assert_eq!(proving.srs, srs);
+        assert_eq!(proving.additions, circom.additions);
+        assert_eq!(proving.pk.c0, pk.c0);
+        assert_eq!(proving.pk.sigma, pk.sigma);
+        assert_eq!(
+            [&proving.pk.ql, &proving.pk.qm, &proving.pk.s3],
+            [&pk.ql, &pk.qm, &pk.s3]
+        );
+
+        let (x, y, z) = (Fr::from(2u64), Fr::from(10u64), Fr::from(4u64));
+        let out = (x + Fr::from(2u64) * y + Fr::from(3u64) * z) * y;
+        let wires = [Fr::one(), out, x, y, z];
+        let witness = proving.witness(&wires).unwrap();
+        assert_eq!(witness, circom.witness(&wires).unwrap());
+        let proof = fflonk_prover::<_, Keccak256TranscriptHash, _>(
+            &proving.pk,
+            &proving.srs,
+            &witness,
+            &out,
+            rng,
+        )
+        .unwrap();
+        assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+            &zkey.vk, &proof, &out, false
+        ));
+
+        // not fflonk, and truncated
+        let mut plonk = bytes.clone();
+        plonk[24] = 2;
+        assert!(FflonkZKey::<Bn254>::from_bytes(&plonk, false).is_err());
+        assert!(FflonkZKey::<Bn254>::from_bytes(&bytes[..bytes.len() - 1], false).is_err());
+
+        // 2 public inputs
+        let mut two_public = circom.clone();
+        two_public.circuit.n_public = 2;
+        let bytes = zkey_bytes(&two_public, &pk, &srs);
+        assert!(FflonkZKey::<Bn254>::from_bytes(&bytes, false).is_err());
+
+        // a domain of 2^29 rows, over the 2^28 roots of unity of BN254: the domain size follows
+        // the 12 bytes of the file header, the protocol section, the section header, both primes
+        // and n_vars || n_public
+        let mut too_large = zkey_bytes(&circom, &pk, &srs);
+        let offset = 12 + (12 + 4) + 12 + 2 * (4 + 32) + 2 * 4;
+        too_large[offset..offset + 4].copy_from_slice(&(1u32 << 29).to_le_bytes());
+        let err = FflonkZKey::<Bn254>::from_bytes(&too_large, false).unwrap_err();
+        assert!(err.to_string().contains("2-adicity"));
+    }
+}
@@ -0,0 +1,90 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::One;
+use ark_fflonk_verifier::mock::multiplier2;
+use ark_fflonk_verifier::pairing::ArkworksBackend;
+use ark_fflonk_verifier::proof::Proof;
+use ark_fflonk_verifier::prover::{fflonk_prover, Srs};
+use ark_fflonk_verifier::setup::fflonk_setup;
+use ark_fflonk_verifier::transcript::{Blake3TranscriptHash, Keccak256TranscriptHash};
+use ark_fflonk_verifier::utils::load_public_input;
+use ark_fflonk_verifier::verifier::fflonk_verifier_with_backend;
+use ark_fflonk_verifier::vk::{Omega, SnarkJSVK, VerificationKey};
+use ark_std::UniformRand;
+use std::path::{Path, PathBuf};
+
+// The fixture under resources/bls12-381/ is an honest proof of Multiplier2 over BLS12-381, see
+// resources/bls12-381/README.md.
+
+// cargo test --features test-utils bls12_381_fflonk_proof_verifier  -- --nocapture
+#[test]
+fn bls12_381_fflonk_proof_verifier() {
+    let path = fixture_dir();
+    let snarkjs_vk = SnarkJSVK::<Bls12_381>::load(path.join("verification_key.json")).unwrap();
+    let vk: VerificationKey<Bls12_381> = snarkjs_vk.into();
+    let proof = Proof::<Bls12_381>::load(path.join("proof.json")).unwrap();
+    let pubs: Fr = load_public_input(path.join("public.json")).unwrap();
+
+    assert_eq!(vk.omega, Omega::new(vk.power).unwrap());
+    assert_eq!(pubs, Fr::from(33u64));
+    assert!(
+        fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &proof,
+            &pubs
+        )
+    );
+    assert!(
+        !fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &proof,
+            &(pubs + Fr::one())
+        )
+    );
+    assert!(!fflonk_verifier_with_backend::<_, Blake3TranscriptHash, _>(
+        &ArkworksBackend,
+        &vk,
+        &proof,
+        &pubs
+    ));
+}
+
+// Regenerate the fixture:
+//      cargo test --features test-utils generate_bls12_381_fixture -- --ignored
+#[test]
+#[ignore]
+fn generate_bls12_381_fixture() {
+    let rng = &mut ark_std::test_rng();
+    // c = a·b on the signals [1, c, a, b] of resources/circom/input.json
+    let (a, b) = (Fr::from(3u64), Fr::from(11u64));
+    let circuit = multiplier2::<Fr>();
+    let srs = Srs::<Bls12_381>::from_trapdoor(Fr::rand(rng), Srs::<Bls12_381>::size_for(3));
+    let pk = fflonk_setup(&circuit, &srs).unwrap();
+    let witness = circuit.witness(&[Fr::one(), a * b, a, b]).unwrap();
+    let proof =
+        fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &(a * b), rng).unwrap();
+
+    let path = fixture_dir();
+    std::fs::create_dir_all(&path).unwrap();
+
+    let mut vk_json = serde_json::to_value(SnarkJSVK::from(&pk.vk)).unwrap();
+    let vk_fields = vk_json.as_object_mut().unwrap();
+    vk_fields.insert("protocol".to_string(), "fflonk".into());
+    vk_fields.insert("curve".to_string(), "bls12381".into());
+    vk_fields.insert("nPublic".to_string(), 1.into());
+    write_json(&path.join("verification_key.json"), &vk_json);
+    write_json(&path.join("proof.json"), &proof);
+    write_json(&path.join("public.json"), &[(a * b).to_string()]);
+}
+
+fn fixture_dir() -> PathBuf {
+    std::env::current_dir()
+        .unwrap()
+        .join("resources/bls12-381/")
+}
+
+fn write_json<T: serde::Serialize + ?Sized>(path: &Path, value: &T) {
+    let json = serde_json::to_string_pretty(value).unwrap();
+    std::fs::write(path, json + "\n").unwrap();
+}
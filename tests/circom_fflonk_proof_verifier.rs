@@ -1,9 +1,14 @@
 use ark_fflonk_verifier::proof::Proof;
-use ark_fflonk_verifier::transcript::{Blake3TranscriptHash, Keccak256TranscriptHash};
+use ark_fflonk_verifier::transcript::{
+    Blake3TranscriptHash, Keccak256TranscriptHash, TranscriptKind,
+};
 use ark_fflonk_verifier::utils::load_public_input;
-use ark_fflonk_verifier::verifier::fflonk_verifier;
-use ark_fflonk_verifier::vk::{SnarkJSVK, VerificationKey};
+use ark_fflonk_verifier::verifier::{
+    fflonk_verifier, fflonk_verifier_detect_transcript, fflonk_verifier_with_transcript,
+};
+use ark_fflonk_verifier::vk::{Omega, SnarkJSVK, VerificationKey};
 use ark_std::{end_timer, start_timer};
+use std::str::FromStr;
 
 // cargo test circom_fflonk_proof_verifier  -- --nocapture
 #[test]
@@ -23,6 +28,8 @@ fn circom_fflonk_proof_verifier_keccak256() {
     let proof_file = circom_file_path.join("proof.json");
     let snarkjs_vk = SnarkJSVK::load(vk_file).unwrap();
     let vk: VerificationKey = snarkjs_vk.into();
+    // the omegas of snarkjs are the ones of `Omega::new`
+    assert_eq!(vk.omega, Omega::new(vk.power).unwrap());
     let proof = Proof::load(proof_file).unwrap();
     let pubs = load_public_input(public_file).unwrap();
     let res = fflonk_verifier::<Keccak256TranscriptHash>(&vk, &proof, &pubs, false);
@@ -50,3 +57,66 @@ fn circom_fflonk_proof_verifier_blake3() {
     assert!(res);
     end_timer!(start);
 }
+
+// cargo test circom_fflonk_proof_verifier_with_transcript  -- --nocapture
+#[test]
+fn circom_fflonk_proof_verifier_with_transcript() {
+    // (resources, transcript name from metadata)
+    let cases = [
+        ("resources/circom/", "keccak256"),
+        ("resources/circom-blake3/", "blake3"),
+    ];
+
+    let current_dir = std::env::current_dir().unwrap();
+    for (dir, transcript) in cases {
+        let circom_file_path = current_dir.join(dir);
+
+        let snarkjs_vk = SnarkJSVK::load(circom_file_path.join("verification_key.json")).unwrap();
+        let vk: VerificationKey = snarkjs_vk.into();
+        let proof = Proof::load(circom_file_path.join("proof.json")).unwrap();
+        let pubs = load_public_input(circom_file_path.join("public.json")).unwrap();
+
+        let transcript = TranscriptKind::from_str(transcript).unwrap();
+        assert!(fflonk_verifier_with_transcript(
+            transcript, &vk, &proof, &pubs, false
+        ));
+
+        // the other transcript derives other challenges, so the inverse check must fail.
+        let other = TranscriptKind::ALL
+            .into_iter()
+            .find(|kind| *kind != transcript)
+            .unwrap();
+        assert!(!fflonk_verifier_with_transcript(
+            other, &vk, &proof, &pubs, false
+        ));
+    }
+}
+
+// cargo test circom_fflonk_proof_verifier_detect_transcript  -- --nocapture
+#[test]
+fn circom_fflonk_proof_verifier_detect_transcript() {
+    let cases = [
+        ("resources/circom/", TranscriptKind::Keccak256),
+        ("resources/circom-blake3/", TranscriptKind::Blake3),
+    ];
+
+    let current_dir = std::env::current_dir().unwrap();
+    for (dir, expect) in cases {
+        let circom_file_path = current_dir.join(dir);
+
+        let snarkjs_vk = SnarkJSVK::load(circom_file_path.join("verification_key.json")).unwrap();
+        let vk: VerificationKey = snarkjs_vk.into();
+        let mut proof = Proof::load(circom_file_path.join("proof.json")).unwrap();
+        let pubs = load_public_input(circom_file_path.join("public.json")).unwrap();
+
+        let res = fflonk_verifier_detect_transcript(&vk, &proof, &pubs, false);
+        assert_eq!(res, Some((expect, true)));
+
+        // tamper the inverse: none of the transcripts matches.
+        proof.evaluations.inv += ark_bn254::Fr::from(1u64);
+        assert_eq!(
+            fflonk_verifier_detect_transcript(&vk, &proof, &pubs, false),
+            None
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use ark_bn254::{Bn254, Fr};
+use ark_fflonk_verifier::challenge::Challenges;
+use ark_fflonk_verifier::mock::multiplier2;
+use ark_fflonk_verifier::proof::Proof;
+use ark_fflonk_verifier::prover::Srs;
+use ark_fflonk_verifier::ptau::Ptau;
+use ark_fflonk_verifier::setup::fflonk_setup;
+use ark_fflonk_verifier::transcript::Keccak256TranscriptHash;
+use ark_fflonk_verifier::utils::load_public_input;
+use ark_fflonk_verifier::vk::{SnarkJSVK, VerificationKey};
+use ark_fflonk_verifier::zkey::FflonkZKey;
+use ark_poly::Polynomial;
+
+// cargo test --features test-utils circom_fflonk_setup  -- --nocapture
+//
+// C0 and X_2 come from the powers of tau of the ceremony, which `resources/` doesn't have: the
+// polynomials of C0 are checked through their evaluations at the xi of the proof instead, see
+// `circom_fflonk_setup_ptau` for C0 and X_2 themselves.
+#[test]
+fn circom_fflonk_setup() {
+    let current_dir = std::env::current_dir().unwrap();
+    // any SRS gives the same polynomials
+    let srs = Srs::<Bn254>::from_trapdoor(Fr::from(7u64), Srs::<Bn254>::size_for(3));
+    let pk = fflonk_setup(&multiplier2(), &srs).unwrap();
+
+    let circom_file_path = current_dir.join("resources/circom/");
+    let expect = SnarkJSVK::load(circom_file_path.join("verification_key.json")).unwrap();
+    let actual = SnarkJSVK::from(&pk.vk);
+    assert_eq!(
+        (actual.power, actual.k1, actual.k2),
+        (expect.power, expect.k1, expect.k2)
+    );
+    assert_eq!(
+        (actual.w, actual.w3, actual.w4, actual.w8, actual.wr),
+        (expect.w, expect.w3, expect.w4, expect.w8, expect.wr)
+    );
+    let vk = VerificationKey::from(expect);
+    assert_eq!(
+        (pk.vk.power, pk.vk.n, pk.vk.k1, pk.vk.k2),
+        (vk.power, vk.n, vk.k1, vk.k2)
+    );
+    assert_eq!(pk.vk.omega, vk.omega);
+    assert_eq!(pk.vk.g2, vk.g2);
+
+    let proof = Proof::load(circom_file_path.join("proof.json")).unwrap();
+    let pub_input = load_public_input(circom_file_path.join("public.json")).unwrap();
+    let xi = Challenges::compute::<Keccak256TranscriptHash>(&vk, &proof, &pub_input).xi;
+    let evaluations = &proof.evaluations;
+    assert_eq!(pk.ql.evaluate(&xi), evaluations.ql);
+    assert_eq!(pk.qr.evaluate(&xi), evaluations.qr);
+    assert_eq!(pk.qm.evaluate(&xi), evaluations.qm);
+    assert_eq!(pk.qo.evaluate(&xi), evaluations.qo);
+    assert_eq!(pk.qc.evaluate(&xi), evaluations.qc);
+    assert_eq!(pk.s1.evaluate(&xi), evaluations.s1);
+    assert_eq!(pk.s2.evaluate(&xi), evaluations.s2);
+    assert_eq!(pk.s3.evaluate(&xi), evaluations.s3);
+}
+
+// C0 and X_2 of the setup over the powers of tau of the ceremony, a `.ptau` of the Hermez
+// ceremony, eg: powersOfTau28_hez_final_08.ptau, which isn't tracked:
+//      CIRCOM_PTAU=<path to the .ptau> cargo test --features test-utils circom_fflonk_setup_ptau -- --ignored
+#[test]
+#[ignore]
+fn circom_fflonk_setup_ptau() {
+    let ptau_path = std::env::var_os("CIRCOM_PTAU").expect("CIRCOM_PTAU isn't set");
+    let ptau = Ptau::<Bn254>::load(ptau_path, Srs::<Bn254>::size_for(3)).unwrap();
+    ptau.check(&mut ark_std::test_rng()).unwrap();
+    let pk = fflonk_setup(&multiplier2(), &ptau.srs()).unwrap();
+
+    let circom_file_path = std::env::current_dir().unwrap().join("resources/circom/");
+    let vk = ptau
+        .verification_key(SnarkJSVK::load(circom_file_path.join("verification_key.json")).unwrap())
+        .unwrap();
+    assert_eq!(pk.vk.c0, vk.c0);
+    assert_eq!(pk.vk.x2, vk.x2);
+}
+
+// The `.zkey` of `snarkjs fflonk setup` for `resources/circom/circuit.circom`, which isn't tracked:
+//      CIRCOM_ZKEY=<path to the .zkey> cargo test --features test-utils circom_fflonk_zkey -- --ignored
+#[test]
+#[ignore]
+fn circom_fflonk_zkey() {
+    let zkey_path = std::env::var_os("CIRCOM_ZKEY").expect("CIRCOM_ZKEY isn't set");
+    let zkey = FflonkZKey::<Bn254>::load(zkey_path, false).unwrap();
+    let circom_file_path = std::env::current_dir().unwrap().join("resources/circom/");
+    let vk: VerificationKey = SnarkJSVK::load(circom_file_path.join("verification_key.json"))
+        .unwrap()
+        .into();
+    assert_eq!(zkey.vk, vk);
+    assert_eq!(zkey.n_public, 1);
+}
@@ -0,0 +1,337 @@
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use ark_fflonk_verifier::challenge::Challenges;
+use ark_fflonk_verifier::circom::{
+    wtns_from_bytes, CircomCircuit, LinearCombination, PlonkAddition, R1CS,
+};
+use ark_fflonk_verifier::proof::Proof;
+use ark_fflonk_verifier::prover::{fflonk_prover, Srs};
+use ark_fflonk_verifier::ptau::Ptau;
+use ark_fflonk_verifier::setup::{fflonk_setup, PlonkConstraint};
+use ark_fflonk_verifier::transcript::Keccak256TranscriptHash;
+use ark_fflonk_verifier::utils::load_public_input;
+use ark_fflonk_verifier::verifier::fflonk_verifier;
+use ark_fflonk_verifier::vk::{SnarkJSVK, VerificationKey};
+use ark_poly::Polynomial;
+
+// The binary files of circom, see https://github.com/iden3/r1csfile and
+// https://github.com/iden3/snarkjs/blob/master/src/wtns_utils.js
+fn binfile(magic: &[u8; 4], version: u32, sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut bytes = magic.to_vec();
+    bytes.extend(version.to_le_bytes());
+    bytes.extend((sections.len() as u32).to_le_bytes());
+    for (section_type, data) in sections {
+        bytes.extend(section_type.to_le_bytes());
+        bytes.extend((data.len() as u64).to_le_bytes());
+        bytes.extend(data);
+    }
+    bytes
+}
+
+fn prime() -> Vec<u8> {
+    let mut bytes = 32u32.to_le_bytes().to_vec();
+    bytes.extend(Fr::MODULUS.to_bytes_le());
+    bytes
+}
+
+fn fr_bytes(fr: &Fr) -> Vec<u8> {
+    fr.into_bigint().to_bytes_le()
+}
+
+fn r1cs_bytes(
+    n_wires: u32,
+    [n_pub_out, n_pub_in, n_prv_in]: [u32; 3],
+    constraints: &[[LinearCombination<Fr>; 3]],
+) -> Vec<u8> {
+    let mut header = prime();
+    for value in [n_wires, n_pub_out, n_pub_in, n_prv_in] {
+        header.extend(value.to_le_bytes());
+    }
+    header.extend((n_wires as u64).to_le_bytes());
+    header.extend((constraints.len() as u32).to_le_bytes());
+
+    let mut data = vec![];
+    for lc in constraints.iter().flatten() {
+        data.extend((lc.len() as u32).to_le_bytes());
+        for (wire, coef) in lc {
+            data.extend((*wire as u32).to_le_bytes());
+            data.extend(fr_bytes(coef));
+        }
+    }
+    // the wire to label map
+    let labels = (0..n_wires as u64).flat_map(u64::to_le_bytes).collect();
+
+    binfile(b"r1cs", 1, &[(1, header), (2, data), (3, labels)])
+}
+
+fn wtns_bytes(wires: &[Fr]) -> Vec<u8> {
+    let mut header = prime();
+    header.extend((wires.len() as u32).to_le_bytes());
+    let data = wires.iter().flat_map(fr_bytes).collect();
+    binfile(b"wtns", 2, &[(1, header), (2, data)])
+}
+
+// The PLONK row of the signals, with the selectors [ql, qr, qm, qo, qc].
+fn row(signals: [usize; 3], [ql, qr, qm, qo, qc]: [i64; 5]) -> PlonkConstraint<Fr> {
+    PlonkConstraint {
+        signals,
+        qm: Fr::from(qm),
+        ql: Fr::from(ql),
+        qr: Fr::from(qr),
+        qo: Fr::from(qo),
+        qc: Fr::from(qc),
+    }
+}
+
+fn addition(signals: [usize; 2], [c1, c2]: [i64; 2]) -> PlonkAddition<Fr> {
+    PlonkAddition {
+        signals,
+        coefs: [Fr::from(c1), Fr::from(c2)],
+    }
+}
+
+// Multiplier2 of `resources/circom/circuit.circom` as circom compiles it, on the wires
+// [1, c, a, b]:
+//      [-a]·[b] - [-c] = 0
+fn multiplier2() -> Vec<u8> {
+    let constraint = [
+        vec![(2, -Fr::one())],
+        vec![(3, Fr::one())],
+        vec![(1, -Fr::one())],
+    ];
+    r1cs_bytes(4, [1, 0, 2], &[constraint])
+}
+
+// cargo test circom_r1cs_multiplier2  -- --nocapture
+//
+// C0 is the commitment of the selectors over the powers of tau of the ceremony, which
+// `resources/` doesn't have: the selectors are checked through their evaluations at the xi of
+// the proof, which C0 binds them to, and a test SRS proves with the witness. See
+// `circom_r1cs_circuit` for the `.r1cs` of circom and C0 themselves.
+#[test]
+fn circom_r1cs_multiplier2() {
+    let r1cs = R1CS::<Fr>::from_bytes(&multiplier2()).unwrap();
+    assert_eq!((r1cs.n_wires, r1cs.n_public()), (4, 1));
+    let circom = CircomCircuit::from_r1cs(&r1cs);
+    assert!(circom.additions.is_empty());
+
+    let srs = Srs::<Bn254>::from_trapdoor(Fr::from(7u64), Srs::<Bn254>::size_for(3));
+    let pk = fflonk_setup(&circom.circuit, &srs).unwrap();
+
+    let circom_file_path = std::env::current_dir().unwrap().join("resources/circom/");
+    let vk: VerificationKey = SnarkJSVK::load(circom_file_path.join("verification_key.json"))
+        .unwrap()
+        .into();
+    assert_eq!((pk.vk.power, pk.vk.k1, pk.vk.k2), (vk.power, vk.k1, vk.k2));
+    assert_eq!(pk.vk.omega, vk.omega);
+
+    let proof = Proof::load(circom_file_path.join("proof.json")).unwrap();
+    let pub_input: Fr = load_public_input(circom_file_path.join("public.json")).unwrap();
+    let xi = Challenges::compute::<Keccak256TranscriptHash>(&vk, &proof, &pub_input).xi;
+    let evaluations = &proof.evaluations;
+    let expect = [
+        evaluations.ql,
+        evaluations.qr,
+        evaluations.qm,
+        evaluations.qo,
+        evaluations.qc,
+        evaluations.s1,
+        evaluations.s2,
+        evaluations.s3,
+    ];
+    let actual = [
+        &pk.ql, &pk.qr, &pk.qm, &pk.qo, &pk.qc, &pk.s1, &pk.s2, &pk.s3,
+    ]
+    .map(|p| p.evaluate(&xi));
+    assert_eq!(actual, expect);
+
+    // {"a": 3, "b": 11} of `resources/circom/input.json`
+    let wires = wtns_from_bytes::<Fr>(&wtns_bytes(&[1u64, 33, 3, 11].map(Fr::from))).unwrap();
+    let witness = circom.witness(&wires).unwrap();
+    let rng = &mut ark_std::test_rng();
+    let proof =
+        fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &pub_input, rng)
+            .unwrap();
+    assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+        &pk.vk, &proof, &pub_input, false
+    ));
+}
+
+// `resources/circom/circuit.circom` compiled by circom, and C0 of its setup over the powers of
+// tau of the ceremony, see `circom_fflonk_setup_ptau`. Neither file is tracked:
+//      circom resources/circom/circuit.circom --r1cs -o <dir>
+//      CIRCOM_R1CS=<dir>/circuit.r1cs CIRCOM_PTAU=<path to the .ptau> \
+//          cargo test circom_r1cs_circuit -- --ignored
+#[test]
+#[ignore]
+fn circom_r1cs_circuit() {
+    let r1cs_path = std::env::var_os("CIRCOM_R1CS").expect("CIRCOM_R1CS isn't set");
+    let ptau_path = std::env::var_os("CIRCOM_PTAU").expect("CIRCOM_PTAU isn't set");
+    let r1cs = R1CS::<Fr>::load(r1cs_path).unwrap();
+    // the constraints of `multiplier2` are the ones circom compiles
+    let expect = R1CS::<Fr>::from_bytes(&multiplier2()).unwrap();
+    assert_eq!(
+        (r1cs.n_wires, r1cs.n_pub_out, r1cs.n_pub_in, r1cs.n_prv_in),
+        (
+            expect.n_wires,
+            expect.n_pub_out,
+            expect.n_pub_in,
+            expect.n_prv_in
+        )
+    );
+    assert_eq!(r1cs.constraints, expect.constraints);
+
+    let ptau = Ptau::<Bn254>::load(ptau_path, Srs::<Bn254>::size_for(3)).unwrap();
+    let pk = fflonk_setup(&CircomCircuit::from_r1cs(&r1cs).circuit, &ptau.srs()).unwrap();
+    let circom_file_path = std::env::current_dir().unwrap().join("resources/circom/");
+    let vk = ptau
+        .verification_key(SnarkJSVK::load(circom_file_path.join("verification_key.json")).unwrap())
+        .unwrap();
+    assert_eq!(pk.vk.c0, vk.c0);
+}
+
+// cargo test circom_r1cs_linear_combinations  -- --nocapture
+#[test]
+fn circom_r1cs_linear_combinations() {
+    // (5 + x + 2y + 3z)·(y - z) = out + 7, on the wires [1, out, x, y, z]
+    let constraint = [
+        vec![
+            (0, Fr::from(5u64)),
+            (2, Fr::one()),
+            (3, Fr::from(2u64)),
+            (4, Fr::from(3u64)),
+        ],
+        vec![(3, Fr::one()), (4, -Fr::one())],
+        vec![(1, Fr::one()), (0, Fr::from(7u64))],
+    ];
+    let r1cs = R1CS::<Fr>::from_bytes(&r1cs_bytes(5, [1, 0, 3], &[constraint])).unwrap();
+    let circom = CircomCircuit::from_r1cs(&r1cs);
+    // as `processR1csMultiplicationConstraint` of snarkjs reduces every side to a single signal:
+    // x + 2y into s5, then 3z + s5 into s6, and y - z into s7
+    assert_eq!(
+        circom.additions,
+        [
+            addition([2, 3], [1, 2]),
+            addition([4, 5], [3, 1]),
+            addition([3, 4], [1, -1]),
+        ]
+    );
+    assert_eq!(circom.circuit.n_vars, 8);
+    assert_eq!(
+        circom.circuit.constraints,
+        [
+            row([2, 3, 5], [-1, -2, 0, 1, 0]),
+            row([4, 5, 6], [-3, -1, 0, 1, 0]),
+            row([3, 4, 7], [-1, 1, 0, 1, 0]),
+            // (5 + s6)·s7 - (out + 7): ql = 0·1, qr = 5·1, qm = 1·1, qo = -1, qc = 5·0 - 7
+            row([6, 7, 1], [0, 5, 1, -1, -7]),
+        ]
+    );
+
+    let (x, y, z) = (Fr::from(2u64), Fr::from(10u64), Fr::from(4u64));
+    let out =
+        (Fr::from(5u64) + x + Fr::from(2u64) * y + Fr::from(3u64) * z) * (y - z) - Fr::from(7u64);
+    let srs = Srs::<Bn254>::from_trapdoor(Fr::from(7u64), Srs::<Bn254>::size_for(3));
+    let pk = fflonk_setup(&circom.circuit, &srs).unwrap();
+    let rng = &mut ark_std::test_rng();
+
+    let witness = circom.witness(&[Fr::one(), out, x, y, z]).unwrap();
+    let proof =
+        fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &out, rng).unwrap();
+    assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+        &pk.vk, &proof, &out, false
+    ));
+
+    let witness = circom.witness(&[Fr::one(), out, x, y, y]).unwrap();
+    assert!(
+        fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &out, rng).is_err()
+    );
+}
+
+// cargo test circom_r1cs_linear_constraints  -- --nocapture
+#[test]
+fn circom_r1cs_linear_constraints() {
+    // On the wires [1, out, x, y, z, t]:
+    //      0·y - (3 - out + 3x + 2y + 2 + z) = 0, ie: out = 3x + 2y + z + 5
+    //      2·(x + y + 0·t) - t = 0
+    let constraints = [
+        [
+            vec![(0, Fr::zero())],
+            vec![(3, Fr::one())],
+            vec![
+                (0, Fr::from(3u64)),
+                (1, -Fr::one()),
+                (2, Fr::from(3u64)),
+                (3, Fr::from(2u64)),
+                (0, Fr::from(2u64)),
+                (4, Fr::one()),
+            ],
+        ],
+        [
+            vec![(0, Fr::from(2u64))],
+            vec![(2, Fr::one()), (3, Fr::one()), (5, Fr::zero())],
+            vec![(5, Fr::one())],
+        ],
+    ];
+    let r1cs = R1CS::<Fr>::from_bytes(&r1cs_bytes(6, [1, 0, 3], &constraints)).unwrap();
+    let circom = CircomCircuit::from_r1cs(&r1cs);
+    // as `processR1csAdditionConstraint` of snarkjs: the zero terms dropped, the constants summed,
+    // and C reduced to 3 signals, -out + 3x into s6; then 2·B - C = 0, of 3 signals already
+    assert_eq!(circom.additions, [addition([1, 2], [-1, 3])]);
+    assert_eq!(circom.circuit.n_vars, 7);
+    assert_eq!(
+        circom.circuit.constraints,
+        [
+            row([1, 2, 6], [1, -3, 0, 1, 0]),
+            row([3, 4, 6], [2, 1, 0, 1, 5]),
+            row([2, 3, 5], [2, 2, 0, -1, 0]),
+        ]
+    );
+
+    let (x, y, z) = (Fr::from(2u64), Fr::from(10u64), Fr::from(4u64));
+    let out = Fr::from(3u64) * x + Fr::from(2u64) * y + z + Fr::from(5u64);
+    let t = Fr::from(2u64) * (x + y);
+    let srs = Srs::<Bn254>::from_trapdoor(Fr::from(7u64), Srs::<Bn254>::size_for(3));
+    let pk = fflonk_setup(&circom.circuit, &srs).unwrap();
+    let rng = &mut ark_std::test_rng();
+
+    let witness = circom.witness(&[Fr::one(), out, x, y, z, t]).unwrap();
+    let proof =
+        fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &out, rng).unwrap();
+    assert!(fflonk_verifier::<Keccak256TranscriptHash>(
+        &pk.vk, &proof, &out, false
+    ));
+
+    let witness = circom
+        .witness(&[Fr::one(), out, x, y, z, t + Fr::one()])
+        .unwrap();
+    assert!(
+        fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &out, rng).is_err()
+    );
+}
+
+// cargo test circom_r1cs_invalid_files  -- --nocapture
+#[test]
+fn circom_r1cs_invalid_files() {
+    let bytes = multiplier2();
+    assert!(R1CS::<Fr>::from_bytes(&bytes).is_ok());
+    // truncated
+    assert!(R1CS::<Fr>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    // not a r1cs file
+    assert!(wtns_from_bytes::<Fr>(&bytes).is_err());
+    // another prime: the one of BLS12-381, or a changed one after the magic, version and the
+    // header of the section
+    assert!(R1CS::<ark_bls12_381::Fr>::from_bytes(&bytes).is_err());
+    let mut other = bytes.clone();
+    other[28] ^= 1;
+    assert!(R1CS::<Fr>::from_bytes(&other).is_err());
+
+    // a wire out of the wires
+    let constraint = [vec![(4, Fr::one())], vec![], vec![]];
+    assert!(R1CS::<Fr>::from_bytes(&r1cs_bytes(4, [1, 0, 2], &[constraint])).is_err());
+
+    // the wire 0 isn't 1
+    let circom = CircomCircuit::from_r1cs(&R1CS::<Fr>::from_bytes(&bytes).unwrap());
+    assert!(circom.witness(&[0u64, 33, 3, 11].map(Fr::from)).is_err());
+    assert!(circom.witness(&[1u64, 33, 3].map(Fr::from)).is_err());
+}
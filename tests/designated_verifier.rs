@@ -0,0 +1,149 @@
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{One, UniformRand, Zero};
+use ark_fflonk_verifier::pairing::{
+    check_pairing_with_w2, ArkworksBackend, DesignatedVerifierBackend, PairingBackend,
+};
+use ark_fflonk_verifier::proof::Proof;
+use ark_fflonk_verifier::simulator::Simulator;
+use ark_fflonk_verifier::transcript::Keccak256TranscriptHash;
+use ark_fflonk_verifier::verifier::{fflonk_designated_verifier, fflonk_verifier};
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use proptest::prelude::*;
+
+// The designated verifier knows the trapdoor x of X_2 = x·[1]_2, so it checks A1 = x·W2
+// instead of e(A1, [1]_2)·e(W2, -X_2) = 1: both must always agree.
+
+// How A1 relates to W2.
+#[derive(Debug, Clone, Copy)]
+enum A1Kind {
+    // A1 = x·W2, which passes
+    Valid,
+    // A1 = x·W2 + G, with G random
+    Shifted,
+    // A1 = (x + 1)·W2
+    WrongScalar,
+    Random,
+    Zero,
+}
+
+fn a1_kind() -> impl Strategy<Value = A1Kind> {
+    prop_oneof![
+        Just(A1Kind::Valid),
+        Just(A1Kind::Shifted),
+        Just(A1Kind::WrongScalar),
+        Just(A1Kind::Random),
+        Just(A1Kind::Zero),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn designated_check_agrees_with_pairing(seed: u64, kind in a1_kind(), zero_w2: bool) {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let simulator = Simulator::<Bn254>::new(3, Fr::rand(rng), rng).unwrap();
+        let (vk, x) = (&simulator.vk, simulator.trapdoor);
+
+        let w2 = if zero_w2 { G1Affine::zero() } else { G1Affine::rand(rng) };
+        let a1 = match kind {
+            A1Kind::Valid => w2 * x,
+            A1Kind::Shifted => w2 * x + G1Affine::rand(rng),
+            A1Kind::WrongScalar => w2 * (x + Fr::one()),
+            A1Kind::Random => G1Affine::rand(rng).into_group(),
+            A1Kind::Zero => G1Projective::zero(),
+        }
+        .into_affine();
+
+        let backend = DesignatedVerifierBackend::new(vk, x).unwrap();
+        prop_assert_eq!(
+            backend.check_pairing(vk, &a1, &w2),
+            check_pairing_with_w2(vk, &a1, &w2)
+        );
+        prop_assert_eq!(
+            backend.check_pairing(vk, &a1, &w2),
+            ArkworksBackend.check_pairing(vk, &a1, &w2)
+        );
+    }
+
+    #[test]
+    fn designated_verifier_agrees_with_verifier(
+        seed: u64,
+        tamper in proptest::option::of(0usize..17),
+    ) {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let simulator = Simulator::<Bn254>::new(3, Fr::rand(rng), rng).unwrap();
+        let (vk, x) = (&simulator.vk, simulator.trapdoor);
+        let pub_input = Fr::from(33u64);
+        let mut proof = simulate(&simulator, rng, &pub_input);
+
+        // perturb one of the evaluations, or W2
+        if let Some(i) = tamper {
+            let evaluations = &mut proof.evaluations;
+            let fields = [
+                &mut evaluations.ql, &mut evaluations.qr, &mut evaluations.qm, &mut evaluations.qo,
+                &mut evaluations.qc, &mut evaluations.s1, &mut evaluations.s2, &mut evaluations.s3,
+                &mut evaluations.a, &mut evaluations.b, &mut evaluations.c, &mut evaluations.z,
+                &mut evaluations.zw, &mut evaluations.t1w, &mut evaluations.t2w, &mut evaluations.inv,
+            ];
+            match fields.into_iter().nth(i) {
+                Some(field) => *field += Fr::one(),
+                None => proof.polynomials.w2 += G1Affine::generator(),
+            }
+        }
+
+        let expected = fflonk_verifier::<Keccak256TranscriptHash>(vk, &proof, &pub_input, false);
+        prop_assert_eq!(expected, tamper.is_none());
+        prop_assert_eq!(
+            fflonk_designated_verifier::<_, Keccak256TranscriptHash>(x, vk, &proof, &pub_input)
+                .unwrap(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn designated_verifier_rejects_wrong_trapdoor() {
+    let rng = &mut StdRng::seed_from_u64(0);
+    let simulator = Simulator::<Bn254>::new(3, Fr::rand(rng), rng).unwrap();
+    let (vk, x) = (simulator.vk.clone(), simulator.trapdoor);
+    let pub_input = Fr::from(33u64);
+    let proof = simulate(&simulator, rng, &pub_input);
+
+    assert!(
+        fflonk_designated_verifier::<_, Keccak256TranscriptHash>(x, &vk, &proof, &pub_input)
+            .unwrap()
+    );
+    assert!(fflonk_designated_verifier::<_, Keccak256TranscriptHash>(
+        x + Fr::one(),
+        &vk,
+        &proof,
+        &pub_input
+    )
+    .is_err());
+}
+
+#[test]
+fn designated_verifier_rejects_other_key() {
+    let rng = &mut StdRng::seed_from_u64(1);
+    let simulator = Simulator::<Bn254>::new(3, Fr::rand(rng), rng).unwrap();
+    let (vk, x) = (simulator.vk.clone(), simulator.trapdoor);
+    let backend = DesignatedVerifierBackend::new(&vk, x).unwrap();
+
+    // A1 = x·W2 passes the key of x only: under another X_2, the pairing of the same A1 and W2 fails
+    let w2 = G1Affine::rand(rng);
+    let a1 = (w2 * x).into_affine();
+    let other_vk = Simulator::<Bn254>::new(3, Fr::rand(rng), rng).unwrap().vk;
+    assert!(backend.check_pairing(&vk, &a1, &w2));
+    assert!(!check_pairing_with_w2(&other_vk, &a1, &w2));
+    assert!(!backend.check_pairing(&other_vk, &a1, &w2));
+}
+
+// A proof simulated with the trapdoor, see `Simulator`.
+fn simulate(simulator: &Simulator, rng: &mut StdRng, pub_input: &Fr) -> Proof {
+    simulator
+        .simulate::<Keccak256TranscriptHash, _>(pub_input, rng)
+        .unwrap()
+}
@@ -0,0 +1,230 @@
+use ark_bn254::{Bn254, Fq, Fr, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_fflonk_verifier::circom::{wtns_from_bytes, R1CS};
+use ark_fflonk_verifier::evm::encode_calldata;
+use ark_fflonk_verifier::fuzz;
+use ark_fflonk_verifier::mock::{MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+use ark_fflonk_verifier::proof::Proof;
+use ark_fflonk_verifier::prover::Srs;
+use ark_fflonk_verifier::ptau::Ptau;
+use ark_fflonk_verifier::utils::load_public_input;
+use ark_fflonk_verifier::vk::{SnarkJSVK, VerificationKey};
+use ark_fflonk_verifier::zkey::FflonkZKey;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// A target of fuzz/, see fuzz/README.md, with the body it runs.
+type Target = (&'static str, fn(&[u8]));
+
+const TARGETS: [Target; 5] = [
+    ("proof_json", fuzz::proof_json),
+    ("vk_json", fuzz::vk_json),
+    ("binfile", fuzz::binfile),
+    ("calldata", fuzz::calldata),
+    ("verifier", fuzz::verifier),
+];
+
+// Every seed of fuzz/seeds/ and every crash of fuzz/regressions/ runs through its target without
+// a panic. With `FUZZ_CORPUS` set, so does any input of the corpus of a local run, which isn't
+// tracked, eg: `fuzz/corpus/verifier`.
+//      FUZZ_CORPUS=1 cargo test --features test-utils fuzz_regressions -- --nocapture
+#[test]
+fn fuzz_regressions() {
+    for (target, run) in TARGETS {
+        let seeds = inputs(&fuzz_dir().join("seeds").join(target));
+        assert!(!seeds.is_empty(), "No seeds for {target}");
+        let regressions = inputs(&fuzz_dir().join("regressions").join(target));
+        let corpus = match std::env::var_os("FUZZ_CORPUS") {
+            Some(_) => inputs(&fuzz_dir().join("corpus").join(target)),
+            None => vec![],
+        };
+        for path in seeds.iter().chain(&regressions).chain(&corpus) {
+            println!("{target}: {}", path.display());
+            run(&std::fs::read(path).unwrap());
+        }
+    }
+}
+
+// Regenerate the seeds from resources/:
+//      cargo test --features test-utils generate_fuzz_seeds -- --ignored
+#[test]
+#[ignore]
+fn generate_fuzz_seeds() {
+    let resources = std::env::current_dir().unwrap().join("resources");
+    let mock = serde_json::to_vec_pretty(&MOCK_PROOF_DATA).unwrap();
+    write_seed("proof_json", "mock.json", &mock);
+    for dir in [
+        "circom",
+        "circom-blake3",
+        "bls12-381",
+        "lookup",
+        "trapdoor/bn254",
+        "trapdoor/bls12-381",
+    ] {
+        let name = format!("{}.json", dir.replace('/', "-"));
+        let proof = std::fs::read(resources.join(dir).join("proof.json")).unwrap();
+        write_seed("proof_json", &name, &proof);
+        let vk = std::fs::read(resources.join(dir).join("verification_key.json")).unwrap();
+        write_seed("vk_json", &name, &vk);
+    }
+
+    // the BN254 proofs of the Keccak256 transcript, the one of the target `verifier` and of the
+    // contract, with the index of their key in `fuzz::VERIFICATION_KEYS`
+    let mut proofs = vec![(
+        "mock",
+        0,
+        Proof::construct(MOCK_PROOF_DATA.to_vec()),
+        Fr::from_str(MOCK_PUB_INPUT).unwrap(),
+    )];
+    for (index, dir) in [(1, "circom"), (2, "trapdoor/bn254")] {
+        let proof = Proof::load(resources.join(dir).join("proof.json")).unwrap();
+        let pub_input = load_public_input(resources.join(dir).join("public.json")).unwrap();
+        proofs.push((dir, index, proof, pub_input));
+    }
+    for (dir, index, proof, pub_input) in &proofs {
+        let name = format!("{}.bin", dir.replace('/', "-"));
+        let calldata = encode_calldata(proof, pub_input);
+        write_seed("calldata", &name, &calldata);
+        let structured = fuzz::encode_structured_proof(*index, proof, pub_input);
+        write_seed("verifier", &name, &structured);
+    }
+
+    // Multiplier2 of resources/circom/circuit.circom on the wires [1, c, a, b] of input.json
+    let (a, b) = (Fr::from(3u64), Fr::from(11u64));
+    write_seed("binfile", "circom.r1cs", &multiplier2_r1cs());
+    write_seed("binfile", "circom.wtns", &wtns(&[Fr::one(), a * b, a, b]));
+    let vk = SnarkJSVK::<Bn254>::load(resources.join("circom/verification_key.json")).unwrap();
+    write_seed("binfile", "circom.zkey", &zkey_header(&vk));
+    write_seed("binfile", "power1.ptau", &ptau(Fr::from(5u64)));
+}
+
+fn fuzz_dir() -> PathBuf {
+    std::env::current_dir().unwrap().join("fuzz")
+}
+
+fn inputs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut paths = entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+fn write_seed(target: &str, name: &str, bytes: &[u8]) {
+    let dir = fuzz_dir().join("seeds").join(target);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(name), bytes).unwrap();
+}
+
+// The binary files of iden3, see https://github.com/iden3/binfileutils
+fn binfile(magic: &[u8; 4], sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut bytes = magic.to_vec();
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend((sections.len() as u32).to_le_bytes());
+    for (section_type, data) in sections {
+        bytes.extend(section_type.to_le_bytes());
+        bytes.extend((data.len() as u64).to_le_bytes());
+        bytes.extend(data);
+    }
+    bytes
+}
+
+fn prime<F: PrimeField>() -> Vec<u8> {
+    [32u32.to_le_bytes().to_vec(), F::MODULUS.to_bytes_le()].concat()
+}
+
+// A field element of ffjavascript in memory, in the Montgomery form.
+fn montgomery<F: Field>(f: &F) -> Vec<u8> {
+    f.to_base_prime_field_elements()
+        .flat_map(|x| {
+            let r = F::BasePrimeField::from(2u64).pow([256]);
+            (x * r).into_bigint().to_bytes_le()
+        })
+        .collect()
+}
+
+fn point<A: AffineRepr>(p: &A) -> Vec<u8> {
+    match p.xy() {
+        Some((x, y)) => [montgomery(x), montgomery(y)].concat(),
+        None => montgomery(&A::BaseField::zero()).repeat(2),
+    }
+}
+
+// [-a]·[b] - [-c] = 0 on the wires [1, c, a, b], as circom compiles Multiplier2.
+fn multiplier2_r1cs() -> Vec<u8> {
+    let mut header = prime::<Fr>();
+    for value in [4u32, 1, 0, 2] {
+        header.extend(value.to_le_bytes());
+    }
+    header.extend(4u64.to_le_bytes());
+    header.extend(1u32.to_le_bytes());
+    let mut data = vec![];
+    for (wire, coef) in [(2u32, -Fr::one()), (3, Fr::one()), (1, -Fr::one())] {
+        data.extend(1u32.to_le_bytes());
+        data.extend(wire.to_le_bytes());
+        data.extend(coef.into_bigint().to_bytes_le());
+    }
+    binfile(b"r1cs", &[(1, header), (2, data)])
+}
+
+fn wtns(wires: &[Fr]) -> Vec<u8> {
+    let mut header = prime::<Fr>();
+    header.extend((wires.len() as u32).to_le_bytes());
+    let data = wires
+        .iter()
+        .flat_map(|wire| wire.into_bigint().to_bytes_le())
+        .collect();
+    binfile(b"wtns", &[(1, header), (2, data)])
+}
+
+// The header sections of the `.zkey` of the key, without the sections for proving.
+fn zkey_header(vk: &SnarkJSVK) -> Vec<u8> {
+    let mut header = [prime::<Fq>(), prime::<Fr>()].concat();
+    // n_vars, n_public, domain_size, n_additions and n_constraints
+    for value in [4, 1, 1u32 << vk.power, 0, 1] {
+        header.extend(value.to_le_bytes());
+    }
+    for f in [vk.k1, vk.k2, vk.w3, vk.w4, vk.w8, vk.wr] {
+        header.extend(montgomery(&f));
+    }
+    header.extend(point(&vk.x2.into_affine()));
+    header.extend(point(&vk.c0.into_affine()));
+    binfile(b"zkey", &[(1, 10u32.to_le_bytes().to_vec()), (2, header)])
+}
+
+// The `.ptau` of a ceremony of power 1 with the trapdoor tau.
+fn ptau(tau: Fr) -> Vec<u8> {
+    let mut header = prime::<Fq>();
+    header.extend(1u32.to_le_bytes());
+    header.extend(1u32.to_le_bytes());
+    let srs = Srs::<Bn254>::from_trapdoor(tau, 3);
+    let tau_g1 = srs.g1_powers.iter().flat_map(point).collect();
+    let tau_g2 = [G2Affine::generator(), srs.x2]
+        .iter()
+        .flat_map(point)
+        .collect();
+    binfile(b"ptau", &[(1, header), (2, tau_g1), (3, tau_g2)])
+}
+
+// cargo test --features test-utils fuzz_seeds_decode -- --nocapture
+#[test]
+fn fuzz_seeds_decode() {
+    // the binary seeds are the files they stand for, which the decoders read through
+    let resources = std::env::current_dir().unwrap().join("resources");
+    let r1cs = R1CS::<Fr>::from_bytes(&multiplier2_r1cs()).unwrap();
+    assert_eq!((r1cs.n_wires, r1cs.n_public()), (4, 1));
+    let wires = [Fr::one(), Fr::from(33u64), Fr::from(3u64), Fr::from(11u64)];
+    assert_eq!(wtns_from_bytes::<Fr>(&wtns(&wires)).unwrap(), wires);
+
+    let snarkjs = SnarkJSVK::<Bn254>::load(resources.join("circom/verification_key.json")).unwrap();
+    let zkey = FflonkZKey::<Bn254>::from_bytes(&zkey_header(&snarkjs), false).unwrap();
+    assert_eq!(zkey.vk, VerificationKey::from(snarkjs));
+
+    let ptau = Ptau::<Bn254>::from_bytes(&ptau(Fr::from(5u64)), 3).unwrap();
+    ptau.check(&mut ark_std::test_rng()).unwrap();
+}
@@ -0,0 +1,88 @@
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{One, UniformRand};
+use ark_fflonk_verifier::lookup::{LookupArgument, LookupArithmetization, LookupProof};
+use ark_fflonk_verifier::mock::multiplier2_lookup;
+use ark_fflonk_verifier::prover::lookup::{fflonk_lookup_prover, LookupProvingKey};
+use ark_fflonk_verifier::prover::Srs;
+use ark_fflonk_verifier::transcript::Keccak256TranscriptHash;
+use ark_fflonk_verifier::utils::load_public_input;
+use ark_fflonk_verifier::verifier::fflonk_lookup_verifier;
+use ark_fflonk_verifier::vk::{SnarkJSVK, VerificationKey};
+use std::path::{Path, PathBuf};
+
+// The fixtures under resources/lookup/ are proven by `generate_lookup_fixture` over a test SRS,
+// see resources/lookup/README.md.
+
+// cargo test --features test-utils lookup_fflonk_proof_verifier  -- --nocapture
+#[test]
+fn lookup_fflonk_proof_verifier() {
+    let path = fixture_dir();
+
+    let vk: VerificationKey = SnarkJSVK::load(path.join("verification_key.json"))
+        .unwrap()
+        .into();
+    let proof = LookupProof::<Bn254>::load(path.join("proof.json")).unwrap();
+    let pubs = load_public_input(path.join("public.json")).unwrap();
+    let lookup: LookupArgument =
+        serde_json::from_str(&std::fs::read_to_string(path.join("lookup.json")).unwrap()).unwrap();
+    let arithmetization = LookupArithmetization::vanilla(&vk, lookup.wire);
+
+    assert!(fflonk_lookup_verifier::<_, Keccak256TranscriptHash>(
+        &arithmetization,
+        &vk,
+        &proof,
+        &pubs
+    )
+    .unwrap());
+    assert!(!fflonk_lookup_verifier::<_, Keccak256TranscriptHash>(
+        &arithmetization,
+        &vk,
+        &proof,
+        &(pubs + Fr::one())
+    )
+    .unwrap());
+
+    // the same proof doesn't pass as a lookup into another wire.
+    let other_wire = LookupArithmetization::vanilla(&vk, (lookup.wire + 1) % 3);
+    assert!(
+        !fflonk_lookup_verifier::<_, Keccak256TranscriptHash>(&other_wire, &vk, &proof, &pubs)
+            .unwrap()
+    );
+}
+
+// Regenerate the fixtures:
+//      cargo test --features test-utils generate_lookup_fixture -- --ignored
+#[test]
+#[ignore]
+fn generate_lookup_fixture() {
+    let rng = &mut ark_std::test_rng();
+    let (arithmetization, circuit, witness) = multiplier2_lookup::<Bn254>();
+    let srs = Srs::<Bn254>::from_trapdoor(Fr::rand(rng), 32 << circuit.gate.power);
+    let pk = LookupProvingKey::new(arithmetization, &circuit, &srs).unwrap();
+    let pub_input = Fr::from(33u64);
+    let proof =
+        fflonk_lookup_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &pub_input, rng)
+            .unwrap();
+
+    let path = fixture_dir();
+    std::fs::create_dir_all(&path).unwrap();
+
+    let mut vk_json = serde_json::to_value(SnarkJSVK::from(&pk.gate.vk)).unwrap();
+    let vk_fields = vk_json.as_object_mut().unwrap();
+    vk_fields.insert("protocol".to_string(), "fflonk".into());
+    vk_fields.insert("curve".to_string(), "bn128".into());
+    vk_fields.insert("nPublic".to_string(), 1.into());
+    write_json(&path.join("verification_key.json"), &vk_json);
+    write_json(&path.join("proof.json"), &proof);
+    write_json(&path.join("public.json"), &[pub_input.to_string()]);
+    write_json(&path.join("lookup.json"), &pk.arithmetization.lookup);
+}
+
+fn fixture_dir() -> PathBuf {
+    std::env::current_dir().unwrap().join("resources/lookup/")
+}
+
+fn write_json<T: serde::Serialize + ?Sized>(path: &Path, value: &T) {
+    let json = serde_json::to_string_pretty(value).unwrap();
+    std::fs::write(path, json + "\n").unwrap();
+}
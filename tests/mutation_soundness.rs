@@ -0,0 +1,219 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::UniformRand;
+use ark_fflonk_verifier::challenge::Challenges;
+use ark_fflonk_verifier::curve::SWPoint;
+use ark_fflonk_verifier::mock::{multiplier2, MOCK_PROOF_DATA, MOCK_PUB_INPUT};
+use ark_fflonk_verifier::mutation::{mutation_report, MutationReport};
+use ark_fflonk_verifier::pairing::{
+    ArkworksBackend, EvmPrecompileBackend, OnProvingPairingsBackend,
+};
+use ark_fflonk_verifier::proof::Proof;
+use ark_fflonk_verifier::prover::{fflonk_prover, Srs};
+use ark_fflonk_verifier::setup::fflonk_setup;
+use ark_fflonk_verifier::simulator::Simulator;
+use ark_fflonk_verifier::transcript::{
+    Blake3TranscriptHash, FiatShamirProfile, Keccak256TranscriptHash, TranscriptHash,
+};
+use ark_fflonk_verifier::utils::load_public_input;
+use ark_fflonk_verifier::verifier::{
+    fflonk_designated_verifier, fflonk_verifier, fflonk_verifier_with_backend,
+    fflonk_verifier_with_profile,
+};
+use ark_fflonk_verifier::vk::{SnarkJSVK, VerificationKey};
+use std::path::Path;
+use std::str::FromStr;
+
+// Every element of the proof, field of the key and the public input is perturbed in turn: each
+// mutant must be rejected, without a panic. The reports are printed with:
+//      cargo test --features test-utils mutation_soundness -- --nocapture
+
+fn assert_sound(name: &str, report: MutationReport) {
+    println!("{name}: {report}");
+    assert!(report.is_sound(), "{name}: {report}");
+}
+
+fn load<E: Pairing>(dir: &Path) -> (VerificationKey<E>, Proof<E>, E::ScalarField)
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+    E::G2: SWPoint,
+{
+    let vk = SnarkJSVK::<E>::load(dir.join("verification_key.json"))
+        .unwrap()
+        .into();
+    let proof = Proof::<E>::load(dir.join("proof.json")).unwrap();
+    let pub_input = load_public_input(dir.join("public.json")).unwrap();
+    (vk, proof, pub_input)
+}
+
+fn verifier<E: Pairing, T: TranscriptHash>(
+) -> impl Fn(&VerificationKey<E>, &Proof<E>, &E::ScalarField) -> bool {
+    |vk, proof, pub_input| {
+        fflonk_verifier_with_backend::<E, T, _>(&ArkworksBackend, vk, proof, pub_input)
+    }
+}
+
+// cargo test --features test-utils mutation_soundness_snarkjs -- --nocapture
+#[test]
+fn mutation_soundness_snarkjs() {
+    let vk = VerificationKey::default();
+    let proof = Proof::construct(MOCK_PROOF_DATA.to_vec());
+    let pub_input = Fr::from_str(MOCK_PUB_INPUT).unwrap();
+    let report = mutation_report(
+        &vk,
+        &proof,
+        &pub_input,
+        verifier::<_, Keccak256TranscriptHash>(),
+    );
+    assert_sound("mock", report);
+
+    let resources = std::env::current_dir().unwrap().join("resources");
+    let (vk, proof, pub_input) = load::<Bn254>(&resources.join("circom"));
+    let report = mutation_report(
+        &vk,
+        &proof,
+        &pub_input,
+        verifier::<_, Keccak256TranscriptHash>(),
+    );
+    assert_sound("circom", report);
+
+    let (vk, proof, pub_input) = load::<Bn254>(&resources.join("circom-blake3"));
+    let report = mutation_report(
+        &vk,
+        &proof,
+        &pub_input,
+        verifier::<_, Blake3TranscriptHash>(),
+    );
+    assert_sound("circom-blake3", report);
+
+    let (vk, proof, pub_input) = load::<Bn254>(&resources.join("trapdoor/bn254"));
+    let report = mutation_report(
+        &vk,
+        &proof,
+        &pub_input,
+        verifier::<_, Keccak256TranscriptHash>(),
+    );
+    assert_sound("trapdoor/bn254", report);
+
+    let (vk, proof, pub_input) = load::<Bls12_381>(&resources.join("trapdoor/bls12-381"));
+    let report = mutation_report(
+        &vk,
+        &proof,
+        &pub_input,
+        verifier::<_, Keccak256TranscriptHash>(),
+    );
+    assert_sound("trapdoor/bls12-381", report);
+
+    let (vk, proof, pub_input) = load::<Bls12_381>(&resources.join("bls12-381"));
+    let report = mutation_report(
+        &vk,
+        &proof,
+        &pub_input,
+        verifier::<_, Keccak256TranscriptHash>(),
+    );
+    assert_sound("bls12-381", report);
+}
+
+// cargo test --features test-utils mutation_soundness_backends -- --nocapture
+#[test]
+fn mutation_soundness_backends() {
+    let rng = &mut ark_std::test_rng();
+    let trapdoor = Fr::rand(rng);
+    let simulator = Simulator::<Bn254>::new(4, trapdoor, rng).unwrap();
+    let vk = &simulator.vk;
+    let pub_input = Fr::rand(rng);
+    let proof = simulator
+        .simulate::<Keccak256TranscriptHash, _>(&pub_input, rng)
+        .unwrap();
+
+    let report = mutation_report(vk, &proof, &pub_input, |vk, proof, pub_input| {
+        fflonk_verifier::<Keccak256TranscriptHash>(vk, proof, pub_input, true)
+    });
+    assert_sound("recursive", report);
+
+    let report = mutation_report(vk, &proof, &pub_input, |vk, proof, pub_input| {
+        fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+            &OnProvingPairingsBackend,
+            vk,
+            proof,
+            pub_input,
+        )
+    });
+    assert_sound("on-proving-pairings", report);
+
+    let report = mutation_report(vk, &proof, &pub_input, |vk, proof, pub_input| {
+        fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+            &EvmPrecompileBackend,
+            vk,
+            proof,
+            pub_input,
+        )
+    });
+    assert_sound("evm", report);
+
+    // the backend doesn't read X_2 and [1]_2, whose mutants no longer match the trapdoor
+    let report = mutation_report(vk, &proof, &pub_input, |vk, proof, pub_input| {
+        fflonk_designated_verifier::<_, Keccak256TranscriptHash>(trapdoor, vk, proof, pub_input)
+            .unwrap_or(false)
+    });
+    assert_sound("designated", report);
+}
+
+// cargo test --features test-utils mutation_soundness_strong_profile -- --nocapture
+#[test]
+fn mutation_soundness_strong_profile() {
+    let rng = &mut ark_std::test_rng();
+    let simulator = Simulator::<Bn254>::new(3, Fr::rand(rng), rng).unwrap();
+    let pub_input = Fr::rand(rng);
+    let proof = simulator.random_proof(rng);
+    let profile = FiatShamirProfile::Strong;
+    let challenges = Challenges::compute_with_profile::<Keccak256TranscriptHash>(
+        profile,
+        &simulator.vk,
+        &proof,
+        &pub_input,
+    );
+    let proof = simulator
+        .complete_with_challenges(proof, &pub_input, &challenges)
+        .unwrap();
+
+    let report = mutation_report(&simulator.vk, &proof, &pub_input, |vk, proof, pub_input| {
+        fflonk_verifier_with_profile::<Keccak256TranscriptHash>(
+            profile, vk, proof, pub_input, false,
+        )
+    });
+    // the strong profile binds the whole key, -w4 and -w8 included
+    assert_eq!(report.equivalent.len(), 1);
+    assert_sound("strong", report);
+}
+
+// cargo test --features test-utils mutation_soundness_prover -- --nocapture
+#[test]
+fn mutation_soundness_prover() {
+    // c = a·b on the signals [1, c, a, b], over an SRS of other generators than the standard ones
+    let circuit = multiplier2::<Fr>();
+    let rng = &mut ark_std::test_rng();
+    let g1 = (G1Affine::generator() * Fr::rand(rng)).into_affine();
+    let g2 = (G2Affine::generator() * Fr::rand(rng)).into_affine();
+    let srs = Srs::<Bn254>::from_trapdoor_with_generators(
+        Fr::rand(rng),
+        Srs::<Bn254>::size_for(3),
+        g1,
+        g2,
+    );
+    let pk = fflonk_setup(&circuit, &srs).unwrap();
+    let (a, b) = (Fr::from(3u64), Fr::from(11u64));
+    let witness = circuit.witness(&[Fr::from(1u64), a * b, a, b]).unwrap();
+    let proof =
+        fflonk_prover::<_, Keccak256TranscriptHash, _>(&pk, &srs, &witness, &(a * b), rng).unwrap();
+
+    let report = mutation_report(
+        &pk.vk,
+        &proof,
+        &(a * b),
+        verifier::<_, Keccak256TranscriptHash>(),
+    );
+    assert_sound("prover", report);
+}
@@ -0,0 +1,156 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{One, UniformRand, Zero};
+use ark_fflonk_verifier::curve::SWPoint;
+use ark_fflonk_verifier::pairing::ArkworksBackend;
+use ark_fflonk_verifier::proof::{Evaluations, Polynomials, Proof};
+use ark_fflonk_verifier::simulator::Simulator;
+use ark_fflonk_verifier::transcript::Keccak256TranscriptHash;
+use ark_fflonk_verifier::utils::load_public_input;
+use ark_fflonk_verifier::verifier::fflonk_verifier_with_backend;
+use ark_fflonk_verifier::vk::{Omega, SnarkJSVK, VerificationKey};
+use std::path::{Path, PathBuf};
+
+// The fixtures under resources/trapdoor/ are not produced by snarkjs but by `generate_fixture`,
+// which knows the trapdoor x of X_2 = x·[1]_2, see resources/trapdoor/README.md.
+
+// cargo test --features test-utils trapdoor_fflonk_proof_verifier  -- --nocapture
+#[test]
+fn trapdoor_fflonk_proof_verifier() {
+    trapdoor_fflonk_proof_verifier_for::<Bn254>("bn254");
+    trapdoor_fflonk_proof_verifier_for::<Bls12_381>("bls12-381");
+}
+
+// The proofs of the other curves than BN254 go through `fflonk_verifier_with_backend`: the
+// recursive verifier proves the pairing with on-proving-pairings, which is BN254-only.
+fn trapdoor_fflonk_proof_verifier_for<E: Pairing>(curve: &str)
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+    E::G2: SWPoint,
+{
+    let path = fixture_dir(curve);
+
+    let snarkjs_vk = SnarkJSVK::<E>::load(path.join("verification_key.json")).unwrap();
+    let vk: VerificationKey<E> = snarkjs_vk.into();
+    let proof = Proof::<E>::load(path.join("proof.json")).unwrap();
+    let pubs = load_public_input(path.join("public.json")).unwrap();
+
+    assert_eq!(vk.omega, Omega::new(vk.power).unwrap());
+    assert!(
+        fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &proof,
+            &pubs
+        )
+    );
+    assert!(
+        !fflonk_verifier_with_backend::<_, Keccak256TranscriptHash, _>(
+            &ArkworksBackend,
+            &vk,
+            &proof,
+            &(pubs + E::ScalarField::one())
+        )
+    );
+}
+
+// Regenerate the fixtures:
+//      cargo test --features test-utils generate_trapdoor_fixtures -- --ignored
+#[test]
+#[ignore]
+fn generate_trapdoor_fixtures() {
+    generate_fixture::<Bn254>("bn254", "bn128");
+    generate_fixture::<Bls12_381>("bls12-381", "bls12381");
+}
+
+fn fixture_dir(curve: &str) -> PathBuf {
+    std::env::current_dir()
+        .unwrap()
+        .join("resources/trapdoor/")
+        .join(curve)
+}
+
+// Forge a proof which passes the verifier with the trapdoor x: pick C0, C1, C2, W1 and the
+// evaluations at random, then `Simulator::complete` sets the inverse and solves W2.
+fn generate_fixture<E: Pairing>(curve: &str, snarkjs_curve: &str)
+where
+    E::G1: SWPoint<BaseField = E::BaseField>,
+    E::G2: SWPoint,
+{
+    let rng = &mut ark_std::test_rng();
+    let x = E::ScalarField::rand(rng);
+
+    let power = 3;
+    let omega = Omega::<E>::new(power).unwrap();
+    let snarkjs_vk = SnarkJSVK::<E> {
+        power,
+        k1: E::ScalarField::from(2u64),
+        k2: E::ScalarField::from(3u64),
+        w: omega.w,
+        w3: omega.w3,
+        w4: omega.w4,
+        w8: omega.w8_1,
+        wr: omega.wr,
+        x2: (E::G2Affine::generator() * x).into_affine().into_group(),
+        c0: random_g1::<E>(rng),
+    };
+    let mut vk_json = serde_json::to_value(&snarkjs_vk).unwrap();
+    let vk: VerificationKey<E> = snarkjs_vk.into();
+
+    let proof = Proof::<E> {
+        polynomials: Polynomials {
+            c1: random_g1::<E>(rng),
+            c2: random_g1::<E>(rng),
+            w1: random_g1::<E>(rng),
+            w2: E::G1::zero(),
+        },
+        evaluations: Evaluations {
+            ql: E::ScalarField::rand(rng),
+            qr: E::ScalarField::rand(rng),
+            qm: E::ScalarField::rand(rng),
+            qo: E::ScalarField::rand(rng),
+            qc: E::ScalarField::rand(rng),
+            s1: E::ScalarField::rand(rng),
+            s2: E::ScalarField::rand(rng),
+            s3: E::ScalarField::rand(rng),
+            a: E::ScalarField::rand(rng),
+            b: E::ScalarField::rand(rng),
+            c: E::ScalarField::rand(rng),
+            z: E::ScalarField::rand(rng),
+            zw: E::ScalarField::rand(rng),
+            t1w: E::ScalarField::rand(rng),
+            t2w: E::ScalarField::rand(rng),
+            inv: E::ScalarField::zero(),
+        },
+    };
+    let pub_input = E::ScalarField::from(33u64);
+    let proof = Simulator::from_vk(vk, x)
+        .unwrap()
+        .complete::<Keccak256TranscriptHash>(proof, &pub_input)
+        .unwrap();
+
+    let path = fixture_dir(curve);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let vk_fields = vk_json.as_object_mut().unwrap();
+    vk_fields.insert("protocol".to_string(), "fflonk".into());
+    vk_fields.insert("curve".to_string(), snarkjs_curve.into());
+    vk_fields.insert("nPublic".to_string(), 1.into());
+    write_json(&path.join("verification_key.json"), &vk_json);
+    write_json(&path.join("proof.json"), &proof);
+    write_json(&path.join("public.json"), &[pub_input.to_string()]);
+}
+
+fn random_g1<E: Pairing>(rng: &mut impl ark_std::rand::Rng) -> E::G1 {
+    // normalize, so that the point is written as [x, y, 1]
+    (E::G1Affine::generator() * E::ScalarField::rand(rng))
+        .into_affine()
+        .into_group()
+}
+
+fn write_json<T: serde::Serialize + ?Sized>(path: &Path, value: &T) {
+    let json = serde_json::to_string_pretty(value).unwrap();
+    std::fs::write(path, json + "\n").unwrap();
+}